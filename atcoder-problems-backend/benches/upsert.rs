@@ -0,0 +1,109 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use atcoder_problems_backend::crawler_utils::upsert_submissions;
+use crawler::Submission;
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Schema};
+use tokio::runtime::Runtime;
+
+/// Postgres database with `postgres/schema.sql` applied. Its submissions are
+/// truncated, so never point it at a database whose data matters.
+const POSTGRES_URL_ENV: &str = "BENCH_POSTGRES_URL";
+
+/// A page of the submissions list, and a backfill's worth of pages
+const BATCH_SIZES: &[usize] = &[20, 1000];
+
+fn submission(id: i64) -> Submission {
+    Submission {
+        id,
+        epoch_second: 1_700_000_000 + id,
+        problem_id: "abc001_a".to_string(),
+        contest_id: "abc001".to_string(),
+        user: format!("user{}", id % 1000),
+        language: "Rust".to_string(),
+        score: 100.0,
+        code_length: 100,
+        result: "AC".to_string(),
+        execution_time: Some(1),
+        memory: Some(1024),
+    }
+}
+
+fn page(first_id: i64, size: usize) -> Vec<Submission> {
+    (first_id..first_id + size as i64).map(submission).collect()
+}
+
+async fn setup_sqlite() -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    let builder = db.get_database_backend();
+    let schema = Schema::new(builder);
+    let stmt = schema.create_table_from_entity(sql_entities::submissions::Entity);
+    db.execute(builder.build(&stmt)).await.unwrap();
+    let stmt = schema.create_table_from_entity(sql_entities::submission_history::Entity);
+    db.execute(builder.build(&stmt)).await.unwrap();
+    db
+}
+
+async fn setup_postgres(url: &str) -> DatabaseConnection {
+    let db = Database::connect(url).await.unwrap();
+    db.execute_unprepared("TRUNCATE submissions, submission_history")
+        .await
+        .unwrap();
+    db
+}
+
+/// Measures upserts of new submissions and of submissions that are already
+/// stored unchanged, which is what most pages of an incremental crawl are.
+fn bench_upsert_submissions(
+    c: &mut Criterion,
+    backend: &str,
+    rt: &Runtime,
+    db: &DatabaseConnection,
+) {
+    let mut next_id = 1;
+    for &size in BATCH_SIZES {
+        let mut group = c.benchmark_group(format!("upsert_submissions/{backend}"));
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_function(BenchmarkId::new("new", size), |b| {
+            b.iter_batched(
+                || {
+                    let submissions = page(next_id, size);
+                    next_id += size as i64;
+                    submissions
+                },
+                |submissions| rt.block_on(upsert_submissions(db, submissions)).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+
+        let stored = page(next_id, size);
+        next_id += size as i64;
+        rt.block_on(upsert_submissions(db, stored.clone())).unwrap();
+        group.bench_function(BenchmarkId::new("unchanged", size), |b| {
+            b.iter_batched(
+                || stored.clone(),
+                |submissions| rt.block_on(upsert_submissions(db, submissions)).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+        group.finish();
+    }
+}
+
+fn upsert_benchmarks(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    let db = rt.block_on(setup_sqlite());
+    bench_upsert_submissions(c, "sqlite", &rt, &db);
+
+    match std::env::var(POSTGRES_URL_ENV) {
+        Ok(url) => {
+            let db = rt.block_on(setup_postgres(&url));
+            bench_upsert_submissions(c, "postgres", &rt, &db);
+        }
+        Err(_) => eprintln!("Skipping Postgres: {POSTGRES_URL_ENV} is not set"),
+    }
+}
+
+criterion_group!(benches, upsert_benchmarks);
+criterion_main!(benches);
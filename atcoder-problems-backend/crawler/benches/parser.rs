@@ -0,0 +1,52 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::hint::black_box;
+
+use crawler::{
+    parse_contests_archive_html, parse_permanent_contests_html, parse_submission_detail_html,
+    parse_submissions_html, parse_task_point_html, parse_tasks_html,
+};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+// The same fixtures as `tests/test_parser.rs`
+const SUBMISSIONS_HTML: &str = include_str!("../tests/assets/submissions.html");
+const TASKS_HTML: &str = include_str!("../tests/assets/tasks.html");
+const TASKS_ABC308_HTML: &str = include_str!("../tests/assets/tasks_abc308.html");
+const CONTESTS_ARCHIVE_HTML: &str = include_str!("../tests/assets/contests_archive.html");
+const CONTESTS_PERMANENT_HTML: &str = include_str!("../tests/assets/contests_permanent.html");
+const TASK_HTML: &str = include_str!("../tests/assets/task_abc399_a.html");
+const SUBMISSION_DETAIL_HTML: &str = include_str!("../tests/assets/submission_detail.html");
+
+fn bench_parser(c: &mut Criterion, name: &str, html: &'static str, parse: impl Fn(&str)) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(html.len() as u64));
+    group.bench_function("parse", |b| b.iter(|| parse(black_box(html))));
+    group.finish();
+}
+
+fn parser_benchmarks(c: &mut Criterion) {
+    bench_parser(c, "submissions", SUBMISSIONS_HTML, |html| {
+        parse_submissions_html(html).unwrap();
+    });
+    bench_parser(c, "tasks", TASKS_HTML, |html| {
+        parse_tasks_html(html, "abc399").unwrap();
+    });
+    bench_parser(c, "tasks_abc308", TASKS_ABC308_HTML, |html| {
+        parse_tasks_html(html, "abc308").unwrap();
+    });
+    bench_parser(c, "contests_archive", CONTESTS_ARCHIVE_HTML, |html| {
+        parse_contests_archive_html(html).unwrap();
+    });
+    bench_parser(c, "contests_permanent", CONTESTS_PERMANENT_HTML, |html| {
+        parse_permanent_contests_html(html).unwrap();
+    });
+    bench_parser(c, "task_point", TASK_HTML, |html| {
+        parse_task_point_html(html).unwrap();
+    });
+    bench_parser(c, "submission_detail", SUBMISSION_DETAIL_HTML, |html| {
+        parse_submission_detail_html(html, 64188418).unwrap();
+    });
+}
+
+criterion_group!(benches, parser_benchmarks);
+criterion_main!(benches);
@@ -1,20 +1,42 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
-use reqwest::Client;
-use reqwest::header::{HeaderMap, HeaderValue};
-use serde_json::Value;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::{Client, Response, StatusCode};
 
 use crate::error::CrawlerError;
+use crate::fixture::fixture_path;
 use crate::parser::{
-    parse_contests_archive_html, parse_permanent_contests_html, parse_submissions_html,
-    parse_tasks_html,
+    is_login_page, parse_contests_archive_html, parse_editorials_html,
+    parse_permanent_contests_html, parse_submission_detail_html, parse_submissions_html,
+    parse_task_point_html, parse_tasks_html, parse_user_profile_html,
+};
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::types::{
+    Contest, Editorial, Problem, RatingHistoryEntry, Standings, Submission, SubmissionDetail,
+    UserProfile,
 };
-use crate::types::{Contest, Problem, Submission};
+
+/// Fraction of unparseable rows above which a submissions page is rejected
+const MAX_SKIPPED_SUBMISSION_RATIO: f64 = 0.2;
+
+/// Page that AtCoder only shows to logged-in users
+const SESSION_CHECK_URL: &str = "https://atcoder.jp/settings";
 
 /// Trait for fetching problems from AtCoder.
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait ProblemFetcher: Send + Sync {
     async fn fetch_problems(&self, contest_id: &str) -> Result<Vec<Problem>, CrawlerError>;
+    /// Fetch the point value from the individual task page
+    async fn fetch_problem_point(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+    ) -> Result<Option<f64>, CrawlerError>;
 }
 
 /// Trait for fetching contests from AtCoder.
@@ -33,12 +55,93 @@ pub trait ContestFetcher: Send + Sync {
     async fn fetch_permanent_contests(&self) -> Result<Vec<Contest>, CrawlerError>;
 }
 
+/// Trait for fetching submissions from AtCoder.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait SubmissionFetcher: Send + Sync {
+    /// Fetch a page of the contest's submission list
+    async fn fetch_submissions(
+        &self,
+        contest_id: &str,
+        page: i32,
+    ) -> Result<Vec<Submission>, CrawlerError>;
+    /// Fetch the judge results from the submission's detail page
+    async fn fetch_submission_detail(
+        &self,
+        contest_id: &str,
+        submission_id: i64,
+    ) -> Result<SubmissionDetail, CrawlerError>;
+    /// Fetch a page of the contest's submission list filtered to one user
+    async fn fetch_user_submissions(
+        &self,
+        contest_id: &str,
+        user_id: &str,
+        page: i32,
+    ) -> Result<Vec<Submission>, CrawlerError>;
+}
+
+/// Trait for fetching contest standings from AtCoder.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait StandingsFetcher: Send + Sync {
+    /// Fetch the standings JSON, or `None` if the contest has no standings
+    async fn fetch_standings(&self, contest_id: &str) -> Result<Option<Standings>, CrawlerError>;
+}
+
+/// Trait for fetching user profiles from AtCoder.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UserFetcher: Send + Sync {
+    /// Fetch the profile page of the user
+    async fn fetch_user_profile(&self, user_id: &str) -> Result<UserProfile, CrawlerError>;
+    /// Fetch the user's results in algorithm contests, oldest first
+    async fn fetch_rating_history(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<RatingHistoryEntry>, CrawlerError>;
+}
+
+/// Trait for fetching editorial links from AtCoder.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait EditorialFetcher: Send + Sync {
+    /// Fetch the editorials listed for the contest's problems
+    async fn fetch_editorials(&self, contest_id: &str) -> Result<Vec<Editorial>, CrawlerError>;
+}
+
+/// Source of the raw pages that the fetchers parse.
+///
+/// Every type implementing it is a [`ProblemFetcher`], [`ContestFetcher`],
+/// [`SubmissionFetcher`], [`StandingsFetcher`], [`UserFetcher`] and
+/// [`EditorialFetcher`], so [`CrawlerClient`] and
+/// [`crate::FixtureClient`] share the URLs and parsers.
+#[async_trait]
+pub trait PageSource: Send + Sync {
+    /// Returns the body of the page at `url`, or [`CrawlerError::NotFound`]
+    /// if there is no such page.
+    async fn fetch_page(&self, url: &str) -> Result<String, CrawlerError>;
+}
+
+/// HTTP client for AtCoder.
+///
+/// Clones share the same rate limiter, so the budget holds across every task
+/// that uses a clone of one client.
+#[derive(Clone)]
 pub struct CrawlerClient {
     client: Client,
+    rate_limiter: Arc<RateLimiter>,
+    record_dir: Option<PathBuf>,
 }
 
 impl CrawlerClient {
     pub fn new(session_cookie: String) -> Result<Self, CrawlerError> {
+        Self::with_rate_limit(session_cookie, RateLimit::default())
+    }
+
+    pub fn with_rate_limit(
+        session_cookie: String,
+        rate_limit: RateLimit,
+    ) -> Result<Self, CrawlerError> {
         let mut headers = HeaderMap::new();
         headers.insert(
             "Cookie",
@@ -49,37 +152,128 @@ impl CrawlerClient {
                 .gzip(true)
                 .default_headers(headers)
                 .build()?,
+            rate_limiter: Arc::new(RateLimiter::new(rate_limit)),
+            record_dir: None,
         })
     }
-}
 
-#[async_trait]
-impl ProblemFetcher for CrawlerClient {
-    async fn fetch_problems(&self, contest_id: &str) -> Result<Vec<Problem>, CrawlerError> {
-        let url = format!("https://atcoder.jp/contests/{}/tasks", contest_id);
-        let request = self.client.get(&url);
-        let response = request.send().await?;
+    /// Records every page fetched successfully into `dir`, where a
+    /// [`crate::FixtureClient`] can replay it later.
+    pub fn record_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.record_dir = Some(dir.into());
+        self
+    }
+
+    /// Checks that the session cookie is still logged in, so that a long crawl
+    /// fails at startup with [`CrawlerError::SessionExpired`] instead of
+    /// quietly storing nothing.
+    pub async fn verify_session(&self) -> Result<(), CrawlerError> {
+        self.fetch_body(SESSION_CHECK_URL).await?;
+        Ok(())
+    }
+
+    /// Fetches the body of the page at `url`.
+    ///
+    /// A redirect to the login page or the login form served in place of the
+    /// page means that the session has expired.
+    async fn fetch_body(&self, url: &str) -> Result<String, CrawlerError> {
+        let response = self.get(url).await?;
+        if response.url().path() == "/login" {
+            return Err(CrawlerError::SessionExpired {
+                url: url.to_string(),
+            });
+        }
         if response.status() == 404 {
             return Err(CrawlerError::NotFound);
         }
         if !response.status().is_success() {
             return Err(CrawlerError::HttpError(response.text().await?));
         }
-        let html = response.text().await?;
+        let body = response.text().await?;
+        if is_login_page(&body) {
+            return Err(CrawlerError::SessionExpired {
+                url: url.to_string(),
+            });
+        }
+        Ok(body)
+    }
+
+    /// Sends a GET request once the rate limiter allows it.
+    ///
+    /// A 429 response pauses every request to the same host for as long as
+    /// the server asked and is returned as [`CrawlerError::RateLimited`].
+    async fn get(&self, url: &str) -> Result<Response, CrawlerError> {
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| CrawlerError::InvalidConfig(format!("Invalid URL {}: {}", url, e)))?;
+        let host = parsed.host_str().unwrap_or_default().to_string();
+
+        self.rate_limiter.acquire(&host).await;
+        let response = self.client.get(parsed).send().await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| parse_retry_after(value, Utc::now()));
+            tracing::warn!("Rate limited by {}, retry after {:?}", host, retry_after);
+            if let Some(retry_after) = retry_after {
+                self.rate_limiter.pause(&host, retry_after);
+            }
+            return Err(CrawlerError::RateLimited { retry_after });
+        }
+        Ok(response)
+    }
+}
+
+/// Parses a `Retry-After` header, given either in seconds or as an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - now).to_std().ok()
+}
+
+#[async_trait]
+impl PageSource for CrawlerClient {
+    async fn fetch_page(&self, url: &str) -> Result<String, CrawlerError> {
+        let body = self.fetch_body(url).await?;
+        if let Some(dir) = &self.record_dir {
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::write(fixture_path(dir, url), &body).await?;
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl<S: PageSource> ProblemFetcher for S {
+    async fn fetch_problems(&self, contest_id: &str) -> Result<Vec<Problem>, CrawlerError> {
+        let url = format!("https://atcoder.jp/contests/{}/tasks", contest_id);
+        let html = self.fetch_page(&url).await?;
         parse_tasks_html(&html, contest_id)
     }
+
+    async fn fetch_problem_point(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+    ) -> Result<Option<f64>, CrawlerError> {
+        let url = format!(
+            "https://atcoder.jp/contests/{}/tasks/{}",
+            contest_id, problem_id
+        );
+        let html = self.fetch_page(&url).await?;
+        parse_task_point_html(&html)
+    }
 }
 
 #[async_trait]
-impl ContestFetcher for CrawlerClient {
+impl<S: PageSource> ContestFetcher for S {
     async fn fetch_contests(&self, page: u32) -> Result<Vec<Contest>, CrawlerError> {
         let url = format!("https://atcoder.jp/contests/archive?lang=ja&page={}", page);
-        let request = self.client.get(&url);
-        let response = request.send().await?;
-        if !response.status().is_success() {
-            return Err(CrawlerError::HttpError(response.text().await?));
-        }
-        let html = response.text().await?;
+        let html = self.fetch_page(&url).await?;
         parse_contests_archive_html(&html)
     }
 
@@ -92,29 +286,21 @@ impl ContestFetcher for CrawlerClient {
             "https://atcoder.jp/contests/archive?lang=ja&category={}&page={}",
             category, page
         );
-        let request = self.client.get(&url);
-        let response = request.send().await?;
-        if !response.status().is_success() {
-            return Err(CrawlerError::HttpError(response.text().await?));
-        }
-        let html = response.text().await?;
+        let html = self.fetch_page(&url).await?;
         parse_contests_archive_html(&html)
     }
 
     async fn fetch_permanent_contests(&self) -> Result<Vec<Contest>, CrawlerError> {
-        let url = "https://atcoder.jp/contests/?lang=ja";
-        let request = self.client.get(url);
-        let response = request.send().await?;
-        if !response.status().is_success() {
-            return Err(CrawlerError::HttpError(response.text().await?));
-        }
-        let html = response.text().await?;
+        let html = self
+            .fetch_page("https://atcoder.jp/contests/?lang=ja")
+            .await?;
         parse_permanent_contests_html(&html)
     }
 }
 
-impl CrawlerClient {
-    pub async fn fetch_submissions(
+#[async_trait]
+impl<S: PageSource> SubmissionFetcher for S {
+    async fn fetch_submissions(
         &self,
         contest_id: &str,
         page: i32,
@@ -123,32 +309,101 @@ impl CrawlerClient {
             "https://atcoder.jp/contests/{}/submissions?page={}",
             contest_id, page
         );
-        let request = self.client.get(&url);
-        let response = request.send().await?;
-        if response.status() == 404 {
-            return Err(CrawlerError::NotFound);
-        }
-        if !response.status().is_success() {
-            return Err(CrawlerError::HttpError(response.text().await?));
-        }
-        let html = response.text().await?;
-        parse_submissions_html(&html)
+        let html = self.fetch_page(&url).await?;
+        parse_submissions_page(&html, &url)
+    }
+
+    async fn fetch_submission_detail(
+        &self,
+        contest_id: &str,
+        submission_id: i64,
+    ) -> Result<SubmissionDetail, CrawlerError> {
+        let url = format!(
+            "https://atcoder.jp/contests/{}/submissions/{}",
+            contest_id, submission_id
+        );
+        let html = self.fetch_page(&url).await?;
+        parse_submission_detail_html(&html, submission_id)
     }
 
-    pub async fn fetch_standings(&self, contest_id: &str) -> Result<Option<Value>, CrawlerError> {
+    async fn fetch_user_submissions(
+        &self,
+        contest_id: &str,
+        user_id: &str,
+        page: i32,
+    ) -> Result<Vec<Submission>, CrawlerError> {
+        let mut url = reqwest::Url::parse(&format!(
+            "https://atcoder.jp/contests/{}/submissions",
+            contest_id
+        ))
+        .map_err(|e| {
+            CrawlerError::InvalidConfig(format!("Invalid contest {}: {}", contest_id, e))
+        })?;
+        url.query_pairs_mut()
+            .append_pair("f.User", user_id)
+            .append_pair("page", &page.to_string());
+        let html = self.fetch_page(url.as_str()).await?;
+        parse_submissions_page(&html, url.as_str())
+    }
+}
+
+/// Parses a submissions page, rejecting it if too many rows were skipped.
+fn parse_submissions_page(html: &str, url: &str) -> Result<Vec<Submission>, CrawlerError> {
+    let (submissions, report) = parse_submissions_html(html)?;
+    if !report.skipped.is_empty() {
+        tracing::warn!(
+            "Skipped {} of {} submissions of {}: {:?}",
+            report.skipped.len(),
+            report.total(),
+            url,
+            report.skipped
+        );
+    }
+    report.check_skip_ratio(MAX_SKIPPED_SUBMISSION_RATIO)?;
+    Ok(submissions)
+}
+
+#[async_trait]
+impl<S: PageSource> StandingsFetcher for S {
+    async fn fetch_standings(&self, contest_id: &str) -> Result<Option<Standings>, CrawlerError> {
         let url = format!("https://atcoder.jp/contests/{}/standings/json", contest_id);
-        let request = self.client.get(&url);
-        let response = request.send().await?;
-        if response.status() == 404 {
-            tracing::warn!("Standings for contest {} not found", contest_id);
-            return Ok(None);
-        }
-        if !response.status().is_success() {
-            return Err(CrawlerError::HttpError(response.text().await?));
-        }
-        let text = response.text().await?;
-        let value: Value =
+        let text = match self.fetch_page(&url).await {
+            Ok(text) => text,
+            Err(CrawlerError::NotFound) => {
+                tracing::warn!("Standings for contest {} not found", contest_id);
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        let standings: Standings =
             serde_json::from_str(&text).map_err(|_| CrawlerError::JsonParseError { body: text })?;
-        Ok(Some(value))
+        Ok(Some(standings))
+    }
+}
+
+#[async_trait]
+impl<S: PageSource> UserFetcher for S {
+    async fn fetch_user_profile(&self, user_id: &str) -> Result<UserProfile, CrawlerError> {
+        let url = format!("https://atcoder.jp/users/{}", user_id);
+        let html = self.fetch_page(&url).await?;
+        parse_user_profile_html(&html, user_id)
+    }
+
+    async fn fetch_rating_history(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<RatingHistoryEntry>, CrawlerError> {
+        let url = format!("https://atcoder.jp/users/{}/history/json", user_id);
+        let text = self.fetch_page(&url).await?;
+        serde_json::from_str(&text).map_err(|_| CrawlerError::JsonParseError { body: text })
+    }
+}
+
+#[async_trait]
+impl<S: PageSource> EditorialFetcher for S {
+    async fn fetch_editorials(&self, contest_id: &str) -> Result<Vec<Editorial>, CrawlerError> {
+        let url = format!("https://atcoder.jp/contests/{}/editorial", contest_id);
+        let html = self.fetch_page(&url).await?;
+        parse_editorials_html(&html)
     }
 }
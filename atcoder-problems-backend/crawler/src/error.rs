@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
 /// Errors that can occur during parsing
@@ -18,9 +20,83 @@ pub enum CrawlerError {
     #[error("Resource not found (404)")]
     NotFound,
 
+    #[error("Session expired: AtCoder served the login page for {url}")]
+    SessionExpired { url: String },
+
+    #[error("Rate limited (429), retry after {retry_after:?}")]
+    RateLimited { retry_after: Option<Duration> },
+
     #[error("Invalid header value: {0}")]
     InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
 
     #[error("Failed to parse JSON: {body}")]
     JsonParseError { body: String },
+
+    #[error("Invalid standings: {0}")]
+    InvalidStandings(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Skipped {skipped} of {total} rows: {reasons:?}")]
+    TooManySkippedRows {
+        skipped: usize,
+        total: usize,
+        reasons: Vec<String>,
+    },
+
+    #[error("Gave up after {attempts} attempts: {last}")]
+    RetriesExhausted {
+        attempts: u32,
+        last: Box<CrawlerError>,
+    },
+}
+
+impl CrawlerError {
+    /// Returns the name of the variant, e.g. to count errors by kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CrawlerError::ParseError(_) => "ParseError",
+            CrawlerError::SelectorError(_) => "SelectorError",
+            CrawlerError::RequestError(_) => "RequestError",
+            CrawlerError::HttpError(_) => "HttpError",
+            CrawlerError::NotFound => "NotFound",
+            CrawlerError::SessionExpired { .. } => "SessionExpired",
+            CrawlerError::RateLimited { .. } => "RateLimited",
+            CrawlerError::InvalidHeaderValue(_) => "InvalidHeaderValue",
+            CrawlerError::JsonParseError { .. } => "JsonParseError",
+            CrawlerError::InvalidStandings(_) => "InvalidStandings",
+            CrawlerError::InvalidConfig(_) => "InvalidConfig",
+            CrawlerError::IoError(_) => "IoError",
+            CrawlerError::TooManySkippedRows { .. } => "TooManySkippedRows",
+            CrawlerError::RetriesExhausted { .. } => "RetriesExhausted",
+        }
+    }
+
+    /// Returns true if the error may go away when the request is sent again.
+    ///
+    /// Missing resources, errors caused by our own configuration and pages whose
+    /// layout we no longer understand will fail the same way on every attempt,
+    /// so retrying them only delays the caller.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            CrawlerError::RequestError(_)
+            | CrawlerError::HttpError(_)
+            | CrawlerError::RateLimited { .. } => true,
+            CrawlerError::NotFound
+            | CrawlerError::ParseError(_)
+            | CrawlerError::JsonParseError { .. }
+            | CrawlerError::SessionExpired { .. }
+            | CrawlerError::SelectorError(_)
+            | CrawlerError::InvalidHeaderValue(_)
+            | CrawlerError::InvalidStandings(_)
+            | CrawlerError::InvalidConfig(_)
+            | CrawlerError::IoError(_)
+            | CrawlerError::TooManySkippedRows { .. }
+            | CrawlerError::RetriesExhausted { .. } => false,
+        }
+    }
 }
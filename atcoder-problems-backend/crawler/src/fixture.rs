@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use crate::client::PageSource;
+use crate::error::CrawlerError;
+use crate::parser::is_login_page;
+
+/// Serves pages recorded by [`crate::CrawlerClient::record_to`] from a
+/// directory instead of fetching them from AtCoder.
+///
+/// A page that was not recorded is reported as [`CrawlerError::NotFound`], the
+/// same way AtCoder answers a request for a page that does not exist, and a
+/// recorded login page as [`CrawlerError::SessionExpired`].
+#[derive(Debug, Clone)]
+pub struct FixtureClient {
+    dir: PathBuf,
+}
+
+impl FixtureClient {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the file the page at `url` is recorded in.
+    pub fn path_for(&self, url: &str) -> PathBuf {
+        fixture_path(&self.dir, url)
+    }
+}
+
+#[async_trait]
+impl PageSource for FixtureClient {
+    async fn fetch_page(&self, url: &str) -> Result<String, CrawlerError> {
+        match tokio::fs::read_to_string(self.path_for(url)).await {
+            Ok(body) if is_login_page(&body) => Err(CrawlerError::SessionExpired {
+                url: url.to_string(),
+            }),
+            Ok(body) => Ok(body),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::debug!("No fixture recorded for {}", url);
+                Err(CrawlerError::NotFound)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Longest file name most file systems accept (`NAME_MAX`), in bytes.
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+/// Returns the file in `dir` that the page at `url` is recorded in.
+///
+/// The URL without its scheme becomes the file name, with every character
+/// other than ASCII alphanumerics, `-`, `_` and `.` percent-encoded, so that
+/// each URL maps to its own file directly inside `dir`. A name longer than
+/// [`MAX_FILE_NAME_LENGTH`], e.g. for submissions filtered by user, task and
+/// page, is cut short and ends with `~` and a hash of the whole URL instead.
+pub(crate) fn fixture_path(dir: &Path, url: &str) -> PathBuf {
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    let mut name = String::with_capacity(url.len());
+    for byte in url.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+            name.push(char::from(byte));
+        } else {
+            name.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    if name.len() > MAX_FILE_NAME_LENGTH {
+        let hash = format!("~{:016x}", fnv1a(url.as_bytes()));
+        // The name is ASCII, so it can be cut at any byte.
+        name.truncate(MAX_FILE_NAME_LENGTH - hash.len());
+        name.push_str(&hash);
+    }
+    dir.join(name)
+}
+
+/// 64-bit FNV-1a hash, which unlike the hashers of `std` is the same on every
+/// platform and Rust version, as recorded file names must be.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
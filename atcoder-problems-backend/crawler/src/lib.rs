@@ -1,12 +1,28 @@
 mod client;
 mod error;
+mod fixture;
 mod parser;
+mod rate_limit;
+mod retry;
+mod stats;
 mod types;
 
-pub use client::{ContestFetcher, CrawlerClient, ProblemFetcher};
+pub use client::{
+    ContestFetcher, CrawlerClient, EditorialFetcher, PageSource, ProblemFetcher, StandingsFetcher,
+    SubmissionFetcher, UserFetcher,
+};
 pub use error::CrawlerError;
+pub use fixture::FixtureClient;
 pub use parser::{
-    parse_contests_archive_html, parse_permanent_contests_html, parse_submissions_html,
-    parse_tasks_html,
+    is_login_page, parse_contests_archive_html, parse_editorials_html,
+    parse_permanent_contests_html, parse_submission_detail_html, parse_submissions_html,
+    parse_task_point_html, parse_tasks_html, parse_user_profile_html,
+};
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use retry::{RetryPolicy, RetryingFetcher};
+pub use stats::FetchStats;
+pub use types::{
+    Contest, ContestType, Editorial, JudgeCase, ParseReport, Problem, RatedRange,
+    RatingHistoryEntry, SkippedRow, Standings, StandingsData, Submission, SubmissionDetail,
+    TaskInfo, TaskResult, TotalResult, UserProfile, is_pending_result,
 };
-pub use types::{Contest, Problem, Submission};
@@ -1,12 +1,92 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
 use chrono::DateTime;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 
 use crate::error::CrawlerError;
-use crate::types::{Contest, Problem, Submission};
+use crate::types::{
+    Contest, Editorial, JudgeCase, ParseReport, Problem, SkippedRow, Submission, SubmissionDetail,
+    UserProfile,
+};
 
 /// Duration for permanent contests (100 years in seconds)
 const PERMANENT_CONTEST_DURATION_SECOND: i64 = 100 * 365 * 24 * 3600;
 
+macro_rules! selectors {
+    ($($name:ident: $css:literal,)*) => {
+        /// CSS selectors used by the parsers
+        struct Selectors {
+            $($name: Selector,)*
+        }
+
+        impl Selectors {
+            fn new() -> Result<Self, String> {
+                Ok(Self {
+                    $($name: Selector::parse($css).map_err(|e| format!("{}: {}", $css, e))?,)*
+                })
+            }
+        }
+    };
+}
+
+selectors! {
+    a: "a",
+    table: "table",
+    tbody: "tbody",
+    tr: "tr",
+    th: "th",
+    td: "td",
+    time: "time",
+    span: "span",
+    var: "var",
+    h4: "h4",
+    body_row: "tbody tr",
+    header_cell: "thead th",
+    bordered_table: "table.table-bordered",
+    permanent_contests: "#contest-table-permanent",
+    task_row: "table.table-bordered tbody tr",
+    task_index: "td.text-center.no-break a",
+    task_name: "td:nth-child(2) a",
+    task_time_limit: "td:nth-child(3)",
+    task_memory_limit: "td:nth-child(4)",
+    task_statement_paragraph: "#task-statement p",
+    submission_details_link: "a.submission-details-link",
+    login_password: "form input[name=\"password\"]",
+    img: "img",
+    profile_row: "table.dl-table tr",
+    li: "li",
+    label: "span.label",
+    editorial_heading: "h3, h4",
+    task_link: "a[href*=\"/tasks/\"]",
+}
+
+/// Selectors are compiled once on first use rather than on every call, which
+/// matters when crawling millions of pages.
+static SELECTORS: LazyLock<Result<Selectors, String>> = LazyLock::new(Selectors::new);
+
+fn selectors() -> Result<&'static Selectors, CrawlerError> {
+    SELECTORS
+        .as_ref()
+        .map_err(|e| CrawlerError::SelectorError(e.clone()))
+}
+
+/// Returns true if the page is AtCoder's login form, which is served in place
+/// of the requested page once the session cookie has expired.
+pub fn is_login_page(html_content: &str) -> bool {
+    // Skip building the DOM for the pages that cannot contain the form.
+    if !html_content.contains("password") {
+        return false;
+    }
+    let Ok(selectors) = selectors() else {
+        return false;
+    };
+    Html::parse_document(html_content)
+        .select(&selectors.login_password)
+        .next()
+        .is_some()
+}
+
 /// Parses the contest archive page and extracts contest information
 ///
 /// # Arguments
@@ -17,25 +97,17 @@ const PERMANENT_CONTEST_DURATION_SECOND: i64 = 100 * 365 * 24 * 3600;
 ///
 /// A Result containing a vector of Contest structs or a CrawlerError
 pub fn parse_contests_archive_html(html_content: &str) -> Result<Vec<Contest>, CrawlerError> {
+    let selectors = selectors()?;
     let document = Html::parse_document(html_content);
 
-    let tbody_selector =
-        Selector::parse("tbody").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-    let tr_selector =
-        Selector::parse("tr").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-    let td_selector =
-        Selector::parse("td").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-    let a_selector =
-        Selector::parse("a").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-
-    let tbody = match document.select(&tbody_selector).next() {
+    let tbody = match document.select(&selectors.tbody).next() {
         Some(tbody) => tbody,
         None => return Ok(Vec::new()), // Empty page, no contests
     };
 
     let mut contests = Vec::new();
-    for tr in tbody.select(&tr_selector) {
-        let mut tds = tr.select(&td_selector);
+    for tr in tbody.select(&selectors.tr) {
+        let mut tds = tr.select(&selectors.td);
 
         // First td: start time
         let start_td = tds
@@ -52,7 +124,7 @@ pub fn parse_contests_archive_html(html_content: &str) -> Result<Vec<Contest>, C
             .next()
             .ok_or_else(|| CrawlerError::ParseError("No contest td".to_string()))?;
         let contest_a = contest_td
-            .select(&a_selector)
+            .select(&selectors.a)
             .next()
             .ok_or_else(|| CrawlerError::ParseError("No contest link".to_string()))?;
         let contest_title = contest_a.text().collect::<String>();
@@ -113,39 +185,29 @@ pub fn parse_contests_archive_html(html_content: &str) -> Result<Vec<Contest>, C
 ///
 /// A Result containing a vector of Contest structs or a CrawlerError
 pub fn parse_permanent_contests_html(html_content: &str) -> Result<Vec<Contest>, CrawlerError> {
+    let selectors = selectors()?;
     let document = Html::parse_document(html_content);
 
-    let permanent_selector = Selector::parse("#contest-table-permanent")
-        .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-    let tbody_selector =
-        Selector::parse("tbody").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-    let tr_selector =
-        Selector::parse("tr").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-    let td_selector =
-        Selector::parse("td").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-    let a_selector =
-        Selector::parse("a").map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-
     let permanent_div = document
-        .select(&permanent_selector)
+        .select(&selectors.permanent_contests)
         .next()
         .ok_or_else(|| CrawlerError::ParseError("No permanent contest table found".to_string()))?;
 
     let tbody = permanent_div
-        .select(&tbody_selector)
+        .select(&selectors.tbody)
         .next()
         .ok_or_else(|| CrawlerError::ParseError("No tbody found".to_string()))?;
 
     let mut contests = Vec::new();
-    for tr in tbody.select(&tr_selector) {
-        let mut tds = tr.select(&td_selector);
+    for tr in tbody.select(&selectors.tr) {
+        let mut tds = tr.select(&selectors.td);
 
         // First td: contest name and link
         let contest_td = tds
             .next()
             .ok_or_else(|| CrawlerError::ParseError("No contest td".to_string()))?;
         let contest_a = contest_td
-            .select(&a_selector)
+            .select(&selectors.a)
             .next()
             .ok_or_else(|| CrawlerError::ParseError("No contest link".to_string()))?;
         let contest_title = contest_a.text().collect::<String>();
@@ -191,24 +253,18 @@ pub fn parse_tasks_html(
     html_content: &str,
     contest_id: &str,
 ) -> Result<Vec<Problem>, CrawlerError> {
+    let selectors = selectors()?;
     let document = Html::parse_document(html_content);
 
-    // Selector for the table rows containing problem information
-    let row_selector = Selector::parse("table.table-bordered tbody tr")
-        .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-
     let mut problems = Vec::new();
 
-    for row in document.select(&row_selector) {
+    // Each table row contains the information of one problem
+    for row in document.select(&selectors.task_row) {
         // Extract the problem index (A, B, C, etc.)
-        let index_selector = Selector::parse("td.text-center.no-break a")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let index_element = row.select(&index_selector).next();
+        let index_element = row.select(&selectors.task_index).next();
 
         // Extract the problem name and URL
-        let name_selector = Selector::parse("td:nth-child(2) a")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let name_element = row.select(&name_selector).next();
+        let name_element = row.select(&selectors.task_name).next();
 
         if let (Some(index_elem), Some(name_elem)) = (index_element, name_element) {
             let problem_index = index_elem.text().collect::<String>();
@@ -221,11 +277,24 @@ pub fn parse_tasks_html(
                 continue; // Skip if no URL is found
             };
 
+            // Extract the limits (e.g., "2 sec" and "1024 MB")
+            let time_limit_ms = row
+                .select(&selectors.task_time_limit)
+                .next()
+                .and_then(|e| parse_time_ms(&e.text().collect::<String>()));
+            let memory_limit_kb = row
+                .select(&selectors.task_memory_limit)
+                .next()
+                .and_then(|e| parse_memory_kb(&e.text().collect::<String>()));
+
             problems.push(Problem {
                 id,
                 contest_id: contest_id.to_string(),
                 problem_index,
                 name,
+                time_limit_ms,
+                memory_limit_kb,
+                point: None,
             });
         }
     }
@@ -233,190 +302,527 @@ pub fn parse_tasks_html(
     Ok(problems)
 }
 
+/// Parses a duration such as "2 sec" or "500 ms" into milliseconds
+fn parse_time_ms(text: &str) -> Option<i32> {
+    let (value, unit) = text.trim().split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let ms = match unit.trim() {
+        "sec" => value * 1000.0,
+        "ms" => value,
+        _ => return None,
+    };
+    Some(ms.round() as i32)
+}
+
+/// Parses a memory size such as "1024 MB", "1024 MiB" or "3868 KB" into kilobytes
+fn parse_memory_kb(text: &str) -> Option<i32> {
+    let (value, unit) = text.trim().split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let kb = match unit.trim() {
+        "KB" | "KiB" => value,
+        "MB" | "MiB" => value * 1024.0,
+        "GB" | "GiB" => value * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(kb.round() as i32)
+}
+
+/// Parses an individual task page and extracts the point value of the task
+///
+/// # Arguments
+///
+/// * `html_content` - The HTML content of the task page
+///
+/// # Returns
+///
+/// A Result containing the point value, or None if the page does not show one
+/// (e.g., old contests without partial scores)
+pub fn parse_task_point_html(html_content: &str) -> Result<Option<f64>, CrawlerError> {
+    let selectors = selectors()?;
+    let document = Html::parse_document(html_content);
+
+    for paragraph in document.select(&selectors.task_statement_paragraph) {
+        let text = paragraph.text().collect::<String>();
+        let text = text.trim_start();
+        // "配点 : 100 点" on Japanese pages, "Score : 100 points" on English ones
+        if !text.starts_with("配点") && !text.starts_with("Score") {
+            continue;
+        }
+        let Some(var) = paragraph.select(&selectors.var).next() else {
+            continue;
+        };
+        let point = var
+            .text()
+            .collect::<String>()
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| CrawlerError::ParseError(format!("Failed to parse point: {}", e)))?;
+        return Ok(Some(point));
+    }
+
+    Ok(None)
+}
+
+/// Columns of the submissions table, located through their header labels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SubmissionColumn {
+    Time,
+    Task,
+    User,
+    Language,
+    Score,
+    CodeSize,
+    Status,
+    ExecTime,
+    Memory,
+}
+
+impl SubmissionColumn {
+    /// Columns that every submissions table must have
+    const REQUIRED: [SubmissionColumn; 7] = [
+        SubmissionColumn::Time,
+        SubmissionColumn::Task,
+        SubmissionColumn::User,
+        SubmissionColumn::Language,
+        SubmissionColumn::Score,
+        SubmissionColumn::CodeSize,
+        SubmissionColumn::Status,
+    ];
+
+    /// Maps a Japanese or English header label to its column
+    fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "提出日時" | "Submission Time" => Some(SubmissionColumn::Time),
+            "問題" | "Task" => Some(SubmissionColumn::Task),
+            "ユーザ" | "User" => Some(SubmissionColumn::User),
+            "言語" | "Language" => Some(SubmissionColumn::Language),
+            "得点" | "Score" => Some(SubmissionColumn::Score),
+            "コード長" | "Code Size" => Some(SubmissionColumn::CodeSize),
+            "結果" | "Status" => Some(SubmissionColumn::Status),
+            "実行時間" | "Exec Time" => Some(SubmissionColumn::ExecTime),
+            "メモリ" | "Memory" => Some(SubmissionColumn::Memory),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the cells of a row keyed by the index of the column they start at,
+/// so that a cell spanning several columns (e.g., the status of a compile
+/// error) leaves the columns it covers empty.
+fn cells_by_column<'a>(
+    row: ElementRef<'a>,
+    cell_selector: &Selector,
+) -> HashMap<usize, ElementRef<'a>> {
+    let mut cells = HashMap::new();
+    let mut column = 0;
+    for cell in row.select(cell_selector) {
+        cells.insert(column, cell);
+        column += cell
+            .value()
+            .attr("colspan")
+            .and_then(|colspan| colspan.parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(1);
+    }
+    cells
+}
+
 /// Parses the submissions.html file and extracts submission information
 ///
+/// Columns are located by reading the table header, so the parser keeps
+/// working when AtCoder adds or reorders columns. Rows that cannot be parsed
+/// are skipped and listed in the returned [`ParseReport`].
+///
 /// # Arguments
 ///
 /// * `html_content` - The HTML content of the submissions page
 ///
 /// # Returns
 ///
-/// A Result containing a vector of Submission structs or a CrawlerError
-pub fn parse_submissions_html(html_content: &str) -> Result<Vec<Submission>, CrawlerError> {
+/// A Result containing a vector of Submission structs and the ParseReport of
+/// the table rows, or a CrawlerError if the table header lacks a required column
+pub fn parse_submissions_html(
+    html_content: &str,
+) -> Result<(Vec<Submission>, ParseReport), CrawlerError> {
+    let selectors = selectors()?;
     let document = Html::parse_document(html_content);
 
-    // Selector for the table rows containing submission information
-    let row_selector = Selector::parse("table.table-bordered tbody tr")
-        .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
+    // The submissions table is the one whose header has submission columns
+    let Some((table, columns)) = document
+        .select(&selectors.bordered_table)
+        .find_map(|table| {
+            let columns = table
+                .select(&selectors.header_cell)
+                .enumerate()
+                .filter_map(|(index, th)| {
+                    let label = th.text().collect::<String>();
+                    SubmissionColumn::from_label(label.trim()).map(|column| (column, index))
+                })
+                .collect::<HashMap<_, _>>();
+            (!columns.is_empty()).then_some((table, columns))
+        })
+    else {
+        return Ok((Vec::new(), ParseReport::default())); // Empty page, no submissions
+    };
 
-    let mut submissions = Vec::new();
+    if let Some(missing) = SubmissionColumn::REQUIRED
+        .iter()
+        .find(|column| !columns.contains_key(column))
+    {
+        return Err(CrawlerError::ParseError(format!(
+            "Submissions table has no {:?} column",
+            missing
+        )));
+    }
 
-    for row in document.select(&row_selector) {
-        // Extract submission ID from the details link
-        let details_selector = Selector::parse("td:last-child a.submission-details-link")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let details_element = row.select(&details_selector).next();
-
-        let id = if let Some(details_elem) = details_element {
-            if let Some(href) = details_elem.value().attr("href") {
-                // Extract ID from the URL (e.g., "/contests/abc399/submissions/64188418" -> "64188418")
-                href.split('/')
-                    .next_back()
-                    .unwrap_or("")
-                    .parse::<i64>()
-                    .map_err(|e| {
-                        CrawlerError::ParseError(format!("Failed to parse submission ID: {}", e))
-                    })?
-            } else {
-                continue; // Skip if no URL is found
+    let mut submissions = Vec::new();
+    let mut report = ParseReport::default();
+    for (index, row) in table.select(&selectors.body_row).enumerate() {
+        match parse_submission_row(row, &columns, selectors) {
+            Ok(submission) => {
+                submissions.push(submission);
+                report.parsed += 1;
             }
-        } else {
-            continue; // Skip if no details link is found
-        };
-
-        // Extract submission date
-        let date_selector = Selector::parse("td:first-child time")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let date_element = row.select(&date_selector).next();
-
-        let epoch_second = if let Some(date_elem) = date_element {
-            let date_str = date_elem.text().collect::<String>();
-            // Parse the date string (e.g., "2024-04-05 12:34:56+0900")
-            let datetime = DateTime::parse_from_str(&date_str, "%Y-%m-%d %H:%M:%S%z")
-                .map_err(|e| CrawlerError::ParseError(format!("Failed to parse date: {}", e)))?;
-            datetime.timestamp()
-        } else {
-            continue; // Skip if no date is found
-        };
+            Err(reason) => report.skipped.push(SkippedRow { index, reason }),
+        }
+    }
 
-        // Extract problem
-        let problem_selector = Selector::parse("td:nth-child(2) a")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let problem_element = row.select(&problem_selector).next();
+    Ok((submissions, report))
+}
 
-        let problem = if let Some(problem_elem) = problem_element {
-            // Extract problem ID from the URL (e.g., "/contests/abc399/tasks/abc399_a" -> "abc399_a")
-            if let Some(href) = problem_elem.value().attr("href") {
-                href.split('/').next_back().unwrap_or("").to_string()
-            } else {
-                continue; // Skip if no URL is found
-            }
-        } else {
-            continue; // Skip if no problem is found
-        };
+/// Parses a single row of the submissions table, or explains why it cannot be
+fn parse_submission_row(
+    row: ElementRef,
+    columns: &HashMap<SubmissionColumn, usize>,
+    selectors: &Selectors,
+) -> Result<Submission, String> {
+    let cells = cells_by_column(row, &selectors.td);
+    let cell = |column: SubmissionColumn| {
+        columns
+            .get(&column)
+            .and_then(|index| cells.get(index))
+            .copied()
+    };
+    let required_cell =
+        |column: SubmissionColumn| cell(column).ok_or_else(|| format!("No {:?} cell", column));
 
-        // Extract user
-        let user_selector = Selector::parse("td:nth-child(3) a:first-child")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let user_element = row.select(&user_selector).next();
-
-        let user = if let Some(user_elem) = user_element {
-            if let Some(href) = user_elem.value().attr("href") {
-                // Extract user_id from the URL path like "/users/{user_id}"
-                href.split('/')
-                    .nth(2) // Get the third component after splitting (index 2)
-                    .unwrap_or("")
-                    .to_string()
-            } else {
-                continue; // Skip if no href is found
-            }
-        } else {
-            continue; // Skip if no user is found
-        };
+    // Extract submission ID and contest ID from the details link
+    // (e.g., "/contests/abc399/submissions/64188418")
+    let details_href = row
+        .select(&selectors.submission_details_link)
+        .next()
+        .and_then(|a| a.value().attr("href"))
+        .ok_or_else(|| "No details link".to_string())?;
+    let id = details_href
+        .split('/')
+        .next_back()
+        .unwrap_or("")
+        .parse::<i64>()
+        .map_err(|e| format!("Failed to parse submission ID {:?}: {}", details_href, e))?;
+    let contest_id = details_href.split('/').nth(2).unwrap_or("").to_string();
+
+    // Parse the date string (e.g., "2024-04-05 12:34:56+0900")
+    let date_str = required_cell(SubmissionColumn::Time)?
+        .select(&selectors.time)
+        .next()
+        .ok_or_else(|| "No submission time".to_string())?
+        .text()
+        .collect::<String>();
+    let epoch_second = DateTime::parse_from_str(date_str.trim(), "%Y-%m-%d %H:%M:%S%z")
+        .map_err(|e| format!("Failed to parse date {:?}: {}", date_str, e))?
+        .timestamp();
+
+    // Extract problem ID from the URL (e.g., "/contests/abc399/tasks/abc399_a" -> "abc399_a")
+    let problem_id = required_cell(SubmissionColumn::Task)?
+        .select(&selectors.a)
+        .next()
+        .and_then(|a| a.value().attr("href"))
+        .and_then(|href| href.split('/').next_back())
+        .filter(|problem_id| !problem_id.is_empty())
+        .ok_or_else(|| "No problem link".to_string())?
+        .to_string();
+
+    // Extract user_id from the URL path like "/users/{user_id}"
+    let user = required_cell(SubmissionColumn::User)?
+        .select(&selectors.a)
+        .next()
+        .and_then(|a| a.value().attr("href"))
+        .and_then(|href| href.split('/').nth(2))
+        .filter(|user| !user.is_empty())
+        .ok_or_else(|| "No user link".to_string())?
+        .to_string();
+
+    let language = required_cell(SubmissionColumn::Language)?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+
+    let score_text = required_cell(SubmissionColumn::Score)?
+        .text()
+        .collect::<String>();
+    let score = score_text
+        .trim()
+        .parse::<f64>()
+        .map_err(|e| format!("Failed to parse score {:?}: {}", score_text, e))?;
+
+    // Remove " Byte" from the end and parse
+    let code_length_text = required_cell(SubmissionColumn::CodeSize)?
+        .text()
+        .collect::<String>();
+    let code_length = code_length_text
+        .trim()
+        .trim_end_matches(" Byte")
+        .parse::<i32>()
+        .map_err(|e| format!("Failed to parse code length {:?}: {}", code_length_text, e))?;
+
+    // The status is shown as a label, e.g. "AC" or "3/20 WJ"
+    let status_cell = required_cell(SubmissionColumn::Status)?;
+    let result = status_cell
+        .select(&selectors.span)
+        .next()
+        .unwrap_or(status_cell)
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+
+    let execution_time =
+        cell(SubmissionColumn::ExecTime).and_then(|e| parse_time_ms(&e.text().collect::<String>()));
+    let memory =
+        cell(SubmissionColumn::Memory).and_then(|e| parse_memory_kb(&e.text().collect::<String>()));
+
+    Ok(Submission {
+        id,
+        epoch_second,
+        problem_id,
+        contest_id,
+        user,
+        language,
+        score,
+        code_length,
+        result,
+        execution_time,
+        memory,
+    })
+}
 
-        // Extract language
-        let language_selector = Selector::parse("td:nth-child(4) a")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let language_element = row.select(&language_selector).next();
+/// Parses a submission detail page and extracts the judge results
+///
+/// # Arguments
+///
+/// * `html_content` - The HTML content of the submission page
+/// * `submission_id` - The ID of the submission the page belongs to
+///
+/// # Returns
+///
+/// A Result containing the SubmissionDetail or a CrawlerError. Submissions that
+/// were not judged on any test case (e.g., compile errors) have no judge cases.
+pub fn parse_submission_detail_html(
+    html_content: &str,
+    submission_id: i64,
+) -> Result<SubmissionDetail, CrawlerError> {
+    let selectors = selectors()?;
+    let document = Html::parse_document(html_content);
 
-        let language = if let Some(language_elem) = language_element {
-            language_elem.text().collect::<String>()
-        } else {
-            continue; // Skip if no language is found
+    // The summary table has one "label | value" row per field, e.g. "メモリ | 3868 KB"
+    let mut memory_kb = None;
+    for row in document.select(&selectors.tr) {
+        let (Some(th), Some(td)) = (
+            row.select(&selectors.th).next(),
+            row.select(&selectors.td).next(),
+        ) else {
+            continue;
         };
+        let label = th.text().collect::<String>();
+        if matches!(label.trim(), "メモリ" | "Memory") {
+            memory_kb = parse_memory_kb(&td.text().collect::<String>());
+        }
+    }
 
-        // Extract score
-        let score_selector = Selector::parse("td:nth-child(5)")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let score_element = row.select(&score_selector).next();
-
-        let score = if let Some(score_elem) = score_element {
-            score_elem
-                .text()
-                .collect::<String>()
-                .parse::<f64>()
-                .map_err(|e| CrawlerError::ParseError(format!("Failed to parse score: {}", e)))?
-        } else {
-            continue; // Skip if no score is found
-        };
+    let compile_error = document.select(&selectors.h4).any(|h4| {
+        matches!(
+            h4.text().collect::<String>().trim(),
+            "コンパイルエラー" | "Compile Error"
+        )
+    });
+
+    // The test case table is the one whose first column is the case name
+    let mut judge_cases = Vec::new();
+    for table in document.select(&selectors.table) {
+        let is_case_table = table.select(&selectors.th).next().is_some_and(|th| {
+            matches!(
+                th.text().collect::<String>().trim(),
+                "ケース名" | "Case Name"
+            )
+        });
+        if !is_case_table {
+            continue;
+        }
 
-        // Extract code length
-        let code_length_selector = Selector::parse("td:nth-child(6)")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let code_length_element = row.select(&code_length_selector).next();
-
-        let code_length = if let Some(code_length_elem) = code_length_element {
-            let text = code_length_elem.text().collect::<String>();
-            // Remove " Byte" from the end and parse as u32
-            text.trim_end_matches(" Byte").parse().map_err(|e| {
-                CrawlerError::ParseError(format!("Failed to parse code length: {}", e))
-            })?
-        } else {
-            continue; // Skip if no code length is found
-        };
+        for row in table.select(&selectors.body_row) {
+            let tds = row.select(&selectors.td).collect::<Vec<_>>();
+            let [name_td, verdict_td, time_td, memory_td] = tds.as_slice() else {
+                return Err(CrawlerError::ParseError(format!(
+                    "Expected 4 columns in judge case row, found {}",
+                    tds.len()
+                )));
+            };
+            judge_cases.push(JudgeCase {
+                case_name: name_td.text().collect::<String>().trim().to_string(),
+                verdict: verdict_td.text().collect::<String>().trim().to_string(),
+                time_ms: parse_time_ms(&time_td.text().collect::<String>()),
+                memory_kb: parse_memory_kb(&memory_td.text().collect::<String>()),
+            });
+        }
+    }
+
+    Ok(SubmissionDetail {
+        submission_id,
+        memory_kb,
+        compile_error,
+        judge_cases,
+    })
+}
 
-        // Extract result
-        let result_selector = Selector::parse("td:nth-child(7) span")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let result_element = row.select(&result_selector).next();
+/// Parse the profile page of a user
+///
+/// # Arguments
+///
+/// * `html_content` - The HTML content of `/users/{user_id}`
+/// * `user_id` - The user ID
+///
+/// # Returns
+///
+/// A Result containing the UserProfile or a CrawlerError. The page lists the
+/// profile and the rating as "label | value" rows, in Japanese or English, and
+/// the rating rows are missing for users who have never been rated.
+pub fn parse_user_profile_html(
+    html_content: &str,
+    user_id: &str,
+) -> Result<UserProfile, CrawlerError> {
+    let selectors = selectors()?;
+    let document = Html::parse_document(html_content);
 
-        let result = if let Some(result_elem) = result_element {
-            result_elem.text().collect::<String>()
-        } else {
-            continue; // Skip if no result is found
+    let mut profile = UserProfile {
+        user_id: user_id.to_string(),
+        country: None,
+        affiliation: None,
+        birth_year: None,
+        rating: None,
+        highest_rating: None,
+    };
+    let mut found_rows = false;
+    for row in document.select(&selectors.profile_row) {
+        let (Some(th), Some(td)) = (
+            row.select(&selectors.th).next(),
+            row.select(&selectors.td).next(),
+        ) else {
+            continue;
         };
+        found_rows = true;
+        let value = td.text().collect::<String>().trim().to_string();
+        match th.text().collect::<String>().trim() {
+            "国と地域" | "Country/Region" => {
+                // The flag image names the country code, e.g. ".../flag/JP.png"
+                profile.country = td
+                    .select(&selectors.img)
+                    .filter_map(|img| img.value().attr("src"))
+                    .find_map(|src| src.rsplit_once("/flag/"))
+                    .and_then(|(_, file)| file.split('.').next())
+                    .map(str::to_string)
+                    .or_else(|| Some(value).filter(|value| !value.is_empty()));
+            }
+            "所属" | "Affiliation" => {
+                profile.affiliation = Some(value).filter(|value| !value.is_empty());
+            }
+            "誕生年" | "Birth Year" => profile.birth_year = value.parse().ok(),
+            "Rating" => profile.rating = leading_number(&value),
+            "最高Rating" | "Highest Rating" => profile.highest_rating = leading_number(&value),
+            _ => {}
+        }
+    }
 
-        // Extract execution time
-        let execution_time_selector = Selector::parse("td:nth-child(8)")
-            .map_err(|e| CrawlerError::SelectorError(e.to_string()))?;
-        let execution_time_element = row.select(&execution_time_selector).next();
+    if !found_rows {
+        return Err(CrawlerError::ParseError(format!(
+            "No profile table found for user {}",
+            user_id
+        )));
+    }
+    Ok(profile)
+}
 
-        let execution_time = execution_time_element.and_then(|e| {
-            let text = e.text().collect::<String>();
-            text.trim_end_matches(" ms").parse::<i32>().ok()
-        });
+/// Parses the number at the start of a cell such as "1850 ― 2 Kyu".
+fn leading_number(text: &str) -> Option<i32> {
+    let digits: String = text
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
 
-        // Get the URL from the details link
-        let url = if let Some(details_elem) = details_element {
-            if let Some(href) = details_elem.value().attr("href") {
-                href.to_string()
-            } else {
-                continue; // Skip if no URL is found
-            }
-        } else {
-            continue; // Skip if no details link is found
-        };
+/// Parse the editorial list of a contest
+///
+/// # Arguments
+///
+/// * `html_content` - The HTML content of `/contests/{contest_id}/editorial`
+///
+/// # Returns
+///
+/// A Result containing the editorials of the contest's problems. Each problem
+/// is a heading linking to its task followed by a list with one item per
+/// editorial; editorials of the whole contest have no task and are left out.
+pub fn parse_editorials_html(html_content: &str) -> Result<Vec<Editorial>, CrawlerError> {
+    let selectors = selectors()?;
+    let document = Html::parse_document(html_content);
 
-        // Extract contest ID from the URL (e.g., "/contests/abc399/submissions/64188418" -> "abc399")
-        let contest_id = url
-            .split('/')
-            .nth(2) // Get the third component after splitting
-            .unwrap_or("")
-            .to_string();
+    let mut editorials = Vec::new();
+    for heading in document.select(&selectors.editorial_heading) {
+        let Some(problem_id) = heading
+            .select(&selectors.task_link)
+            .filter_map(|a| a.value().attr("href"))
+            .find_map(|href| href.rsplit_once("/tasks/"))
+            .map(|(_, problem_id)| problem_id.to_string())
+        else {
+            continue;
+        };
 
-        submissions.push(Submission {
-            id,
-            epoch_second,
-            problem_id: problem,
-            contest_id,
-            user,
-            language,
-            score,
-            code_length,
-            result,
-            execution_time,
-        });
+        let items = heading
+            .next_siblings()
+            .filter_map(ElementRef::wrap)
+            .take_while(|element| !matches!(element.value().name(), "h3" | "h4"))
+            .flat_map(|element| element.select(&selectors.li).collect::<Vec<_>>());
+        for item in items {
+            let Some(href) = item
+                .select(&selectors.a)
+                .filter_map(|a| a.value().attr("href"))
+                .find(|href| !href.starts_with("/users/"))
+            else {
+                continue;
+            };
+            let url = if href.starts_with('/') {
+                format!("https://atcoder.jp{}", href)
+            } else {
+                href.to_string()
+            };
+            // The language is shown as a flag, e.g. ".../flag-lang/ja.png"
+            let language = item
+                .select(&selectors.img)
+                .filter_map(|img| img.value().attr("src"))
+                .find_map(|src| src.rsplit_once("flag-lang/"))
+                .and_then(|(_, file)| file.split('.').next())
+                .map(str::to_string);
+            let is_official = item.select(&selectors.label).any(|label| {
+                matches!(label.text().collect::<String>().trim(), "公式" | "Official")
+            });
+            editorials.push(Editorial {
+                problem_id: problem_id.clone(),
+                url,
+                language,
+                is_official,
+            });
+        }
     }
-
-    Ok(submissions)
+    Ok(editorials)
 }
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::error::CrawlerError;
+
+/// Environment variable holding the per-host request budget
+const REQUESTS_PER_SECOND_ENV: &str = "CRAWLER_REQUESTS_PER_SECOND";
+
+/// Request budget applied to every host the crawler talks to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained number of requests per second allowed for each host
+    pub requests_per_second: f64,
+    /// Number of requests that may be sent back to back after being idle
+    pub burst: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 2.0,
+            burst: 1,
+        }
+    }
+}
+
+impl RateLimit {
+    /// Reads the budget from `CRAWLER_REQUESTS_PER_SECOND`, falling back to the
+    /// default when it is not set.
+    pub fn from_env() -> Result<Self, CrawlerError> {
+        let mut limit = Self::default();
+        if let Ok(value) = std::env::var(REQUESTS_PER_SECOND_ENV) {
+            let requests_per_second: f64 = value.parse().map_err(|_| {
+                CrawlerError::InvalidConfig(format!(
+                    "{} must be a number, got {:?}",
+                    REQUESTS_PER_SECOND_ENV, value
+                ))
+            })?;
+            if !requests_per_second.is_finite() || requests_per_second <= 0.0 {
+                return Err(CrawlerError::InvalidConfig(format!(
+                    "{} must be positive, got {}",
+                    REQUESTS_PER_SECOND_ENV, requests_per_second
+                )));
+            }
+            limit.requests_per_second = requests_per_second;
+        }
+        Ok(limit)
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    paused_until: Option<Instant>,
+}
+
+/// Token bucket limiter keeping a separate budget for each host.
+///
+/// Every request made through a [`crate::CrawlerClient`] takes a token from the
+/// bucket of its host, so concurrent tasks sharing a client never exceed the
+/// budget together.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Waits until a request to `host` fits in the budget.
+    pub async fn acquire(&self, host: &str) {
+        loop {
+            let wait = self.try_acquire(host, Instant::now());
+            match wait {
+                None => return,
+                Some(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Stops handing out tokens for `host` for the given duration, e.g. after
+    /// the server answered with `Retry-After`.
+    pub fn pause(&self, host: &str, duration: Duration) {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets
+            .entry(host.to_string())
+            .or_insert_with(|| self.new_bucket(now));
+        let until = now + duration;
+        if bucket.paused_until.is_none_or(|paused| paused < until) {
+            bucket.paused_until = Some(until);
+        }
+        bucket.tokens = 0.0;
+        bucket.refilled_at = until;
+    }
+
+    /// Takes a token if one is available, otherwise returns how long to wait.
+    fn try_acquire(&self, host: &str, now: Instant) -> Option<Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets
+            .entry(host.to_string())
+            .or_insert_with(|| self.new_bucket(now));
+
+        if let Some(paused_until) = bucket.paused_until {
+            if now < paused_until {
+                return Some(paused_until - now);
+            }
+            bucket.paused_until = None;
+        }
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        let capacity = f64::from(self.limit.burst.max(1));
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.limit.requests_per_second).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Some(Duration::from_secs_f64(
+                missing / self.limit.requests_per_second,
+            ))
+        }
+    }
+
+    fn new_bucket(&self, now: Instant) -> Bucket {
+        Bucket {
+            tokens: f64::from(self.limit.burst.max(1)),
+            refilled_at: now,
+            paused_until: None,
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::client::{
+    ContestFetcher, EditorialFetcher, ProblemFetcher, StandingsFetcher, SubmissionFetcher,
+    UserFetcher,
+};
+use crate::error::CrawlerError;
+use crate::stats::FetchStats;
+use crate::types::{
+    Contest, Editorial, Problem, RatingHistoryEntry, Standings, Submission, SubmissionDetail,
+    UserProfile,
+};
+
+/// Decides how often and how long to wait before a failed request is sent again.
+///
+/// The delay before the `n`-th retry is `base_delay * 2^(n - 1)`, capped at
+/// `max_delay`, and then shortened by a random fraction of up to `jitter` so
+/// that concurrent crawlers do not retry in lockstep.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound of the delay between two attempts
+    pub max_delay: Duration,
+    /// Fraction of the delay (0.0 to 1.0) that is randomly cut off
+    pub jitter: f64,
+    /// Predicate deciding which errors are worth another attempt
+    pub retryable: fn(&CrawlerError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(300),
+            jitter: 0.5,
+            retryable: CrawlerError::is_retryable,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay to wait after the given (1-based) failed attempt.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        delay.mul_f64(1.0 - jitter * rand::random::<f64>())
+    }
+
+    /// Runs `operation` until it succeeds, fails with a non-retryable error, or
+    /// runs out of attempts.
+    ///
+    /// Running out of attempts is reported as [`CrawlerError::RetriesExhausted`]
+    /// so that callers can tell a request that gave up from one that returned
+    /// an empty result.
+    pub async fn run<T, F, Fut>(&self, label: &str, operation: F) -> Result<T, CrawlerError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, CrawlerError>>,
+    {
+        self.run_counted(label, None, operation).await
+    }
+
+    /// Same as [`RetryPolicy::run`], counting every attempt in `stats`.
+    async fn run_counted<T, F, Fut>(
+        &self,
+        label: &str,
+        stats: Option<&FetchStats>,
+        mut operation: F,
+    ) -> Result<T, CrawlerError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, CrawlerError>>,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = operation().await;
+            if let Some(stats) = stats {
+                stats.record(&result);
+            }
+            let error = match result {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            if !(self.retryable)(&error) {
+                return Err(error);
+            }
+            if attempt >= max_attempts {
+                tracing::error!("Failed to fetch {} after {} attempts", label, attempt);
+                return Err(CrawlerError::RetriesExhausted {
+                    attempts: attempt,
+                    last: Box::new(error),
+                });
+            }
+
+            let mut delay = self.delay_for(attempt);
+            if let CrawlerError::RateLimited {
+                retry_after: Some(retry_after),
+            } = &error
+            {
+                // Never come back earlier than the server asked us to.
+                delay = delay.max(*retry_after);
+            }
+            tracing::warn!(
+                "Failed to fetch {} (attempt {}/{}), retrying in {:?}: {}",
+                label,
+                attempt,
+                max_attempts,
+                delay,
+                error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Wraps a fetcher so that every request it makes is retried according to a
+/// [`RetryPolicy`].
+///
+/// Every attempt is counted in [`RetryingFetcher::stats`].
+pub struct RetryingFetcher<F> {
+    inner: F,
+    policy: RetryPolicy,
+    stats: FetchStats,
+}
+
+impl<F> RetryingFetcher<F> {
+    pub fn new(inner: F, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            stats: FetchStats::default(),
+        }
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
+
+    pub fn stats(&self) -> &FetchStats {
+        &self.stats
+    }
+}
+
+#[async_trait]
+impl<F: ProblemFetcher> ProblemFetcher for RetryingFetcher<F> {
+    async fn fetch_problems(&self, contest_id: &str) -> Result<Vec<Problem>, CrawlerError> {
+        let label = format!("problems of {}", contest_id);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_problems(contest_id)
+            })
+            .await
+    }
+
+    async fn fetch_problem_point(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+    ) -> Result<Option<f64>, CrawlerError> {
+        let label = format!("point of {}", problem_id);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_problem_point(contest_id, problem_id)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl<F: ContestFetcher> ContestFetcher for RetryingFetcher<F> {
+    async fn fetch_contests(&self, page: u32) -> Result<Vec<Contest>, CrawlerError> {
+        let label = format!("contests page {}", page);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_contests(page)
+            })
+            .await
+    }
+
+    async fn fetch_contests_in_category(
+        &self,
+        page: u32,
+        category: u32,
+    ) -> Result<Vec<Contest>, CrawlerError> {
+        let label = format!("contests category {} page {}", category, page);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_contests_in_category(page, category)
+            })
+            .await
+    }
+
+    async fn fetch_permanent_contests(&self) -> Result<Vec<Contest>, CrawlerError> {
+        self.policy
+            .run_counted("permanent contests", Some(&self.stats), || {
+                self.inner.fetch_permanent_contests()
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl<F: SubmissionFetcher> SubmissionFetcher for RetryingFetcher<F> {
+    async fn fetch_submissions(
+        &self,
+        contest_id: &str,
+        page: i32,
+    ) -> Result<Vec<Submission>, CrawlerError> {
+        let label = format!("submissions of {} page {}", contest_id, page);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_submissions(contest_id, page)
+            })
+            .await
+    }
+
+    async fn fetch_submission_detail(
+        &self,
+        contest_id: &str,
+        submission_id: i64,
+    ) -> Result<SubmissionDetail, CrawlerError> {
+        let label = format!("submission {}", submission_id);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner
+                    .fetch_submission_detail(contest_id, submission_id)
+            })
+            .await
+    }
+
+    async fn fetch_user_submissions(
+        &self,
+        contest_id: &str,
+        user_id: &str,
+        page: i32,
+    ) -> Result<Vec<Submission>, CrawlerError> {
+        let label = format!("submissions of {} in {} page {}", user_id, contest_id, page);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_user_submissions(contest_id, user_id, page)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl<F: StandingsFetcher> StandingsFetcher for RetryingFetcher<F> {
+    async fn fetch_standings(&self, contest_id: &str) -> Result<Option<Standings>, CrawlerError> {
+        let label = format!("standings of {}", contest_id);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_standings(contest_id)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl<F: UserFetcher> UserFetcher for RetryingFetcher<F> {
+    async fn fetch_user_profile(&self, user_id: &str) -> Result<UserProfile, CrawlerError> {
+        let label = format!("profile of {}", user_id);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_user_profile(user_id)
+            })
+            .await
+    }
+
+    async fn fetch_rating_history(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<RatingHistoryEntry>, CrawlerError> {
+        let label = format!("rating history of {}", user_id);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_rating_history(user_id)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl<F: EditorialFetcher> EditorialFetcher for RetryingFetcher<F> {
+    async fn fetch_editorials(&self, contest_id: &str) -> Result<Vec<Editorial>, CrawlerError> {
+        let label = format!("editorials of {}", contest_id);
+        self.policy
+            .run_counted(&label, Some(&self.stats), || {
+                self.inner.fetch_editorials(contest_id)
+            })
+            .await
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::CrawlerError;
+
+/// Counts the outcome of every request attempt made through a
+/// [`crate::RetryingFetcher`].
+#[derive(Debug, Default)]
+pub struct FetchStats {
+    pages_fetched: AtomicU64,
+    errors: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+impl FetchStats {
+    /// Counts a fetched page, or the failed attempt by its error variant.
+    pub fn record<T>(&self, result: &Result<T, CrawlerError>) {
+        match result {
+            Ok(_) => {
+                self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+                *errors.entry(e.kind()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Returns the number of pages fetched and parsed successfully.
+    pub fn pages_fetched(&self) -> u64 {
+        self.pages_fetched.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of failed attempts by [`CrawlerError::kind`],
+    /// including the ones that succeeded when retried.
+    pub fn errors(&self) -> BTreeMap<&'static str, u64> {
+        self.errors
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
@@ -1,5 +1,13 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::CrawlerError;
+
 /// Represents a problem from AtCoder (matches the DB schema)
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Problem {
     /// The problem ID (e.g., "abc399_a")
     pub id: String,
@@ -9,6 +17,12 @@ pub struct Problem {
     pub problem_index: String,
     /// The problem name (e.g., "Hamming Distance")
     pub name: String,
+    /// The time limit in milliseconds (None if not available)
+    pub time_limit_ms: Option<i32>,
+    /// The memory limit in kilobytes (None if not available)
+    pub memory_limit_kb: Option<i32>,
+    /// The point value shown on the task page (None if not available)
+    pub point: Option<f64>,
 }
 
 impl Problem {
@@ -33,8 +47,165 @@ pub struct Contest {
     pub rate_change: String,
 }
 
+impl Contest {
+    /// Returns the rating range of the contest, or `None` if it is unrated.
+    pub fn rated_range(&self) -> Option<RatedRange> {
+        RatedRange::parse(&self.rate_change)
+    }
+
+    pub fn contest_type(&self) -> ContestType {
+        ContestType::classify(&self.id, self.rated_range())
+    }
+}
+
+/// Range of ratings for which a contest is rated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatedRange {
+    /// The lowest rating that is rated (0 if there is no lower bound)
+    pub lower: i32,
+    /// The highest rating that is rated (None if there is no upper bound)
+    pub upper: Option<i32>,
+}
+
+impl RatedRange {
+    /// Parses the "rated range" label of a contest.
+    ///
+    /// AtCoder used to write the range with `~` (e.g. `" ~ 1999"`, `"1200 ~ "`)
+    /// and switched to `-` (e.g. `" - 1999"`) in late 2025, so both are
+    /// accepted. `"All"` has no bound at all. Returns `None` for unrated
+    /// contests (see [`RatedRange::is_unrated_label`]) and labels that are not
+    /// recognized.
+    pub fn parse(rate_change: &str) -> Option<Self> {
+        if Self::is_unrated_label(rate_change) {
+            return None;
+        }
+        let text = rate_change.trim();
+        if text == "All" {
+            return Some(Self {
+                lower: 0,
+                upper: None,
+            });
+        }
+        let (lower, upper) = text.split_once(['~', '-'])?;
+        let bound = |value: &str| -> Option<Option<i32>> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Some(None);
+            }
+            if !value.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            value.parse().ok().map(Some)
+        };
+        Some(Self {
+            lower: bound(lower)?.unwrap_or(0),
+            upper: bound(upper)?,
+        })
+    }
+
+    /// Returns true if the label marks an unrated contest (`"-"`, or nothing).
+    pub fn is_unrated_label(rate_change: &str) -> bool {
+        matches!(rate_change.trim(), "" | "-")
+    }
+}
+
+/// Sponsored contests held before AtCoder started rating contests, which are
+/// therefore unrated but still ARC-level contests.
+const OLD_SPONSORED_CONTESTS: &[&str] = &[
+    "code-festival-2014-exhibition",
+    "code-festival-2014-final",
+    "code-festival-2014-morning-easy",
+    "code-festival-2014-morning-hard",
+    "code-festival-2014-morning-middle",
+    "code-festival-2014-quala",
+    "code-festival-2014-qualb",
+    "code-festival-2015-exhibition",
+    "code-festival-2015-morning-easy",
+    "code-festival-2015-morning-hard",
+    "code-festival-2015-morning-middle",
+    "code-festival-2015-quala",
+    "code-festival-2015-qualb",
+    "code-formula-2014-final",
+    "code-formula-2014-quala",
+    "code-formula-2014-qualb",
+    "digitalarts2012",
+    "discovery2016-final",
+    "discovery2016-qual",
+    "donuts-2015",
+    "dwango2015-finals",
+    "dwango2015-prelims",
+    "dwango2016-finals",
+    "dwango2016-prelims",
+    "indeednow-quala",
+    "indeednow-qualb",
+    "mujin-pc-2016",
+    "tenka1-2012-final",
+    "tenka1-2012-qualA",
+    "tenka1-2012-qualB",
+    "tenka1-2012-qualC",
+    "tenka1-2013-final",
+    "tenka1-2013-quala",
+    "tenka1-2013-qualb",
+    "tenka1-2014-final",
+    "tenka1-2014-quala",
+    "tenka1-2014-qualb",
+    "tenka1-2015-final",
+    "tenka1-2015-quala",
+    "tenka1-2015-qualb",
+];
+
+/// Series a contest belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContestType {
+    /// AtCoder Beginner Contest
+    Abc,
+    /// AtCoder Regular Contest
+    Arc,
+    /// AtCoder Grand Contest
+    Agc,
+    /// AtCoder Heuristic Contest
+    Ahc,
+    /// Contest held by a sponsor, rated or held before ratings existed
+    Sponsored,
+    /// Any other contest, e.g. unrated practice or virtual contests
+    Other,
+}
+
+impl ContestType {
+    /// Classifies a contest by its id, falling back to whether it is rated.
+    pub fn classify(contest_id: &str, rated_range: Option<RatedRange>) -> Self {
+        if contest_id.starts_with("ahc") {
+            ContestType::Ahc
+        } else if contest_id.starts_with("abc") {
+            ContestType::Abc
+        } else if contest_id.starts_with("arc") {
+            ContestType::Arc
+        } else if contest_id.starts_with("agc") {
+            ContestType::Agc
+        } else if rated_range.is_some() || OLD_SPONSORED_CONTESTS.contains(&contest_id) {
+            ContestType::Sponsored
+        } else {
+            ContestType::Other
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContestType::Abc => "abc",
+            ContestType::Arc => "arc",
+            ContestType::Agc => "agc",
+            ContestType::Ahc => "ahc",
+            ContestType::Sponsored => "sponsored",
+            ContestType::Other => "other",
+        }
+    }
+}
+
 /// Represents a submission from AtCoder
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Deserialization also accepts the column names of the `submissions` table,
+/// so that an export of the table can be read back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Submission {
     /// The submission ID
     pub id: i64,
@@ -45,15 +216,314 @@ pub struct Submission {
     /// The contest ID (e.g., "abc399")
     pub contest_id: String,
     /// The user who made the submission
+    #[serde(alias = "user_id")]
     pub user: String,
     /// The programming language used
     pub language: String,
     /// The score obtained
+    #[serde(alias = "point")]
     pub score: f64,
     /// The code length in bytes
+    #[serde(alias = "length")]
     pub code_length: i32,
     /// The result of the submission (e.g., "AC", "WA", "TLE")
     pub result: String,
     /// The execution time in milliseconds (None if not available)
     pub execution_time: Option<i32>,
+    /// The memory usage in kilobytes (None if not available)
+    pub memory: Option<i32>,
+}
+
+impl Submission {
+    /// Returns true if the judge has not settled the result yet, e.g. `WJ`,
+    /// `WR` or an in-progress `3/20 WJ`.
+    pub fn is_pending(&self) -> bool {
+        is_pending_result(&self.result)
+    }
+}
+
+/// Returns true if `result` is not a final verdict.
+pub fn is_pending_result(result: &str) -> bool {
+    matches!(result, "WJ" | "WR") || result.contains('/') || result.ends_with(" WJ")
+}
+
+/// Outcome of parsing the rows of a table, kept alongside the parsed values so
+/// that a layout change shows up as skipped rows instead of a silent empty page
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseReport {
+    /// The number of rows parsed successfully
+    pub parsed: usize,
+    /// The rows that could not be parsed
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// A table row that could not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedRow {
+    /// The 0-based position of the row in the table body
+    pub index: usize,
+    /// Why the row could not be parsed
+    pub reason: String,
+}
+
+impl ParseReport {
+    /// Returns the number of rows seen, parsed or not
+    pub fn total(&self) -> usize {
+        self.parsed + self.skipped.len()
+    }
+
+    /// Returns the fraction of rows that were skipped (0.0 for an empty table)
+    pub fn skip_ratio(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        self.skipped.len() as f64 / self.total() as f64
+    }
+
+    /// Fails with [`CrawlerError::TooManySkippedRows`] if more than
+    /// `max_skip_ratio` of the rows were skipped.
+    pub fn check_skip_ratio(&self, max_skip_ratio: f64) -> Result<(), CrawlerError> {
+        if self.skip_ratio() <= max_skip_ratio {
+            return Ok(());
+        }
+        Err(CrawlerError::TooManySkippedRows {
+            skipped: self.skipped.len(),
+            total: self.total(),
+            reasons: self.skipped.iter().map(|row| row.reason.clone()).collect(),
+        })
+    }
+}
+
+/// Judge results shown on the detail page of a submission
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmissionDetail {
+    /// The submission ID
+    pub submission_id: i64,
+    /// The memory usage in kilobytes (None if not available)
+    pub memory_kb: Option<i32>,
+    /// Whether the submission failed to compile
+    pub compile_error: bool,
+    /// The result of each test case, in the order shown on the page
+    pub judge_cases: Vec<JudgeCase>,
+}
+
+/// Result of a submission on a single test case
+#[derive(Debug, Clone, PartialEq)]
+pub struct JudgeCase {
+    /// The test case name (e.g., "01_random_03.txt")
+    pub case_name: String,
+    /// The verdict on this case (e.g., "AC", "TLE")
+    pub verdict: String,
+    /// The execution time in milliseconds (None if not available)
+    pub time_ms: Option<i32>,
+    /// The memory usage in kilobytes (None if not available)
+    pub memory_kb: Option<i32>,
+}
+
+/// Contest standings as served by `/contests/{contest_id}/standings/json`.
+///
+/// Fields that are not modelled here are kept in `extra`, so serializing a
+/// parsed value gives back everything AtCoder sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Standings {
+    /// Whether the standings are final
+    #[serde(default)]
+    pub fixed: bool,
+    /// The tasks of the contest, in display order
+    pub task_info: Vec<TaskInfo>,
+    /// One row per participant
+    pub standings_data: Vec<StandingsData>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A task listed in the standings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskInfo {
+    /// The problem index (e.g., "A")
+    pub assignment: String,
+    /// The problem name (e.g., "Hamming Distance")
+    pub task_name: String,
+    /// The problem ID (e.g., "abc399_a"), used as the key of `TaskResults`
+    pub task_screen_name: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A participant's row in the standings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StandingsData {
+    /// The rank of the participant
+    pub rank: i64,
+    /// The user ID (e.g., "tourist")
+    pub user_screen_name: String,
+    /// Whether the participant was rated in this contest
+    pub is_rated: bool,
+    /// The rating before the contest
+    pub old_rating: i64,
+    /// The current rating, if AtCoder provides it
+    #[serde(default)]
+    pub rating: Option<i64>,
+    /// The number of rated contests the participant has taken part in
+    pub competitions: i64,
+    /// The overall result
+    pub total_result: TotalResult,
+    /// Results per task, keyed by the task screen name
+    pub task_results: BTreeMap<String, TaskResult>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A participant's overall result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TotalResult {
+    pub count: i64,
+    pub accepted: i64,
+    pub penalty: i64,
+    /// The score multiplied by 100
+    pub score: i64,
+    /// The elapsed time in nanoseconds
+    pub elapsed: i64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A participant's result on a single task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskResult {
+    pub count: i64,
+    pub failure: i64,
+    pub penalty: i64,
+    /// The score multiplied by 100
+    pub score: i64,
+    /// The elapsed time in nanoseconds
+    pub elapsed: i64,
+    pub status: i64,
+    pub pending: bool,
+    pub frozen: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Standings {
+    /// Checks that the standings are internally consistent.
+    ///
+    /// Truncated or partially rendered standings usually still parse, so this
+    /// catches them before they are stored.
+    pub fn validate(&self) -> Result<(), CrawlerError> {
+        if self.task_info.is_empty() {
+            return Err(CrawlerError::InvalidStandings(
+                "No tasks in TaskInfo".to_string(),
+            ));
+        }
+
+        let mut task_screen_names = BTreeSet::new();
+        for task in &self.task_info {
+            if task.task_screen_name.is_empty() {
+                return Err(CrawlerError::InvalidStandings(
+                    "Empty TaskScreenName".to_string(),
+                ));
+            }
+            if !task_screen_names.insert(task.task_screen_name.as_str()) {
+                return Err(CrawlerError::InvalidStandings(format!(
+                    "Duplicate TaskScreenName {}",
+                    task.task_screen_name
+                )));
+            }
+        }
+
+        for row in &self.standings_data {
+            if row.user_screen_name.is_empty() {
+                return Err(CrawlerError::InvalidStandings(
+                    "Empty UserScreenName".to_string(),
+                ));
+            }
+            if let Some(task) = row
+                .task_results
+                .keys()
+                .find(|task| !task_screen_names.contains(task.as_str()))
+            {
+                return Err(CrawlerError::InvalidStandings(format!(
+                    "{} has a result for unknown task {}",
+                    row.user_screen_name, task
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A user's profile as shown on `/users/{user_id}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserProfile {
+    /// The user ID (e.g., "kenkoooo")
+    pub user_id: String,
+    /// The country or region code of the user's flag (e.g., "JP")
+    pub country: Option<String>,
+    /// The affiliation the user entered
+    pub affiliation: Option<String>,
+    /// The birth year the user entered
+    pub birth_year: Option<i32>,
+    /// The current algorithm rating (None if the user has never been rated)
+    pub rating: Option<i32>,
+    /// The highest algorithm rating (None if the user has never been rated)
+    pub highest_rating: Option<i32>,
+}
+
+/// A contest result served by `/users/{user_id}/history/json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RatingHistoryEntry {
+    /// Whether the contest changed the user's rating
+    pub is_rated: bool,
+    /// The user's place in the contest
+    pub place: i32,
+    pub old_rating: i32,
+    pub new_rating: i32,
+    pub performance: i32,
+    /// The performance before it is capped by the rated range
+    #[serde(default)]
+    pub inner_performance: Option<i32>,
+    /// The contest host name (e.g., "abc399.contest.atcoder.jp")
+    pub contest_screen_name: String,
+    /// The contest end time in RFC 3339 (e.g., "2025-03-29T22:40:00+09:00")
+    pub end_time: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl RatingHistoryEntry {
+    /// Returns the contest ID, which is the first label of the contest host
+    /// name.
+    pub fn contest_id(&self) -> &str {
+        self.contest_screen_name
+            .split('.')
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Returns the contest end time in epoch seconds.
+    pub fn end_epoch_second(&self) -> Result<i64, CrawlerError> {
+        DateTime::parse_from_rfc3339(&self.end_time)
+            .map(|time| time.timestamp())
+            .map_err(|e| CrawlerError::ParseError(format!("EndTime {}: {}", self.end_time, e)))
+    }
+}
+
+/// An editorial listed on `/contests/{contest_id}/editorial`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Editorial {
+    /// The problem the editorial is for (e.g., "abc399_a")
+    pub problem_id: String,
+    /// The absolute URL of the editorial
+    pub url: String,
+    /// The language of the editorial (e.g., "ja", "en"), if shown
+    pub language: Option<String>,
+    /// Whether the editorial is written by the contest writers
+    pub is_official: bool,
 }
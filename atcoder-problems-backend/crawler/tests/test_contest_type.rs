@@ -0,0 +1,58 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use crawler::{Contest, ContestType, RatedRange};
+
+fn contest(id: &str, rate_change: &str) -> Contest {
+    Contest {
+        id: id.to_string(),
+        start_epoch_second: 0,
+        duration_second: 6000,
+        title: id.to_string(),
+        rate_change: rate_change.to_string(),
+    }
+}
+
+fn range(lower: i32, upper: Option<i32>) -> Option<RatedRange> {
+    Some(RatedRange { lower, upper })
+}
+
+#[test]
+fn test_rated_range_parses_both_separators() {
+    assert_eq!(RatedRange::parse(" ~ 1999"), range(0, Some(1999)));
+    assert_eq!(RatedRange::parse(" - 1999"), range(0, Some(1999)));
+    assert_eq!(RatedRange::parse("1200 ~ 2799"), range(1200, Some(2799)));
+    assert_eq!(RatedRange::parse("1200 - "), range(1200, None));
+    assert_eq!(RatedRange::parse("All"), range(0, None));
+}
+
+#[test]
+fn test_rated_range_rejects_unrated_and_unknown_labels() {
+    assert_eq!(RatedRange::parse("-"), None);
+    assert_eq!(RatedRange::parse(""), None);
+    assert_eq!(RatedRange::parse("Unrated"), None);
+    assert_eq!(RatedRange::parse("abc ~ 1999"), None);
+
+    // Only the unrated labels are expected to parse to `None`.
+    assert!(RatedRange::is_unrated_label(" - "));
+    assert!(RatedRange::is_unrated_label(""));
+    assert!(!RatedRange::is_unrated_label("Unrated"));
+}
+
+#[test]
+fn test_contest_type_classifies_by_id_then_rating() {
+    let cases = [
+        (contest("abc399", " ~ 1999"), ContestType::Abc),
+        (contest("abc001", "-"), ContestType::Abc),
+        (contest("arc190", "1200 ~ 2399"), ContestType::Arc),
+        (contest("agc070", "1200 ~ "), ContestType::Agc),
+        (contest("ahc040", "All"), ContestType::Ahc),
+        (contest("keyence2021", " ~ 2799"), ContestType::Sponsored),
+        (contest("tenka1-2012-qualC", "-"), ContestType::Sponsored),
+        (contest("practice", "-"), ContestType::Other),
+    ];
+
+    for (contest, expected) in cases {
+        assert_eq!(contest.contest_type(), expected, "{}", contest.id);
+    }
+    assert_eq!(ContestType::Sponsored.as_str(), "sponsored");
+}
@@ -0,0 +1,148 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::path::PathBuf;
+
+use crawler::{
+    CrawlerError, FixtureClient, PageSource, ProblemFetcher, StandingsFetcher, SubmissionFetcher,
+    UserFetcher,
+};
+
+fn fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("crawler-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_fixture_client_maps_each_url_to_its_own_file() {
+    let client = FixtureClient::new("fixtures");
+
+    let path = client.path_for("https://atcoder.jp/contests/abc399/submissions?page=2");
+
+    assert_eq!(
+        path,
+        PathBuf::from("fixtures/atcoder.jp%2Fcontests%2Fabc399%2Fsubmissions%3Fpage%3D2")
+    );
+    assert_ne!(
+        client.path_for("https://atcoder.jp/contests/a/b"),
+        client.path_for("https://atcoder.jp/contests/a%2Fb")
+    );
+}
+
+#[tokio::test]
+async fn test_fixture_client_shortens_file_names_of_long_urls() {
+    let dir = fixture_dir("long");
+    let client = FixtureClient::new(&dir);
+    let url = |page: u32| {
+        format!(
+            "https://atcoder.jp/contests/abc399/submissions?f.LanguageName=C%2B%2B&f.Status=AC\
+             &f.Task=abc399_a&f.User={}&orderBy=source_length&page={page}",
+            "u".repeat(100)
+        )
+    };
+
+    let path = client.path_for(&url(1));
+    let name = path.file_name().unwrap().to_str().unwrap();
+    assert_eq!(name.len(), 255);
+    assert!(name.starts_with("atcoder.jp%2Fcontests%2Fabc399%2Fsubmissions%3F"));
+    assert_eq!(path.parent(), Some(dir.as_path()));
+    assert_ne!(path, client.path_for(&url(2)));
+
+    // The file can be written and replayed.
+    std::fs::write(&path, "body").unwrap();
+    assert_eq!(client.fetch_page(&url(1)).await.unwrap(), "body");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_fixture_client_replays_recorded_pages() {
+    let dir = fixture_dir("replay");
+    let client = FixtureClient::new(&dir);
+    std::fs::write(
+        client.path_for("https://atcoder.jp/contests/abc399/tasks"),
+        include_str!("assets/tasks.html"),
+    )
+    .unwrap();
+
+    let problems = client.fetch_problems("abc399").await.unwrap();
+
+    assert_eq!(problems.len(), 7);
+    assert_eq!(problems[0].id, "abc399_a");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_fixture_client_replays_user_filtered_submissions() {
+    let dir = fixture_dir("user");
+    let client = FixtureClient::new(&dir);
+    std::fs::write(
+        client.path_for(
+            "https://atcoder.jp/contests/tenka1-2012-qualC/submissions?f.User=kenkoooo&page=1",
+        ),
+        include_str!("assets/submissions.html"),
+    )
+    .unwrap();
+
+    let submissions = client
+        .fetch_user_submissions("tenka1-2012-qualC", "kenkoooo", 1)
+        .await
+        .unwrap();
+
+    assert_eq!(submissions.len(), 20);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_fixture_client_replays_rating_history() {
+    let dir = fixture_dir("history");
+    let client = FixtureClient::new(&dir);
+    std::fs::write(
+        client.path_for("https://atcoder.jp/users/kenkoooo/history/json"),
+        include_str!("assets/rating_history.json"),
+    )
+    .unwrap();
+
+    let history = client.fetch_rating_history("kenkoooo").await.unwrap();
+
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].contest_id(), "abc042");
+    assert_eq!(history[0].end_epoch_second().unwrap(), 1_469_281_200);
+    assert!(!history[1].is_rated);
+    assert_eq!(history[2].new_rating, 1803);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_fixture_client_reports_missing_pages_as_not_found() {
+    let client = FixtureClient::new(std::env::temp_dir().join("crawler-missing-fixtures"));
+
+    let page = client
+        .fetch_page("https://atcoder.jp/contests/abc399/tasks")
+        .await;
+    let submissions = client.fetch_submissions("abc399", 1).await;
+    let standings = client.fetch_standings("abc399").await.unwrap();
+
+    assert!(matches!(page, Err(CrawlerError::NotFound)));
+    assert!(matches!(submissions, Err(CrawlerError::NotFound)));
+    assert!(standings.is_none());
+}
+
+#[tokio::test]
+async fn test_fixture_client_reports_recorded_login_page_as_session_expired() {
+    let dir = fixture_dir("login");
+    let client = FixtureClient::new(&dir);
+    std::fs::write(
+        client.path_for("https://atcoder.jp/contests/abc399/submissions?page=1"),
+        include_str!("assets/login.html"),
+    )
+    .unwrap();
+
+    let submissions = client.fetch_submissions("abc399", 1).await;
+
+    assert!(matches!(
+        submissions,
+        Err(CrawlerError::SessionExpired { url })
+            if url == "https://atcoder.jp/contests/abc399/submissions?page=1"
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -1,8 +1,10 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
 use crawler::{
-    parse_contests_archive_html, parse_permanent_contests_html, parse_submissions_html,
-    parse_tasks_html,
+    CrawlerError, Editorial, JudgeCase, is_login_page, is_pending_result,
+    parse_contests_archive_html, parse_editorials_html, parse_permanent_contests_html,
+    parse_submission_detail_html, parse_submissions_html, parse_task_point_html, parse_tasks_html,
+    parse_user_profile_html,
 };
 
 #[test]
@@ -28,6 +30,9 @@ fn test_parse_tasks_html() {
     assert_eq!(first_problem.problem_index, "A");
     assert_eq!(first_problem.name, "Hamming Distance");
     assert_eq!(first_problem.title(), "A. Hamming Distance");
+    assert_eq!(first_problem.time_limit_ms, Some(2000));
+    assert_eq!(first_problem.memory_limit_kb, Some(1024 * 1024));
+    assert_eq!(first_problem.point, None);
 
     // Verify the last problem
     let last_problem = &problems[6];
@@ -62,8 +67,10 @@ fn test_parse_submissions_html() {
     let html_content = include_str!("assets/submissions.html");
 
     // Parse the HTML content
-    let submissions =
+    let (submissions, report) =
         parse_submissions_html(html_content).expect("Failed to parse submissions HTML");
+    assert_eq!(report.parsed, 20);
+    assert!(report.skipped.is_empty());
 
     // Verify that we found the expected number of submissions
     assert_eq!(
@@ -85,6 +92,7 @@ fn test_parse_submissions_html() {
     assert_eq!(first_submission.code_length, 265);
     assert_eq!(first_submission.result, "RE");
     assert_eq!(first_submission.execution_time, Some(13));
+    assert_eq!(first_submission.memory, Some(8948));
 
     let screenamed_submission = &submissions[17];
     assert_eq!(screenamed_submission.user, "The_Bouningeeeen");
@@ -101,6 +109,7 @@ fn test_parse_submissions_html() {
     assert_eq!(last_submission.code_length, 21215);
     assert_eq!(last_submission.result, "AC");
     assert_eq!(last_submission.execution_time, Some(17));
+    assert_eq!(last_submission.memory, Some(3648));
 
     // Verify all submissions have the expected format
     for submission in &submissions {
@@ -140,6 +149,166 @@ fn test_parse_submissions_html() {
     }
 }
 
+/// An English submissions table with the score and code size columns swapped,
+/// a compile error row whose status spans the time and memory columns, and a
+/// row with a broken score.
+const REORDERED_SUBMISSIONS_HTML: &str = r#"
+<table class="table table-bordered table-striped small th-center">
+  <thead>
+    <tr>
+      <th>Submission Time</th><th>Task</th><th>User</th><th>Language</th><th>Code Size</th>
+      <th>Score</th><th>Status</th><th>Exec Time</th><th>Memory</th><th></th>
+    </tr>
+  </thead>
+  <tbody>
+    <tr>
+      <td><time>2025-04-24 19:15:34+0900</time></td>
+      <td><a href="/contests/abc399/tasks/abc399_a">A - Hamming Distance</a></td>
+      <td><a href="/users/kenkoooo">kenkoooo</a></td>
+      <td><a href="/contests/abc399/submissions?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+      <td>512 Byte</td>
+      <td>100</td>
+      <td><span class="label label-success">AC</span></td>
+      <td>1 ms</td>
+      <td>2048 KB</td>
+      <td><a href="/contests/abc399/submissions/100" class="submission-details-link">Detail</a></td>
+    </tr>
+    <tr>
+      <td><time>2025-04-24 19:16:00+0900</time></td>
+      <td><a href="/contests/abc399/tasks/abc399_b">B - Ranking with Ties</a></td>
+      <td><a href="/users/kenkoooo">kenkoooo</a></td>
+      <td><a href="/contests/abc399/submissions?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+      <td>20 Byte</td>
+      <td>0</td>
+      <td colspan="3"><span class="label label-warning">CE</span></td>
+      <td><a href="/contests/abc399/submissions/101" class="submission-details-link">Detail</a></td>
+    </tr>
+    <tr>
+      <td><time>2025-04-24 19:17:00+0900</time></td>
+      <td><a href="/contests/abc399/tasks/abc399_c">C - Make it Forest</a></td>
+      <td><a href="/users/kenkoooo">kenkoooo</a></td>
+      <td><a href="/contests/abc399/submissions?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+      <td>20 Byte</td>
+      <td>-</td>
+      <td><span class="label label-success">AC</span></td>
+      <td>1 ms</td>
+      <td>2048 KB</td>
+      <td><a href="/contests/abc399/submissions/102" class="submission-details-link">Detail</a></td>
+    </tr>
+  </tbody>
+</table>
+"#;
+
+#[test]
+fn test_parse_submissions_html_maps_columns_by_header() {
+    let (submissions, report) = parse_submissions_html(REORDERED_SUBMISSIONS_HTML)
+        .expect("Failed to parse submissions HTML");
+
+    assert_eq!(submissions.len(), 2);
+    assert_eq!(submissions[0].id, 100);
+    assert_eq!(submissions[0].contest_id, "abc399");
+    assert_eq!(submissions[0].problem_id, "abc399_a");
+    assert_eq!(submissions[0].user, "kenkoooo");
+    assert_eq!(submissions[0].language, "Rust (rustc 1.70.0)");
+    assert_eq!(submissions[0].code_length, 512);
+    assert_eq!(submissions[0].result, "AC");
+    assert_eq!(submissions[0].execution_time, Some(1));
+    assert_eq!(submissions[0].memory, Some(2048));
+
+    // The compile error has neither execution time nor memory.
+    assert_eq!(submissions[1].result, "CE");
+    assert_eq!(submissions[1].execution_time, None);
+    assert_eq!(submissions[1].memory, None);
+
+    assert_eq!(report.parsed, 2);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].index, 2);
+    assert!(report.skipped[0].reason.contains("score"));
+}
+
+#[test]
+fn test_parse_report_check_skip_ratio() {
+    let (_, report) = parse_submissions_html(REORDERED_SUBMISSIONS_HTML)
+        .expect("Failed to parse submissions HTML");
+
+    assert!(report.check_skip_ratio(0.5).is_ok());
+    match report.check_skip_ratio(0.2) {
+        Err(CrawlerError::TooManySkippedRows {
+            skipped,
+            total,
+            reasons,
+        }) => {
+            assert_eq!(skipped, 1);
+            assert_eq!(total, 3);
+            assert_eq!(reasons.len(), 1);
+        }
+        other => panic!("expected TooManySkippedRows, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_submissions_html_rejects_table_without_required_column() {
+    let html_content = REORDERED_SUBMISSIONS_HTML.replace("<th>Score</th>", "<th>Points</th>");
+
+    let error = parse_submissions_html(&html_content).unwrap_err();
+
+    assert!(matches!(error, CrawlerError::ParseError(_)));
+}
+
+#[test]
+fn test_parse_submissions_html_empty_page() {
+    let (submissions, report) =
+        parse_submissions_html("<html><body><p>No submissions</p></body></html>")
+            .expect("Failed to parse submissions HTML");
+
+    assert!(submissions.is_empty());
+    assert_eq!(report.total(), 0);
+    assert!(report.check_skip_ratio(0.0).is_ok());
+}
+
+#[test]
+fn test_parse_submission_detail_html() {
+    let html_content = include_str!("assets/submission_detail.html");
+
+    let detail = parse_submission_detail_html(html_content, 64188418)
+        .expect("Failed to parse submission detail HTML");
+
+    assert_eq!(detail.submission_id, 64188418);
+    assert_eq!(detail.memory_kb, Some(9180));
+    assert!(!detail.compile_error);
+    assert_eq!(detail.judge_cases.len(), 3);
+    assert_eq!(
+        detail.judge_cases[0],
+        JudgeCase {
+            case_name: "00_sample_00.txt".to_string(),
+            verdict: "AC".to_string(),
+            time_ms: Some(13),
+            memory_kb: Some(9044),
+        }
+    );
+    assert_eq!(
+        detail.judge_cases[2],
+        JudgeCase {
+            case_name: "01_random_00.txt".to_string(),
+            verdict: "TLE".to_string(),
+            time_ms: Some(2207),
+            memory_kb: Some(9180),
+        }
+    );
+}
+
+#[test]
+fn test_parse_submission_detail_html_compile_error() {
+    let html_content = include_str!("assets/submission_detail_compile_error.html");
+
+    let detail = parse_submission_detail_html(html_content, 64188500)
+        .expect("Failed to parse submission detail HTML");
+
+    assert_eq!(detail.memory_kb, None);
+    assert!(detail.compile_error);
+    assert!(detail.judge_cases.is_empty());
+}
+
 #[test]
 fn test_parse_tasks_html_abc308() {
     // Load the test HTML file
@@ -163,6 +332,8 @@ fn test_parse_tasks_html_abc308() {
     assert_eq!(first_problem.problem_index, "A");
     assert_eq!(first_problem.name, "New Scheme");
     assert_eq!(first_problem.title(), "A. New Scheme");
+    assert_eq!(first_problem.time_limit_ms, Some(2000));
+    assert_eq!(first_problem.memory_limit_kb, Some(1024 * 1024));
 
     // Verify the last problem (Ex)
     let last_problem = &problems[7];
@@ -171,6 +342,8 @@ fn test_parse_tasks_html_abc308() {
     assert_eq!(last_problem.problem_index, "Ex");
     assert_eq!(last_problem.name, "Make Q");
     assert_eq!(last_problem.title(), "Ex. Make Q");
+    assert_eq!(last_problem.time_limit_ms, Some(4000));
+    assert_eq!(problems[6].time_limit_ms, Some(3000));
 }
 
 #[test]
@@ -218,6 +391,24 @@ fn test_parse_tasks_html_codequeen2023() {
     );
 }
 
+#[test]
+fn test_parse_task_point_html() {
+    let html_content = include_str!("assets/task_abc399_a.html");
+
+    let point = parse_task_point_html(html_content).expect("Failed to parse task HTML");
+
+    assert_eq!(point, Some(100.0));
+}
+
+#[test]
+fn test_parse_task_point_html_without_score() {
+    let html_content = r#"<div id="task-statement"><p>No score here.</p></div>"#;
+
+    let point = parse_task_point_html(html_content).expect("Failed to parse task HTML");
+
+    assert_eq!(point, None);
+}
+
 #[test]
 fn test_parse_contests_archive_html() {
     // Load the test HTML file
@@ -326,3 +517,93 @@ fn test_parse_contests_archive_html_empty_page() {
 
     assert_eq!(contests.len(), 0, "Expected 0 contests for empty page");
 }
+
+#[test]
+fn test_is_pending_result() {
+    for result in ["WJ", "WR", "3/20", "3/20 WJ"] {
+        assert!(is_pending_result(result), "{result} should be pending");
+    }
+    for result in ["AC", "WA", "TLE", "CE", "IE"] {
+        assert!(!is_pending_result(result), "{result} should be final");
+    }
+}
+
+#[test]
+fn test_is_login_page() {
+    assert!(is_login_page(include_str!("assets/login.html")));
+    assert!(!is_login_page(include_str!("assets/tasks.html")));
+    assert!(!is_login_page(include_str!("assets/submissions.html")));
+    assert!(!is_login_page(include_str!("assets/contests_archive.html")));
+}
+
+#[test]
+fn test_parse_user_profile_html() {
+    let profile =
+        parse_user_profile_html(include_str!("assets/user_profile.html"), "kenkoooo").unwrap();
+
+    assert_eq!(profile.user_id, "kenkoooo");
+    assert_eq!(profile.country.as_deref(), Some("JP"));
+    assert_eq!(profile.affiliation.as_deref(), Some("AtCoder Problems"));
+    assert_eq!(profile.birth_year, Some(1993));
+    assert_eq!(profile.rating, Some(1803));
+    assert_eq!(profile.highest_rating, Some(1876));
+}
+
+#[test]
+fn test_parse_user_profile_html_unrated_user() {
+    let html_content = r#"
+        <html><body>
+        <table class="dl-table">
+            <tr><th class="no-break">Country/Region</th><td></td></tr>
+            <tr><th class="no-break">Affiliation</th><td class="break-all"></td></tr>
+        </table>
+        </body></html>
+    "#;
+
+    let profile = parse_user_profile_html(html_content, "newcomer").unwrap();
+
+    assert_eq!(profile.country, None);
+    assert_eq!(profile.affiliation, None);
+    assert_eq!(profile.rating, None);
+    assert_eq!(profile.highest_rating, None);
+    assert!(matches!(
+        parse_user_profile_html("<html><body></body></html>", "newcomer"),
+        Err(CrawlerError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_parse_editorials_html() {
+    let editorials = parse_editorials_html(include_str!("assets/editorial.html")).unwrap();
+
+    let editorial = |problem_id: &str, url: &str, language: &str, is_official: bool| Editorial {
+        problem_id: problem_id.to_string(),
+        url: url.to_string(),
+        language: Some(language.to_string()),
+        is_official,
+    };
+    assert_eq!(
+        editorials,
+        vec![
+            editorial(
+                "abc399_a",
+                "https://atcoder.jp/contests/abc399/editorial/12486",
+                "ja",
+                true
+            ),
+            editorial(
+                "abc399_a",
+                "https://atcoder.jp/contests/abc399/editorial/12507",
+                "en",
+                true
+            ),
+            editorial(
+                "abc399_b",
+                "https://atcoder.jp/contests/abc399/editorial/12487",
+                "ja",
+                true
+            ),
+            editorial("abc399_b", "https://example.com/abc399-b", "ja", false),
+        ]
+    );
+}
@@ -0,0 +1,67 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::time::Duration;
+
+use crawler::{RateLimit, RateLimiter};
+use tokio::time::Instant;
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limiter_spaces_requests_to_the_same_host() {
+    let limiter = RateLimiter::new(RateLimit {
+        requests_per_second: 2.0,
+        burst: 1,
+    });
+
+    let start = Instant::now();
+    for _ in 0..5 {
+        limiter.acquire("atcoder.jp").await;
+    }
+
+    // The first request goes through immediately, the other four wait 500ms each.
+    assert_eq!(start.elapsed(), Duration::from_secs(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limiter_allows_burst_after_idle() {
+    let limiter = RateLimiter::new(RateLimit {
+        requests_per_second: 1.0,
+        burst: 3,
+    });
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        limiter.acquire("atcoder.jp").await;
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    limiter.acquire("atcoder.jp").await;
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limiter_keeps_separate_budget_per_host() {
+    let limiter = RateLimiter::new(RateLimit {
+        requests_per_second: 1.0,
+        burst: 1,
+    });
+
+    let start = Instant::now();
+    limiter.acquire("atcoder.jp").await;
+    limiter.acquire("img.atcoder.jp").await;
+
+    assert_eq!(start.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limiter_pause_blocks_host() {
+    let limiter = RateLimiter::new(RateLimit {
+        requests_per_second: 10.0,
+        burst: 1,
+    });
+
+    let start = Instant::now();
+    limiter.pause("atcoder.jp", Duration::from_secs(30));
+    limiter.acquire("atcoder.jp").await;
+
+    assert!(start.elapsed() >= Duration::from_secs(30));
+}
@@ -0,0 +1,223 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use crawler::{CrawlerError, Problem, ProblemFetcher, RetryPolicy, RetryingFetcher};
+use mockall::{Sequence, mock};
+
+mock! {
+    pub ProblemFetcher {}
+
+    #[async_trait]
+    impl ProblemFetcher for ProblemFetcher {
+        async fn fetch_problems(&self, contest_id: &str) -> Result<Vec<Problem>, CrawlerError>;
+        async fn fetch_problem_point(
+            &self,
+            contest_id: &str,
+            problem_id: &str,
+        ) -> Result<Option<f64>, CrawlerError>;
+    }
+}
+
+fn instant_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+        jitter: 0.0,
+        ..RetryPolicy::default()
+    }
+}
+
+fn problem() -> Problem {
+    Problem {
+        id: "abc001_a".to_string(),
+        contest_id: "abc001".to_string(),
+        problem_index: "A".to_string(),
+        name: "Problem A".to_string(),
+        time_limit_ms: None,
+        memory_limit_kb: None,
+        point: None,
+    }
+}
+
+#[tokio::test]
+async fn test_retrying_fetcher_retries_transient_errors() {
+    let mut mock = MockProblemFetcher::new();
+    let mut seq = Sequence::new();
+    mock.expect_fetch_problems()
+        .times(2)
+        .in_sequence(&mut seq)
+        .returning(|_| Err(CrawlerError::HttpError("503".to_string())));
+    mock.expect_fetch_problems()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(vec![problem()]));
+
+    let fetcher = RetryingFetcher::new(mock, instant_policy(3));
+    let problems = fetcher.fetch_problems("abc001").await.unwrap();
+
+    assert_eq!(problems, vec![problem()]);
+}
+
+#[tokio::test]
+async fn test_retrying_fetcher_reports_giving_up() {
+    let mut mock = MockProblemFetcher::new();
+    mock.expect_fetch_problems()
+        .times(3)
+        .returning(|_| Err(CrawlerError::HttpError("503".to_string())));
+
+    let fetcher = RetryingFetcher::new(mock, instant_policy(3));
+    let error = fetcher.fetch_problems("abc001").await.unwrap_err();
+
+    match error {
+        CrawlerError::RetriesExhausted { attempts, last } => {
+            assert_eq!(attempts, 3);
+            assert!(matches!(*last, CrawlerError::HttpError(_)));
+        }
+        other => panic!("expected RetriesExhausted, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_retrying_fetcher_counts_pages_and_errors_by_kind() {
+    let mut mock = MockProblemFetcher::new();
+    let mut seq = Sequence::new();
+    mock.expect_fetch_problems()
+        .times(2)
+        .in_sequence(&mut seq)
+        .returning(|_| Err(CrawlerError::HttpError("503".to_string())));
+    mock.expect_fetch_problems()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(vec![problem()]));
+    mock.expect_fetch_problems()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| Err(CrawlerError::NotFound));
+
+    let fetcher = RetryingFetcher::new(mock, instant_policy(3));
+    fetcher.fetch_problems("abc001").await.unwrap();
+    fetcher.fetch_problems("abc002").await.unwrap_err();
+
+    assert_eq!(fetcher.stats().pages_fetched(), 1);
+    let errors = fetcher.stats().errors();
+    assert_eq!(errors.get("HttpError"), Some(&2));
+    assert_eq!(errors.get("NotFound"), Some(&1));
+    assert_eq!(errors.len(), 2);
+}
+
+#[tokio::test]
+async fn test_retrying_fetcher_does_not_retry_not_found() {
+    let mut mock = MockProblemFetcher::new();
+    mock.expect_fetch_problems()
+        .times(1)
+        .returning(|_| Err(CrawlerError::NotFound));
+
+    let fetcher = RetryingFetcher::new(mock, instant_policy(3));
+    let error = fetcher.fetch_problems("abc001").await.unwrap_err();
+
+    assert!(matches!(error, CrawlerError::NotFound));
+}
+
+#[tokio::test]
+async fn test_retrying_fetcher_does_not_retry_expired_session() {
+    let mut mock = MockProblemFetcher::new();
+    mock.expect_fetch_problems().times(1).returning(|_| {
+        Err(CrawlerError::SessionExpired {
+            url: "https://atcoder.jp/contests/abc001/tasks".to_string(),
+        })
+    });
+
+    let fetcher = RetryingFetcher::new(mock, instant_policy(3));
+    let error = fetcher.fetch_problems("abc001").await.unwrap_err();
+
+    assert!(matches!(error, CrawlerError::SessionExpired { .. }));
+}
+
+#[tokio::test]
+async fn test_retrying_fetcher_does_not_retry_unparsable_pages() {
+    let mut mock = MockProblemFetcher::new();
+    mock.expect_fetch_problems()
+        .times(1)
+        .returning(|_| Err(CrawlerError::ParseError("No tbody found".to_string())));
+
+    let fetcher = RetryingFetcher::new(mock, instant_policy(3));
+    let error = fetcher.fetch_problems("abc001").await.unwrap_err();
+
+    assert!(matches!(error, CrawlerError::ParseError(_)));
+}
+
+#[tokio::test]
+async fn test_retrying_fetcher_uses_custom_retryable_predicate() {
+    let mut mock = MockProblemFetcher::new();
+    mock.expect_fetch_problems()
+        .times(1)
+        .returning(|_| Err(CrawlerError::HttpError("503".to_string())));
+
+    let policy = RetryPolicy {
+        retryable: |e| matches!(e, CrawlerError::RateLimited { .. }),
+        ..instant_policy(3)
+    };
+    let fetcher = RetryingFetcher::new(mock, policy);
+    let error = fetcher.fetch_problems("abc001").await.unwrap_err();
+
+    assert!(matches!(error, CrawlerError::HttpError(_)));
+}
+
+#[test]
+fn test_retry_policy_delay_grows_exponentially_up_to_max() {
+    let policy = RetryPolicy {
+        base_delay: Duration::from_secs(2),
+        max_delay: Duration::from_secs(30),
+        jitter: 0.0,
+        ..RetryPolicy::default()
+    };
+
+    assert_eq!(policy.delay_for(1), Duration::from_secs(2));
+    assert_eq!(policy.delay_for(2), Duration::from_secs(4));
+    assert_eq!(policy.delay_for(4), Duration::from_secs(16));
+    assert_eq!(policy.delay_for(5), Duration::from_secs(30));
+    assert_eq!(policy.delay_for(100), Duration::from_secs(30));
+}
+
+#[test]
+fn test_retry_policy_jitter_only_shortens_delay() {
+    let policy = RetryPolicy {
+        base_delay: Duration::from_secs(8),
+        max_delay: Duration::from_secs(8),
+        jitter: 0.5,
+        ..RetryPolicy::default()
+    };
+
+    for _ in 0..100 {
+        let delay = policy.delay_for(1);
+        assert!(delay <= Duration::from_secs(8));
+        assert!(delay >= Duration::from_secs(4));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retrying_fetcher_waits_for_retry_after() {
+    let mut mock = MockProblemFetcher::new();
+    let mut seq = Sequence::new();
+    mock.expect_fetch_problems()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| {
+            Err(CrawlerError::RateLimited {
+                retry_after: Some(Duration::from_secs(60)),
+            })
+        });
+    mock.expect_fetch_problems()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(vec![problem()]));
+
+    let fetcher = RetryingFetcher::new(mock, instant_policy(3));
+    let start = tokio::time::Instant::now();
+    fetcher.fetch_problems("abc001").await.unwrap();
+
+    assert!(start.elapsed() >= Duration::from_secs(60));
+}
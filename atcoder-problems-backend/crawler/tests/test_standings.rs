@@ -0,0 +1,94 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use crawler::{CrawlerError, Standings};
+
+#[test]
+fn test_parse_standings() {
+    let json = include_str!("assets/standings.json");
+
+    let standings: Standings = serde_json::from_str(json).expect("Failed to parse standings");
+
+    assert!(standings.fixed);
+    assert_eq!(standings.task_info.len(), 2);
+    assert_eq!(standings.task_info[0].assignment, "A");
+    assert_eq!(standings.task_info[0].task_name, "Hamming Distance");
+    assert_eq!(standings.task_info[0].task_screen_name, "abc399_a");
+
+    assert_eq!(standings.standings_data.len(), 2);
+    let first = &standings.standings_data[0];
+    assert_eq!(first.rank, 1);
+    assert_eq!(first.user_screen_name, "tourist");
+    assert!(!first.is_rated);
+    assert_eq!(first.old_rating, 3790);
+    assert_eq!(first.rating, Some(3800));
+    assert_eq!(first.competitions, 120);
+    assert_eq!(first.total_result.accepted, 2);
+    assert_eq!(first.total_result.score, 30000);
+    assert_eq!(first.task_results["abc399_b"].score, 20000);
+    assert_eq!(first.task_results["abc399_b"].status, 1);
+
+    let second = &standings.standings_data[1];
+    assert_eq!(second.task_results.len(), 1);
+    assert_eq!(second.task_results["abc399_a"].failure, 1);
+
+    standings.validate().expect("Standings should be valid");
+}
+
+#[test]
+fn test_standings_keep_unknown_fields() {
+    let json = include_str!("assets/standings.json");
+
+    let standings: Standings = serde_json::from_str(json).unwrap();
+
+    assert_eq!(standings.standings_data[0].extra["Country"], "BY");
+    assert_eq!(
+        standings.standings_data[0].task_results["abc399_a"].extra["SubmissionID"],
+        64188418
+    );
+
+    let original: serde_json::Value = serde_json::from_str(json).unwrap();
+    let round_trip = serde_json::to_value(&standings).unwrap();
+    assert_eq!(round_trip, original);
+}
+
+#[test]
+fn test_standings_without_fixed_are_not_fixed() {
+    let json = r#"{"TaskInfo": [], "StandingsData": []}"#;
+
+    let standings: Standings = serde_json::from_str(json).unwrap();
+
+    assert!(!standings.fixed);
+}
+
+#[test]
+fn test_standings_missing_required_field_fail_to_parse() {
+    let json = r#"{"Fixed": true, "TaskInfo": [{"Assignment": "A", "TaskName": "A"}], "StandingsData": []}"#;
+
+    let result = serde_json::from_str::<Standings>(json);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_validate_rejects_standings_without_tasks() {
+    let json = r#"{"Fixed": true, "TaskInfo": [], "StandingsData": []}"#;
+    let standings: Standings = serde_json::from_str(json).unwrap();
+
+    let result = standings.validate();
+
+    assert!(matches!(result, Err(CrawlerError::InvalidStandings(_))));
+}
+
+#[test]
+fn test_validate_rejects_results_for_unknown_tasks() {
+    let json = include_str!("assets/standings.json");
+    let mut standings: Standings = serde_json::from_str(json).unwrap();
+    let result = standings.standings_data[0].task_results["abc399_a"].clone();
+    standings.standings_data[0]
+        .task_results
+        .insert("abc399_z".to_string(), result);
+
+    let result = standings.validate();
+
+    assert!(matches!(result, Err(CrawlerError::InvalidStandings(_))));
+}
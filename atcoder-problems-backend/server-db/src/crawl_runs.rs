@@ -0,0 +1,82 @@
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, JsonValue, QueryFilter, QueryOrder,
+    QuerySelect, sea_query::Query,
+};
+use serde::Serialize;
+use sql_entities::{crawl_run_contests, crawl_runs};
+
+/// A row of `crawl_runs` as listed by the internal API. `error_message` is left
+/// out since it may quote the responses of AtCoder.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CrawlRun {
+    pub id: i64,
+    pub command: String,
+    pub mode: Option<String>,
+    pub status: String,
+    pub started_epoch_second: i64,
+    pub finished_epoch_second: Option<i64>,
+    pub pages_fetched: i64,
+    pub rows_inserted: i64,
+    pub rows_updated: i64,
+    pub errors: JsonValue,
+}
+
+impl From<crawl_runs::Model> for CrawlRun {
+    fn from(m: crawl_runs::Model) -> Self {
+        Self {
+            id: m.id,
+            command: m.command,
+            mode: m.mode,
+            status: m.status,
+            started_epoch_second: m.started_epoch_second,
+            finished_epoch_second: m.finished_epoch_second,
+            pages_fetched: m.pages_fetched,
+            rows_inserted: m.rows_inserted,
+            rows_updated: m.rows_updated,
+            errors: m.errors,
+        }
+    }
+}
+
+/// Conditions on the runs returned by [`get_recent_runs`]; `None` matches
+/// every run.
+#[derive(Debug, Default, Clone)]
+pub struct CrawlRunFilter<'a> {
+    pub command: Option<&'a str>,
+    pub status: Option<&'a str>,
+    /// Only runs that crawled the submissions of this contest to the end
+    pub contest_id: Option<&'a str>,
+}
+
+/// Most recently started runs matching the filter, newest first.
+pub async fn get_recent_runs(
+    db: &DatabaseConnection,
+    filter: CrawlRunFilter<'_>,
+    limit: u64,
+) -> Result<Vec<CrawlRun>, DbErr> {
+    let mut query = crawl_runs::Entity::find();
+    if let Some(command) = filter.command {
+        query = query.filter(crawl_runs::Column::Command.eq(command));
+    }
+    if let Some(status) = filter.status {
+        query = query.filter(crawl_runs::Column::Status.eq(status));
+    }
+    if let Some(contest_id) = filter.contest_id {
+        query = query.filter(
+            crawl_runs::Column::Id.in_subquery(
+                Query::select()
+                    .column(crawl_run_contests::Column::RunId)
+                    .from(crawl_run_contests::Entity)
+                    .and_where(crawl_run_contests::Column::ContestId.eq(contest_id))
+                    .to_owned(),
+            ),
+        );
+    }
+    let rows = query
+        .order_by_desc(crawl_runs::Column::StartedEpochSecond)
+        .order_by_desc(crawl_runs::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
@@ -1,9 +1,9 @@
 use sea_orm::{
     ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
-    sea_query::OnConflict,
+    TransactionTrait, sea_query::OnConflict,
 };
 use serde::Serialize;
-use sql_entities::internal_users;
+use sql_entities::{internal_users, user_crawl_jobs};
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct InternalUserInfo {
@@ -52,18 +52,54 @@ pub async fn get_user(
     Ok(model.map(Into::into))
 }
 
+/// Links the AtCoder id to the user.
+///
+/// A newly linked id is queued in `user_crawl_jobs`, so that the submissions of
+/// the user are crawled in the contests they may have submitted to.
 pub async fn update_atcoder_user_id(
     db: &DatabaseConnection,
     internal_user_id: &str,
     atcoder_user_id: &str,
 ) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    let previous = internal_users::Entity::find()
+        .filter(internal_users::Column::InternalUserId.eq(internal_user_id))
+        .one(&txn)
+        .await?
+        .and_then(|user| user.atcoder_user_id);
     internal_users::Entity::update_many()
         .col_expr(
             internal_users::Column::AtcoderUserId,
             sea_orm::sea_query::Expr::value(atcoder_user_id.to_string()),
         )
         .filter(internal_users::Column::InternalUserId.eq(internal_user_id))
-        .exec(db)
+        .exec(&txn)
         .await?;
+
+    if !atcoder_user_id.is_empty() && previous.as_deref() != Some(atcoder_user_id) {
+        let job = user_crawl_jobs::ActiveModel {
+            atcoder_user_id: ActiveValue::Set(atcoder_user_id.to_string()),
+            requested_epoch_second: ActiveValue::Set(chrono::Utc::now().timestamp()),
+            last_contest_id: ActiveValue::Set(None),
+            attempts: ActiveValue::Set(0),
+            claimed_by: ActiveValue::Set(None),
+            claimed_until: ActiveValue::Set(None),
+        };
+        // A job already queued for the same id is left as it is.
+        let res = user_crawl_jobs::Entity::insert(job)
+            .on_conflict(
+                OnConflict::column(user_crawl_jobs::Column::AtcoderUserId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(&txn)
+            .await;
+        match res {
+            Ok(_) | Err(DbErr::RecordNotInserted) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    txn.commit().await?;
     Ok(())
 }
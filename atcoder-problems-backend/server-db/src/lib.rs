@@ -1,3 +1,4 @@
+pub mod crawl_runs;
 pub mod internal_user;
 pub mod problem_list;
 pub mod progress_reset;
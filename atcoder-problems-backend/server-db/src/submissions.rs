@@ -18,6 +18,7 @@ pub struct Submission {
     pub length: i32,
     pub result: String,
     pub execution_time: Option<i32>,
+    pub memory: Option<i32>,
 }
 
 impl From<submissions::Model> for Submission {
@@ -33,6 +34,7 @@ impl From<submissions::Model> for Submission {
             length: m.length,
             result: m.result,
             execution_time: m.execution_time,
+            memory: m.memory,
         }
     }
 }
@@ -11,6 +11,9 @@ pub struct Model {
     pub duration_second: i64,
     pub title: String,
     pub rate_change: String,
+    pub rated_lower: Option<i32>,
+    pub rated_upper: Option<i32>,
+    pub contest_type: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
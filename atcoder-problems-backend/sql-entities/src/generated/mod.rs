@@ -5,6 +5,11 @@ pub mod prelude;
 pub mod accepted_count;
 pub mod contest_problem;
 pub mod contests;
+pub mod crawl_queue;
+pub mod crawl_run_contests;
+pub mod crawl_runs;
+pub mod crawl_state;
+pub mod editorial_crawls;
 pub mod fastest;
 pub mod first;
 pub mod internal_problem_list_items;
@@ -19,8 +24,15 @@ pub mod max_streaks;
 pub mod merged_problems;
 pub mod points;
 pub mod predicted_rating;
+pub mod problem_editorials;
 pub mod problems;
 pub mod rated_point_sum;
 pub mod shortest;
 pub mod solver;
+pub mod submission_details;
+pub mod submission_history;
+pub mod submission_judge_cases;
 pub mod submissions;
+pub mod user_crawl_jobs;
+pub mod user_profiles;
+pub mod user_rating_history;
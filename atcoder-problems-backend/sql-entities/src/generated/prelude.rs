@@ -3,6 +3,11 @@
 pub use super::accepted_count::Entity as AcceptedCount;
 pub use super::contest_problem::Entity as ContestProblem;
 pub use super::contests::Entity as Contests;
+pub use super::crawl_queue::Entity as CrawlQueue;
+pub use super::crawl_run_contests::Entity as CrawlRunContests;
+pub use super::crawl_runs::Entity as CrawlRuns;
+pub use super::crawl_state::Entity as CrawlState;
+pub use super::editorial_crawls::Entity as EditorialCrawls;
 pub use super::fastest::Entity as Fastest;
 pub use super::first::Entity as First;
 pub use super::internal_problem_list_items::Entity as InternalProblemListItems;
@@ -17,8 +22,15 @@ pub use super::max_streaks::Entity as MaxStreaks;
 pub use super::merged_problems::Entity as MergedProblems;
 pub use super::points::Entity as Points;
 pub use super::predicted_rating::Entity as PredictedRating;
+pub use super::problem_editorials::Entity as ProblemEditorials;
 pub use super::problems::Entity as Problems;
 pub use super::rated_point_sum::Entity as RatedPointSum;
 pub use super::shortest::Entity as Shortest;
 pub use super::solver::Entity as Solver;
+pub use super::submission_details::Entity as SubmissionDetails;
+pub use super::submission_history::Entity as SubmissionHistory;
+pub use super::submission_judge_cases::Entity as SubmissionJudgeCases;
 pub use super::submissions::Entity as Submissions;
+pub use super::user_crawl_jobs::Entity as UserCrawlJobs;
+pub use super::user_profiles::Entity as UserProfiles;
+pub use super::user_rating_history::Entity as UserRatingHistory;
@@ -2,7 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "problems")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -11,6 +11,11 @@ pub struct Model {
     pub problem_index: String,
     pub name: String,
     pub title: String,
+    pub time_limit_ms: Option<i32>,
+    pub memory_limit_kb: Option<i32>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub point: Option<f64>,
+    pub orphaned_epoch_second: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -17,6 +17,7 @@ pub struct Model {
     pub length: i32,
     pub result: String,
     pub execution_time: Option<i32>,
+    pub memory: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
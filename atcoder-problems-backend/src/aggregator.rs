@@ -0,0 +1,554 @@
+//! Computes the marts built by the dbt project in `dbt/` from `submissions`,
+//! `contests`, `contest_problem` and `problems`, so that deployments without
+//! dbt can serve rankings and problem statistics.
+//!
+//! Each mart follows the model of the same name in `dbt/models/marts`; the
+//! comments on the fields of [`Marts`] note where the models differ from what
+//! their names suggest.
+
+use std::collections::{HashMap, HashSet};
+
+use futures::TryStreamExt;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel,
+    QueryOrder, StreamTrait, TransactionTrait,
+};
+use sql_entities::{
+    accepted_count, contest_problem, contests, fastest, first, language_count, max_streaks,
+    merged_problems, points, problems, rated_point_sum, shortest, solver, submissions,
+};
+
+/// Users whose submissions are left out of every mart, the bots that mirror
+/// AtCoder submissions to other judges.
+///
+/// Kept in sync with `dbt/seeds/excluded_users.csv`.
+pub const EXCLUDED_USERS: &[&str] = &[
+    "vjudge1",
+    "vjudge2",
+    "vjudge3",
+    "vjudge4",
+    "vjudge5",
+    "luogu__bot1",
+    "luogu__bot2",
+    "luogu__bot4",
+    "luogu__bot5",
+    "luogu_bot",
+    "luogu_bot0",
+    "luogu_bot1",
+    "luogu_bot2",
+    "luogu_bot3",
+    "luogu_bot4",
+    "luogu_bot5",
+    "luogu_bot6",
+];
+
+/// Start of the first AGC (2016/7/16); earlier contests are not rated
+const RATED_SINCE_EPOCH_SECOND: i64 = 1_468_670_400;
+
+/// Rated contests have at least this many problems
+const MIN_RATED_CONTEST_PROBLEMS: usize = 2;
+
+/// Offset of JST, in which the days of a streak are counted
+const JST_OFFSET_SECONDS: i64 = 9 * 60 * 60;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Maximum number of rows written by one `INSERT`
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Rows of every mart, each sorted by its primary key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Marts {
+    pub accepted_count: Vec<accepted_count::Model>,
+    /// Sum over the problems of rated contests of the maximum integral point
+    /// of each user, counting every final result and not only ACs
+    pub rated_point_sum: Vec<rated_point_sum::Model>,
+    /// Longest run of JST days with the first AC of at least one problem
+    pub max_streaks: Vec<max_streaks::Model>,
+    pub language_count: Vec<language_count::Model>,
+    /// The shortest, fastest and first ACs submitted after their contest
+    /// started, ties going to the smallest submission id
+    pub shortest: Vec<shortest::Model>,
+    pub fastest: Vec<fastest::Model>,
+    pub first: Vec<first::Model>,
+    pub solver: Vec<solver::Model>,
+    /// Points of the problems of rated contests that have a submission: the
+    /// crawled point, or else the maximum point of their submissions
+    pub points: Vec<points::Model>,
+    pub merged_problems: Vec<merged_problems::Model>,
+}
+
+/// Returns the name of `language` without its version, e.g. `C++` for
+/// `C++ 20 (gcc 12.2)`.
+///
+/// Mirrors `regexp_replace(language, '\d*\s*\(.*\)', '', 'g')` of the dbt
+/// model, with Perl 6 renamed to Raku.
+pub fn simplify_language(language: &str) -> String {
+    if language.starts_with("Perl6") {
+        return "Raku".to_string();
+    }
+    let Some(close) = language.rfind(')') else {
+        return language.trim_matches(' ').to_string();
+    };
+    let chars: Vec<(usize, char)> = language.char_indices().collect();
+    // The regex matches at the leftmost start whose digits and spaces are
+    // followed by a `(`, and its greedy `.*` runs up to the last `)`.
+    for (i, &(start, _)) in chars.iter().enumerate() {
+        let mut rest = chars[i..].iter().copied().peekable();
+        while rest.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+        while rest.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        if let Some((open, '(')) = rest.next()
+            && open < close
+        {
+            let simplified = language[..start].to_string() + &language[close + 1..];
+            return simplified.trim_matches(' ').to_string();
+        }
+    }
+    language.trim_matches(' ').to_string()
+}
+
+/// Returns whether the points of `contest` count towards the rated point sum.
+///
+/// # Arguments
+///
+/// * `problem_count` - Number of problems of the contest in `contest_problem`
+pub fn is_rated_contest(contest: &contests::Model, problem_count: usize) -> bool {
+    contest.start_epoch_second >= RATED_SINCE_EPOCH_SECOND
+        && contest.rated_lower.is_some()
+        && problem_count >= MIN_RATED_CONTEST_PROBLEMS
+        && contest.contest_type != "ahc"
+}
+
+/// AC that is the shortest, fastest or first of its problem so far
+#[derive(Debug, Clone)]
+struct Record {
+    submission_id: i64,
+    contest_id: String,
+    user_id: String,
+    length: i32,
+    execution_time: Option<i32>,
+}
+
+impl Record {
+    fn new(submission: &submissions::Model) -> Self {
+        Self {
+            submission_id: submission.id,
+            contest_id: submission.contest_id.clone(),
+            user_id: submission.user_id.clone(),
+            length: submission.length,
+            execution_time: submission.execution_time,
+        }
+    }
+}
+
+/// Replaces the record in `slot` with `submission` if its key is smaller.
+fn keep_smallest<K: PartialOrd>(
+    slot: &mut HashMap<String, (K, Record)>,
+    submission: &submissions::Model,
+    key: K,
+) {
+    match slot.get_mut(&submission.problem_id) {
+        Some((best, _)) if *best <= key => {}
+        Some(entry) => *entry = (key, Record::new(submission)),
+        None => {
+            slot.insert(
+                submission.problem_id.clone(),
+                (key, Record::new(submission)),
+            );
+        }
+    }
+}
+
+/// Folds submissions into the marts one at a time, so that the submissions
+/// never have to be held in memory; the state kept is per user and problem.
+pub struct Aggregator {
+    excluded_users: HashSet<&'static str>,
+    contest_starts: HashMap<String, i64>,
+    /// Earliest start among the rated contests of each problem
+    rated_problem_starts: HashMap<String, i64>,
+    /// Epoch second of the first AC of each user and problem
+    first_accepted: HashMap<(String, String), i64>,
+    accepted_languages: HashMap<(String, String), HashSet<String>>,
+    /// Maximum integral point of each user on each rated problem
+    rated_points: HashMap<(String, String), f64>,
+    max_submission_points: HashMap<String, f64>,
+    shortest: HashMap<String, ((i32, i64), Record)>,
+    fastest: HashMap<String, ((i32, i64), Record)>,
+    first: HashMap<String, (i64, Record)>,
+}
+
+impl Aggregator {
+    pub fn new(contests: &[contests::Model], contest_problems: &[contest_problem::Model]) -> Self {
+        let mut problem_counts: HashMap<&str, usize> = HashMap::new();
+        for contest_problem in contest_problems {
+            *problem_counts
+                .entry(contest_problem.contest_id.as_str())
+                .or_default() += 1;
+        }
+        let rated_contest_starts: HashMap<&str, i64> = contests
+            .iter()
+            .filter(|c| {
+                let problem_count = problem_counts.get(c.id.as_str()).copied().unwrap_or(0);
+                is_rated_contest(c, problem_count)
+            })
+            .map(|c| (c.id.as_str(), c.start_epoch_second))
+            .collect();
+
+        let mut rated_problem_starts: HashMap<String, i64> = HashMap::new();
+        for contest_problem in contest_problems {
+            if let Some(&start) = rated_contest_starts.get(contest_problem.contest_id.as_str()) {
+                rated_problem_starts
+                    .entry(contest_problem.problem_id.clone())
+                    .and_modify(|s| *s = (*s).min(start))
+                    .or_insert(start);
+            }
+        }
+
+        Self {
+            excluded_users: EXCLUDED_USERS.iter().copied().collect(),
+            contest_starts: contests
+                .iter()
+                .map(|c| (c.id.clone(), c.start_epoch_second))
+                .collect(),
+            rated_problem_starts,
+            first_accepted: HashMap::new(),
+            accepted_languages: HashMap::new(),
+            rated_points: HashMap::new(),
+            max_submission_points: HashMap::new(),
+            shortest: HashMap::new(),
+            fastest: HashMap::new(),
+            first: HashMap::new(),
+        }
+    }
+
+    pub fn add_submission(&mut self, submission: &submissions::Model) {
+        // Pending results are refreshed by the crawler and not counted yet.
+        if self.excluded_users.contains(submission.user_id.as_str())
+            || crawler::is_pending_result(&submission.result)
+        {
+            return;
+        }
+
+        if let Some(&rated_start) = self.rated_problem_starts.get(&submission.problem_id) {
+            self.max_submission_points
+                .entry(submission.problem_id.clone())
+                .and_modify(|p| *p = p.max(submission.point))
+                .or_insert(submission.point);
+            // Submissions before the contest are the writers' tests.
+            if submission.epoch_second >= rated_start && submission.point.fract() == 0.0 {
+                self.rated_points
+                    .entry((submission.user_id.clone(), submission.problem_id.clone()))
+                    .and_modify(|p| *p = p.max(submission.point))
+                    .or_insert(submission.point);
+            }
+        }
+
+        if submission.result != "AC" {
+            return;
+        }
+        let key = (submission.user_id.clone(), submission.problem_id.clone());
+        self.first_accepted
+            .entry(key)
+            .and_modify(|e| *e = (*e).min(submission.epoch_second))
+            .or_insert(submission.epoch_second);
+        self.accepted_languages
+            .entry((
+                submission.user_id.clone(),
+                simplify_language(&submission.language),
+            ))
+            .or_default()
+            .insert(submission.problem_id.clone());
+
+        let started = self
+            .contest_starts
+            .get(&submission.contest_id)
+            .is_some_and(|&start| submission.epoch_second > start);
+        if !started {
+            return;
+        }
+        keep_smallest(
+            &mut self.shortest,
+            submission,
+            (submission.length, submission.id),
+        );
+        if let Some(execution_time) = submission.execution_time {
+            keep_smallest(
+                &mut self.fastest,
+                submission,
+                (execution_time, submission.id),
+            );
+        }
+        keep_smallest(&mut self.first, submission, submission.id);
+    }
+
+    /// Builds the marts from the submissions added so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `problems` - Every problem, one row of `merged_problems` each
+    pub fn finish(self, problems: &[problems::Model]) -> Marts {
+        let mut accepted_counts: HashMap<&str, i32> = HashMap::new();
+        let mut solver_counts: HashMap<&str, i32> = HashMap::new();
+        let mut solve_days: HashMap<&str, Vec<i64>> = HashMap::new();
+        for ((user_id, problem_id), &epoch_second) in &self.first_accepted {
+            *accepted_counts.entry(user_id).or_default() += 1;
+            *solver_counts.entry(problem_id).or_default() += 1;
+            solve_days
+                .entry(user_id)
+                .or_default()
+                .push((epoch_second + JST_OFFSET_SECONDS).div_euclid(SECONDS_PER_DAY));
+        }
+
+        let mut accepted_count: Vec<_> = accepted_counts
+            .into_iter()
+            .map(|(user_id, problem_count)| accepted_count::Model {
+                user_id: user_id.to_string(),
+                problem_count,
+            })
+            .collect();
+        accepted_count.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+
+        let mut solver: Vec<_> = solver_counts
+            .iter()
+            .map(|(&problem_id, &user_count)| solver::Model {
+                problem_id: problem_id.to_string(),
+                user_count,
+            })
+            .collect();
+        solver.sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+
+        let mut max_streaks: Vec<_> = solve_days
+            .into_iter()
+            .map(|(user_id, days)| max_streaks::Model {
+                user_id: user_id.to_string(),
+                streak: longest_streak(days),
+            })
+            .collect();
+        max_streaks.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+
+        let mut language_count: Vec<_> = self
+            .accepted_languages
+            .into_iter()
+            .map(
+                |((user_id, simplified_language), problem_ids)| language_count::Model {
+                    user_id,
+                    simplified_language,
+                    problem_count: problem_ids.len() as i32,
+                },
+            )
+            .collect();
+        language_count.sort_by(|a, b| {
+            (&a.user_id, &a.simplified_language).cmp(&(&b.user_id, &b.simplified_language))
+        });
+
+        let mut point_sums: HashMap<&str, f64> = HashMap::new();
+        for ((user_id, _), point) in &self.rated_points {
+            *point_sums.entry(user_id).or_default() += point;
+        }
+        let mut rated_point_sum: Vec<_> = point_sums
+            .into_iter()
+            .map(|(user_id, point_sum)| rated_point_sum::Model {
+                user_id: user_id.to_string(),
+                point_sum: point_sum.round() as i64,
+            })
+            .collect();
+        rated_point_sum.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+
+        let mut crawled_points: HashMap<&str, f64> = HashMap::new();
+        for problem in problems {
+            if let Some(point) = problem.point {
+                crawled_points
+                    .entry(problem.id.as_str())
+                    .and_modify(|p| *p = p.max(point))
+                    .or_insert(point);
+            }
+        }
+        let mut points: Vec<_> = self
+            .max_submission_points
+            .iter()
+            .map(|(problem_id, &submission_point)| points::Model {
+                problem_id: problem_id.clone(),
+                point: Some(
+                    crawled_points
+                        .get(problem_id.as_str())
+                        .copied()
+                        .unwrap_or(submission_point),
+                ),
+                predict: None,
+            })
+            .collect();
+        points.sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+
+        let point_by_problem: HashMap<&str, Option<f64>> = points
+            .iter()
+            .map(|p| (p.problem_id.as_str(), p.point))
+            .collect();
+        let mut merged_problems: Vec<_> = problems
+            .iter()
+            .map(|problem| {
+                let id = problem.id.as_str();
+                let shortest = self.shortest.get(id).map(|(_, r)| r);
+                let fastest = self.fastest.get(id).map(|(_, r)| r);
+                let first = self.first.get(id).map(|(_, r)| r);
+                merged_problems::Model {
+                    id: problem.id.clone(),
+                    contest_id: problem.contest_id.clone(),
+                    problem_index: problem.problem_index.clone(),
+                    name: problem.name.clone(),
+                    title: problem.title.clone(),
+                    shortest_submission_id: shortest.map(|r| r.submission_id),
+                    shortest_contest_id: shortest.map(|r| r.contest_id.clone()),
+                    shortest_user_id: shortest.map(|r| r.user_id.clone()),
+                    fastest_submission_id: fastest.map(|r| r.submission_id),
+                    fastest_contest_id: fastest.map(|r| r.contest_id.clone()),
+                    fastest_user_id: fastest.map(|r| r.user_id.clone()),
+                    first_submission_id: first.map(|r| r.submission_id),
+                    first_contest_id: first.map(|r| r.contest_id.clone()),
+                    first_user_id: first.map(|r| r.user_id.clone()),
+                    source_code_length: shortest.map(|r| r.length),
+                    execution_time: fastest.and_then(|r| r.execution_time),
+                    point: point_by_problem.get(id).copied().flatten(),
+                    solver_count: solver_counts.get(id).copied(),
+                }
+            })
+            .collect();
+        merged_problems.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Marts {
+            accepted_count,
+            rated_point_sum,
+            max_streaks,
+            language_count,
+            shortest: records(self.shortest, |problem_id, r| shortest::Model {
+                contest_id: r.contest_id,
+                problem_id,
+                submission_id: r.submission_id,
+            }),
+            fastest: records(self.fastest, |problem_id, r| fastest::Model {
+                contest_id: r.contest_id,
+                problem_id,
+                submission_id: r.submission_id,
+            }),
+            first: records(self.first, |problem_id, r| first::Model {
+                contest_id: r.contest_id,
+                problem_id,
+                submission_id: r.submission_id,
+            }),
+            solver,
+            points,
+            merged_problems,
+        }
+    }
+}
+
+/// Converts the records of each problem into rows sorted by problem id.
+fn records<K, M>(
+    records: HashMap<String, (K, Record)>,
+    row: impl Fn(String, Record) -> M,
+) -> Vec<M> {
+    let mut records: Vec<_> = records.into_iter().collect();
+    records.sort_by(|(a, _), (b, _)| a.cmp(b));
+    records
+        .into_iter()
+        .map(|(problem_id, (_, record))| row(problem_id, record))
+        .collect()
+}
+
+/// Returns the length of the longest run of consecutive days.
+fn longest_streak(mut days: Vec<i64>) -> i64 {
+    days.sort_unstable();
+    days.dedup();
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous = None;
+    for day in days {
+        current = if previous == Some(day - 1) {
+            current + 1
+        } else {
+            1
+        };
+        longest = longest.max(current);
+        previous = Some(day);
+    }
+    longest
+}
+
+/// Computes the marts from the tables in `db`, streaming the submissions.
+pub async fn compute_marts<C>(db: &C) -> Result<Marts, DbErr>
+where
+    C: ConnectionTrait + StreamTrait,
+{
+    let contests = contests::Entity::find().all(db).await?;
+    let contest_problems = contest_problem::Entity::find().all(db).await?;
+    let problems = problems::Entity::find().all(db).await?;
+
+    let mut aggregator = Aggregator::new(&contests, &contest_problems);
+    let mut submissions = submissions::Entity::find()
+        .order_by_asc(submissions::Column::Id)
+        .stream(db)
+        .await?;
+    let mut count = 0;
+    while let Some(submission) = submissions.try_next().await? {
+        aggregator.add_submission(&submission);
+        count += 1;
+    }
+    tracing::info!("Aggregated {} submissions", count);
+    Ok(aggregator.finish(&problems))
+}
+
+/// Replaces the rows of every mart with `marts`.
+///
+/// Run it in a transaction, so that readers never see a mart half written.
+pub async fn replace_marts(db: &impl ConnectionTrait, marts: &Marts) -> Result<(), DbErr> {
+    replace_table::<accepted_count::ActiveModel>(db, &marts.accepted_count).await?;
+    replace_table::<rated_point_sum::ActiveModel>(db, &marts.rated_point_sum).await?;
+    replace_table::<max_streaks::ActiveModel>(db, &marts.max_streaks).await?;
+    replace_table::<language_count::ActiveModel>(db, &marts.language_count).await?;
+    replace_table::<shortest::ActiveModel>(db, &marts.shortest).await?;
+    replace_table::<fastest::ActiveModel>(db, &marts.fastest).await?;
+    replace_table::<first::ActiveModel>(db, &marts.first).await?;
+    replace_table::<solver::ActiveModel>(db, &marts.solver).await?;
+    replace_table::<points::ActiveModel>(db, &marts.points).await?;
+    replace_table::<merged_problems::ActiveModel>(db, &marts.merged_problems).await?;
+    Ok(())
+}
+
+/// Deletes every row of the table of `A` and inserts `rows` in statements of
+/// at most [`INSERT_CHUNK_SIZE`] rows.
+async fn replace_table<A>(
+    db: &impl ConnectionTrait,
+    rows: &[<A::Entity as EntityTrait>::Model],
+) -> Result<(), DbErr>
+where
+    A: ActiveModelTrait,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+{
+    A::Entity::delete_many().exec(db).await?;
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        let chunk: Vec<A> = chunk
+            .iter()
+            .cloned()
+            .map(IntoActiveModel::into_active_model)
+            .collect();
+        A::Entity::insert_many(chunk)
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Recomputes every mart in one transaction, which is rolled back in a dry
+/// run.
+///
+/// Returns the marts written.
+pub async fn recompute_marts(db: &DatabaseConnection, dry_run: bool) -> Result<Marts, DbErr> {
+    let txn = db.begin().await?;
+    let marts = compute_marts(&txn).await?;
+    replace_marts(&txn, &marts).await?;
+    if dry_run {
+        txn.rollback().await?;
+    } else {
+        txn.commit().await?;
+    }
+    Ok(marts)
+}
@@ -0,0 +1,29 @@
+use atcoder_problems_backend::{aggregator, config::Config};
+
+use crate::{Result, backfill_contest_types, setup_db};
+
+/// Recomputes the marts in one transaction and logs the size of each.
+pub async fn run(config: &Config, dry_run: bool) -> Result<()> {
+    let db = setup_db(config).await?;
+    backfill_contest_types(&db, dry_run).await?;
+    if dry_run {
+        tracing::info!("Dry run: the recomputed marts are rolled back at the end");
+    }
+    let marts = aggregator::recompute_marts(&db, dry_run).await?;
+    tracing::info!(
+        "Recomputed marts: {} accepted_count, {} rated_point_sum, {} max_streaks, \
+         {} language_count, {} shortest, {} fastest, {} first, {} solver, {} points, \
+         {} merged_problems",
+        marts.accepted_count.len(),
+        marts.rated_point_sum.len(),
+        marts.max_streaks.len(),
+        marts.language_count.len(),
+        marts.shortest.len(),
+        marts.fastest.len(),
+        marts.first.len(),
+        marts.solver.len(),
+        marts.points.len(),
+        marts.merged_problems.len()
+    );
+    Ok(())
+}
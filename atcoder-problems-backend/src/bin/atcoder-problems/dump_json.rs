@@ -1,43 +1,58 @@
 use s3::S3Client;
-use sea_orm::{Database, DatabaseConnection, EntityTrait, QueryOrder};
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder};
 use serde::Serialize;
 use std::cmp::Reverse;
-use std::collections::BTreeMap;
-use std::error::Error;
+use std::collections::{BTreeMap, HashMap};
 
-const LANGUAGE_COUNT_LIMIT: usize = 1000;
-
-type Result<T> = std::result::Result<T, Box<dyn Error>>;
+use atcoder_problems_backend::config::Config;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .json()
-        .init();
+use crate::{Result, backfill_contest_types, setup_db, setup_s3};
 
-    tracing::info!("Starting dump-json...");
+const LANGUAGE_COUNT_LIMIT: usize = 1000;
 
-    let database_url = std::env::var("DATABASE_URL")?;
-    let bucket_name = std::env::var("S3_BUCKET_NAME")?;
+/// Where the dumped files go; a dry run only logs their size.
+enum Output {
+    S3(S3Client),
+    DryRun,
+}
 
-    let db = Database::connect(&database_url).await?;
-    let s3 = S3Client::new(&bucket_name).await;
+pub async fn run(config: &Config, dry_run: bool) -> Result<()> {
+    tracing::info!("Starting dump-json...");
 
-    dump_contests(&db, &s3).await?;
-    dump_problems(&db, &s3).await?;
-    dump_contest_problem(&db, &s3).await?;
-    dump_accepted_count(&db, &s3).await?;
-    dump_rated_point_sum(&db, &s3).await?;
-    dump_language_count(&db, &s3).await?;
-    dump_max_streaks(&db, &s3).await?;
-    dump_merged_problems(&db, &s3).await?;
+    let db = setup_db(config).await?;
+    backfill_contest_types(&db, dry_run).await?;
+    let out = if dry_run {
+        Output::DryRun
+    } else {
+        Output::S3(setup_s3(config).await?)
+    };
+
+    let editorials = load_editorials(&db).await?;
+    dump_contests(&db, &out).await?;
+    dump_problems(&db, &out, &editorials).await?;
+    dump_contest_problem(&db, &out).await?;
+    dump_accepted_count(&db, &out).await?;
+    dump_rated_point_sum(&db, &out).await?;
+    dump_language_count(&db, &out).await?;
+    dump_max_streaks(&db, &out).await?;
+    dump_merged_problems(&db, &out, &editorials).await?;
 
     tracing::info!("Done.");
     Ok(())
 }
 
-async fn upload(s3: &S3Client, path: &str, data: Vec<u8>) -> Result<()> {
+async fn upload(out: &Output, path: &str, data: Vec<u8>) -> Result<()> {
+    let s3 = match out {
+        Output::S3(s3) => s3,
+        Output::DryRun => {
+            tracing::info!(
+                "Dry run: skipping upload of {} ({} bytes)",
+                path,
+                data.len()
+            );
+            return Ok(());
+        }
+    };
     tracing::info!("Checking {}...", path);
     let updated = s3.update(path, data).await?;
     if updated {
@@ -48,7 +63,7 @@ async fn upload(s3: &S3Client, path: &str, data: Vec<u8>) -> Result<()> {
     Ok(())
 }
 
-async fn dump_contests(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+async fn dump_contests(db: &DatabaseConnection, out: &Output) -> Result<()> {
     let contests: Vec<_> = sql_entities::contests::Entity::find()
         .order_by_asc(sql_entities::contests::Column::Id)
         .all(db)
@@ -62,13 +77,42 @@ async fn dump_contests(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
             duration_second: c.duration_second,
             title: c.title,
             rate_change: c.rate_change,
+            rated_lower: c.rated_lower,
+            rated_upper: c.rated_upper,
+            contest_type: c.contest_type,
         })
         .collect();
 
-    upload(s3, "resources/contests.json", serde_json::to_vec(&output)?).await
+    upload(out, "resources/contests.json", serde_json::to_vec(&output)?).await
 }
 
-async fn dump_problems(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+/// Groups the stored editorial links by problem, official ones first.
+async fn load_editorials(db: &DatabaseConnection) -> Result<HashMap<String, Vec<Editorial>>> {
+    let rows: Vec<_> = sql_entities::problem_editorials::Entity::find()
+        .order_by_desc(sql_entities::problem_editorials::Column::IsOfficial)
+        .order_by_asc(sql_entities::problem_editorials::Column::EditorialUrl)
+        .all(db)
+        .await?;
+
+    let mut editorials: HashMap<String, Vec<Editorial>> = HashMap::new();
+    for row in rows {
+        editorials
+            .entry(row.problem_id)
+            .or_default()
+            .push(Editorial {
+                url: row.editorial_url,
+                language: row.language,
+                is_official: row.is_official,
+            });
+    }
+    Ok(editorials)
+}
+
+async fn dump_problems(
+    db: &DatabaseConnection,
+    out: &Output,
+    editorials: &HashMap<String, Vec<Editorial>>,
+) -> Result<()> {
     let problems: Vec<_> = sql_entities::problems::Entity::find()
         .order_by_asc(sql_entities::problems::Column::Id)
         .all(db)
@@ -77,18 +121,22 @@ async fn dump_problems(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
     let output: Vec<_> = problems
         .into_iter()
         .map(|p| Problem {
+            editorials: editorials.get(&p.id).cloned().unwrap_or_default(),
             id: p.id,
             contest_id: p.contest_id,
             problem_index: p.problem_index,
             name: p.name,
             title: p.title,
+            time_limit_ms: p.time_limit_ms,
+            memory_limit_kb: p.memory_limit_kb,
+            point: p.point,
         })
         .collect();
 
-    upload(s3, "resources/problems.json", serde_json::to_vec(&output)?).await
+    upload(out, "resources/problems.json", serde_json::to_vec(&output)?).await
 }
 
-async fn dump_contest_problem(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+async fn dump_contest_problem(db: &DatabaseConnection, out: &Output) -> Result<()> {
     let contest_problem: Vec<_> = sql_entities::contest_problem::Entity::find()
         .order_by_asc(sql_entities::contest_problem::Column::ContestId)
         .order_by_asc(sql_entities::contest_problem::Column::ProblemId)
@@ -105,14 +153,14 @@ async fn dump_contest_problem(db: &DatabaseConnection, s3: &S3Client) -> Result<
         .collect();
 
     upload(
-        s3,
+        out,
         "resources/contest-problem.json",
         serde_json::to_vec(&output)?,
     )
     .await
 }
 
-async fn dump_accepted_count(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+async fn dump_accepted_count(db: &DatabaseConnection, out: &Output) -> Result<()> {
     let accepted_count: Vec<_> = sql_entities::accepted_count::Entity::find()
         .order_by_asc(sql_entities::accepted_count::Column::UserId)
         .all(db)
@@ -126,10 +174,10 @@ async fn dump_accepted_count(db: &DatabaseConnection, s3: &S3Client) -> Result<(
         })
         .collect();
 
-    upload(s3, "resources/ac.json", serde_json::to_vec(&output)?).await
+    upload(out, "resources/ac.json", serde_json::to_vec(&output)?).await
 }
 
-async fn dump_rated_point_sum(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+async fn dump_rated_point_sum(db: &DatabaseConnection, out: &Output) -> Result<()> {
     let sums: Vec<_> = sql_entities::rated_point_sum::Entity::find()
         .order_by_asc(sql_entities::rated_point_sum::Column::UserId)
         .all(db)
@@ -143,10 +191,10 @@ async fn dump_rated_point_sum(db: &DatabaseConnection, s3: &S3Client) -> Result<
         })
         .collect();
 
-    upload(s3, "resources/sums.json", serde_json::to_vec(&output)?).await
+    upload(out, "resources/sums.json", serde_json::to_vec(&output)?).await
 }
 
-async fn dump_language_count(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+async fn dump_language_count(db: &DatabaseConnection, out: &Output) -> Result<()> {
     let all_counts: Vec<_> = sql_entities::language_count::Entity::find().all(db).await?;
 
     // Group by language and keep top LANGUAGE_COUNT_LIMIT per language
@@ -178,10 +226,10 @@ async fn dump_language_count(db: &DatabaseConnection, s3: &S3Client) -> Result<(
             .then_with(|| a.simplified_language.cmp(&b.simplified_language))
     });
 
-    upload(s3, "resources/lang.json", serde_json::to_vec(&output)?).await
+    upload(out, "resources/lang.json", serde_json::to_vec(&output)?).await
 }
 
-async fn dump_max_streaks(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+async fn dump_max_streaks(db: &DatabaseConnection, out: &Output) -> Result<()> {
     let streaks: Vec<_> = sql_entities::max_streaks::Entity::find()
         .order_by_asc(sql_entities::max_streaks::Column::UserId)
         .all(db)
@@ -195,10 +243,14 @@ async fn dump_max_streaks(db: &DatabaseConnection, s3: &S3Client) -> Result<()>
         })
         .collect();
 
-    upload(s3, "resources/streaks.json", serde_json::to_vec(&output)?).await
+    upload(out, "resources/streaks.json", serde_json::to_vec(&output)?).await
 }
 
-async fn dump_merged_problems(db: &DatabaseConnection, s3: &S3Client) -> Result<()> {
+async fn dump_merged_problems(
+    db: &DatabaseConnection,
+    out: &Output,
+    editorials: &HashMap<String, Vec<Editorial>>,
+) -> Result<()> {
     let merged: Vec<_> = sql_entities::merged_problems::Entity::find()
         .order_by_asc(sql_entities::merged_problems::Column::Id)
         .all(db)
@@ -207,6 +259,7 @@ async fn dump_merged_problems(db: &DatabaseConnection, s3: &S3Client) -> Result<
     let output: Vec<_> = merged
         .into_iter()
         .map(|m| MergedProblem {
+            editorials: editorials.get(&m.id).cloned().unwrap_or_default(),
             id: m.id,
             contest_id: m.contest_id,
             problem_index: m.problem_index,
@@ -229,7 +282,7 @@ async fn dump_merged_problems(db: &DatabaseConnection, s3: &S3Client) -> Result<
         .collect();
 
     upload(
-        s3,
+        out,
         "resources/merged-problems.json",
         serde_json::to_vec(&output)?,
     )
@@ -243,6 +296,9 @@ struct Contest {
     duration_second: i64,
     title: String,
     rate_change: String,
+    rated_lower: Option<i32>,
+    rated_upper: Option<i32>,
+    contest_type: String,
 }
 
 #[derive(Serialize)]
@@ -252,6 +308,17 @@ struct Problem {
     problem_index: String,
     name: String,
     title: String,
+    time_limit_ms: Option<i32>,
+    memory_limit_kb: Option<i32>,
+    point: Option<f64>,
+    editorials: Vec<Editorial>,
+}
+
+#[derive(Serialize, Clone)]
+struct Editorial {
+    url: String,
+    language: Option<String>,
+    is_official: bool,
 }
 
 #[derive(Serialize)]
@@ -306,4 +373,5 @@ struct MergedProblem {
     execution_time: Option<i32>,
     point: Option<f64>,
     solver_count: Option<i32>,
+    editorials: Vec<Editorial>,
 }
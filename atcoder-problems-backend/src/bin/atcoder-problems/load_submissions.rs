@@ -0,0 +1,80 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use atcoder_problems_backend::{
+    bulk_load::{self, DumpFormat, DumpReader},
+    config::Config,
+};
+
+use crate::{Result, setup_db};
+
+/// Loads each dump in its own transaction.
+///
+/// # Arguments
+///
+/// * `format` - Format of every dump, or `None` to guess it from the
+///   extension of each
+/// * `rejects_path` - JSON Lines file that receives the rejected rows
+pub async fn run(
+    config: &Config,
+    files: &[PathBuf],
+    format: Option<DumpFormat>,
+    max_rejects: usize,
+    rejects_path: Option<&Path>,
+    dry_run: bool,
+) -> Result<()> {
+    let db = setup_db(config).await?;
+    let mut rejects_out = rejects_path
+        .map(File::create)
+        .transpose()?
+        .map(BufWriter::new);
+    if dry_run {
+        tracing::info!("Dry run: every load is rolled back at the end");
+    }
+
+    for path in files {
+        let format = format
+            .or_else(|| DumpFormat::from_path(path))
+            .ok_or_else(|| {
+                format!(
+                    "Cannot tell the format of {} from its extension, pass --format",
+                    path.display()
+                )
+            })?;
+        tracing::info!("Loading {} as {:?}", path.display(), format);
+        let dump = DumpReader::new(BufReader::new(File::open(path)?), format)?;
+        let report = bulk_load::load_submissions(&db, dump, max_rejects, dry_run).await?;
+
+        for reject in &report.rejects {
+            tracing::warn!(
+                "Rejected line {} of {}: {}",
+                reject.line,
+                path.display(),
+                reject.reason
+            );
+            if let Some(out) = rejects_out.as_mut() {
+                let entry = serde_json::json!({
+                    "file": path.display().to_string(),
+                    "line": reject.line,
+                    "reason": reject.reason,
+                });
+                writeln!(out, "{}", entry)?;
+            }
+        }
+        tracing::info!(
+            "Loaded {}: {} rows staged, {} inserted, {} updated, {} rejected",
+            path.display(),
+            report.rows_staged,
+            report.inserted,
+            report.updated,
+            report.rejects.len()
+        );
+    }
+    if let Some(mut out) = rejects_out {
+        out.flush()?;
+    }
+    Ok(())
+}
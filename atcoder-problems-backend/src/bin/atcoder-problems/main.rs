@@ -0,0 +1,417 @@
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+
+use atcoder_problems_backend::{
+    bulk_load::DumpFormat,
+    config::Config,
+    crawl_runs::{self, RunStats},
+    crawler_utils::{self, CrawlDb, Mode},
+    server::{AppState, GithubClient, make_router},
+};
+use clap::{Parser, Subcommand};
+use crawler::{CrawlerClient, FetchStats, RetryPolicy, RetryingFetcher};
+use s3::S3Client;
+use sea_orm::{Database, DatabaseConnection, EntityTrait, QuerySelect};
+
+mod aggregate;
+mod dump_json;
+mod load_submissions;
+mod standings;
+mod submissions;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Number of submissions enriched with judge cases by one `details` run
+const DEFAULT_DETAIL_LIMIT: u64 = 1000;
+
+/// Number of profiles refreshed by one `users` run
+const DEFAULT_USER_REFRESH_LIMIT: usize = 1000;
+
+/// Number of contests crawled at the same time by one process
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Number of rejected rows after which a dump is assumed to be malformed
+const DEFAULT_MAX_REJECTS: usize = 1000;
+
+/// API server, crawlers and dataset dump of AtCoder Problems.
+#[derive(Parser, Debug)]
+#[command(name = "atcoder-problems", version)]
+struct Cli {
+    /// TOML config file; environment variables override its settings
+    #[arg(long, global = true, env = "ATCODER_PROBLEMS_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Fetch and parse as usual, but skip the uploads to S3 and the database
+    /// writes of crawls, which are logged instead; loads and aggregations are
+    /// rolled back
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the API server
+    Serve {
+        /// Port to listen on, overriding `server.port`
+        #[arg(long)]
+        port: Option<u16>,
+    },
+    /// Crawl AtCoder into the database
+    Crawl {
+        /// Save every fetched page into this directory
+        #[arg(long, global = true, value_name = "DIR")]
+        record: Option<PathBuf>,
+
+        #[command(subcommand)]
+        target: CrawlTarget,
+    },
+    /// Dump the datasets as JSON to S3
+    DumpJson,
+    /// Load submission dumps into the database through Postgres `COPY`
+    LoadSubmissions {
+        /// CSV or JSON Lines files with the fields of a crawled submission
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Format of the dumps, guessed from their extensions by default
+        #[arg(long, value_enum)]
+        format: Option<DumpFormat>,
+
+        /// Abort a dump after this many rejected rows
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_REJECTS)]
+        max_rejects: usize,
+
+        /// Write the rejected rows to this file as JSON Lines
+        #[arg(long, value_name = "FILE")]
+        rejects: Option<PathBuf>,
+    },
+    /// Recompute the marts built by dbt (rankings, shortest/fastest/first
+    /// submissions and merged problems) from the submissions
+    Aggregate,
+}
+
+#[derive(Subcommand, Debug)]
+enum CrawlTarget {
+    /// Contests listed in the archive
+    Contests {
+        /// Re-crawl what is already stored and log what was added, renamed or
+        /// removed
+        #[arg(long)]
+        reconcile: bool,
+
+        /// Delete the contests missing from the archives, unless too many are
+        /// missing
+        #[arg(long, requires = "reconcile")]
+        prune: bool,
+    },
+    /// Problems of every stored contest
+    Problems {
+        /// Re-crawl what is already stored, log what changed and flag the
+        /// problems left without a contest in `orphaned_epoch_second`
+        #[arg(long)]
+        reconcile: bool,
+
+        /// Reconcile only this many random contests
+        #[arg(long, value_name = "N", requires = "reconcile")]
+        sample: Option<usize>,
+    },
+    /// Final standings of the contests that are not uploaded to S3 yet
+    Standings,
+    /// Submissions of the contests chosen by the mode
+    Submissions {
+        #[arg(value_enum)]
+        mode: Mode,
+
+        /// Number of queued contests crawled at the same time
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+
+        /// Number of submissions enriched with judge cases by `details`
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_DETAIL_LIMIT)]
+        limit: u64,
+    },
+    /// Editorial links of the contests that ended recently or were never
+    /// crawled
+    Editorials {
+        /// Re-crawl every contest
+        #[arg(long)]
+        all: bool,
+    },
+    /// Profiles and rating histories of the given users, or of the users who
+    /// submitted recently or linked an AtCoder id
+    Users {
+        user_ids: Vec<String>,
+
+        /// Refresh at most this many of the active and linked users
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_USER_REFRESH_LIMIT)]
+        limit: usize,
+    },
+}
+
+impl CrawlTarget {
+    /// Name of the target in `crawl_runs.command`
+    fn command(&self) -> &'static str {
+        match self {
+            CrawlTarget::Contests { .. } => "contests",
+            CrawlTarget::Problems { .. } => "problems",
+            CrawlTarget::Standings => "standings",
+            CrawlTarget::Submissions { .. } => "submissions",
+            CrawlTarget::Editorials { .. } => "editorials",
+            CrawlTarget::Users { .. } => "users",
+        }
+    }
+
+    /// Variant of the target in `crawl_runs.mode`
+    fn mode(&self) -> Option<&'static str> {
+        match self {
+            CrawlTarget::Contests {
+                reconcile: true, ..
+            }
+            | CrawlTarget::Problems {
+                reconcile: true, ..
+            } => Some("reconcile"),
+            CrawlTarget::Submissions { mode, .. } => Some(mode.as_str()),
+            CrawlTarget::Editorials { all: true } => Some("all"),
+            _ => None,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .json()
+        .init();
+
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())?;
+
+    match cli.command {
+        Command::Serve { port } => {
+            if cli.dry_run {
+                return Err("--dry-run is not supported by serve".into());
+            }
+            serve(&config, port.unwrap_or(config.server.port)).await
+        }
+        Command::Crawl { record, target } => crawl(&config, record, target, cli.dry_run).await,
+        Command::DumpJson => dump_json::run(&config, cli.dry_run).await,
+        Command::LoadSubmissions {
+            files,
+            format,
+            max_rejects,
+            rejects,
+        } => {
+            load_submissions::run(
+                &config,
+                &files,
+                format,
+                max_rejects,
+                rejects.as_deref(),
+                cli.dry_run,
+            )
+            .await
+        }
+        Command::Aggregate => aggregate::run(&config, cli.dry_run).await,
+    }
+}
+
+async fn setup_db(config: &Config) -> Result<DatabaseConnection> {
+    let db = Database::connect(config.database_url()?).await?;
+    Ok(db)
+}
+
+/// Fills the rated range and type of the contests stored before they were
+/// parsed, which the marts and the dumped contests would count as unrated.
+async fn backfill_contest_types(db: &DatabaseConnection, dry_run: bool) -> Result<()> {
+    if dry_run {
+        crawler_utils::dry_run(crawler_utils::backfill_contest_types(db)).await?;
+    } else {
+        crawler_utils::backfill_contest_types(db).await?;
+    }
+    Ok(())
+}
+
+async fn setup_crawler(
+    config: &Config,
+    record_dir: Option<PathBuf>,
+) -> Result<RetryingFetcher<CrawlerClient>> {
+    let mut crawler =
+        CrawlerClient::with_rate_limit(config.session()?.to_string(), config.rate_limit()?)?;
+    if let Some(dir) = record_dir {
+        crawler = crawler.record_to(dir);
+    }
+    crawler.verify_session().await?;
+    Ok(RetryingFetcher::new(crawler, RetryPolicy::default()))
+}
+
+async fn setup_s3(config: &Config) -> Result<S3Client> {
+    Ok(S3Client::new(config.bucket()?).await)
+}
+
+async fn serve(config: &Config, port: u16) -> Result<()> {
+    let db = setup_db(config).await?;
+    let github = GithubClient::new(
+        config.client_id()?,
+        config.client_secret()?,
+        "https://github.com",
+        "https://api.github.com",
+    )?;
+    let state = AppState::new(db, Arc::new(github));
+    let app = make_router(state);
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    tracing::info!("listening on {}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn crawl(
+    config: &Config,
+    record_dir: Option<PathBuf>,
+    target: CrawlTarget,
+    dry_run: bool,
+) -> Result<()> {
+    let db = setup_db(config).await?;
+    if !dry_run {
+        return crawl_recorded(config, &db, record_dir, target).await;
+    }
+
+    let crawler = setup_crawler(config, record_dir).await?;
+    tracing::info!("Dry run: database writes are skipped and logged");
+    crawler_utils::dry_run(run_crawl(config, &crawler, &db, target, true)).await
+}
+
+/// Runs the crawl and records it with its metrics in `crawl_runs`.
+async fn crawl_recorded(
+    config: &Config,
+    db: &DatabaseConnection,
+    record_dir: Option<PathBuf>,
+    target: CrawlTarget,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    let abandoned = crawl_runs::abandon_stale_runs(db, now).await?;
+    if abandoned > 0 {
+        tracing::warn!("Marked {} stale crawl runs as abandoned", abandoned);
+    }
+    let run_id = crawl_runs::start_run(db, target.command(), target.mode(), now).await?;
+    tracing::info!("Started crawl run {}", run_id);
+
+    let crawler = match setup_crawler(config, record_dir).await {
+        Ok(crawler) => crawler,
+        Err(e) => {
+            let result = Err(e);
+            finish_run(
+                db,
+                run_id,
+                &RunStats::default(),
+                &FetchStats::default(),
+                &result,
+            )
+            .await;
+            return result;
+        }
+    };
+    let (result, stats) =
+        crawl_runs::track_run(run_crawl(config, &crawler, db, target, false)).await;
+    finish_run(db, run_id, &stats, crawler.stats(), &result).await;
+    result
+}
+
+/// Records the outcome of a crawl run, logging rather than returning a
+/// failure so that it does not hide the outcome itself.
+async fn finish_run(
+    db: &DatabaseConnection,
+    run_id: i64,
+    stats: &RunStats,
+    fetch_stats: &FetchStats,
+    result: &Result<()>,
+) {
+    let error = result.as_ref().err().map(|e| e.to_string());
+    if let Err(e) = crawl_runs::finish_run(
+        db,
+        run_id,
+        stats,
+        fetch_stats,
+        error.as_deref(),
+        chrono::Utc::now().timestamp(),
+    )
+    .await
+    {
+        tracing::error!("Failed to record the end of crawl run {}: {}", run_id, e);
+    }
+}
+
+async fn run_crawl(
+    config: &Config,
+    crawler: &RetryingFetcher<CrawlerClient>,
+    db: &impl CrawlDb,
+    target: CrawlTarget,
+    dry_run: bool,
+) -> Result<()> {
+    match target {
+        CrawlTarget::Contests { reconcile, prune } => {
+            crawler_utils::backfill_contest_types(db).await?;
+            if reconcile {
+                crawler_utils::reconcile_contests(crawler, db, prune).await?;
+            } else {
+                crawler_utils::crawl_contests(crawler, db).await?;
+            }
+        }
+        CrawlTarget::Problems {
+            reconcile: true,
+            sample,
+        } => {
+            crawler_utils::reconcile_problems(crawler, db, sample).await?;
+        }
+        CrawlTarget::Problems {
+            reconcile: false, ..
+        } => {
+            crawler_utils::crawl_problems(crawler, db).await?;
+        }
+        CrawlTarget::Standings => {
+            let s3 = setup_s3(config).await?;
+            standings::run(crawler, db, &s3, dry_run).await?;
+        }
+        CrawlTarget::Submissions {
+            mode,
+            concurrency,
+            limit,
+        } => {
+            submissions::run(crawler, db, mode, concurrency, limit).await?;
+        }
+        CrawlTarget::Editorials { all } => {
+            let contest_ids: Vec<String> = if all {
+                sql_entities::contests::Entity::find()
+                    .select_only()
+                    .column(sql_entities::contests::Column::Id)
+                    .into_tuple()
+                    .all(db)
+                    .await?
+            } else {
+                crawler_utils::contest_ids_for_editorial_crawl(db, chrono::Utc::now().timestamp())
+                    .await?
+            };
+            tracing::info!("Crawling editorials of {} contests", contest_ids.len());
+            crawler_utils::crawl_editorials(crawler, db, &contest_ids).await?;
+        }
+        CrawlTarget::Users {
+            mut user_ids,
+            limit,
+        } => {
+            // User IDs given on the command line are refreshed instead of the
+            // active and linked users.
+            if user_ids.is_empty() {
+                user_ids =
+                    crawler_utils::users_to_refresh(db, chrono::Utc::now().timestamp(), limit)
+                        .await?;
+            }
+            tracing::info!("Refreshing {} user profiles", user_ids.len());
+            crawler_utils::refresh_user_profiles(crawler, db, &user_ids).await?;
+        }
+    }
+    Ok(())
+}
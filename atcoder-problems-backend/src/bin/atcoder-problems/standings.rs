@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use atcoder_problems_backend::crawler_utils::{self, CrawlDb};
+use crawler::{CrawlerClient, CrawlerError, RetryingFetcher};
+use s3::S3Client;
+use sea_orm::EntityTrait;
+
+use crate::Result;
+
+pub async fn run(
+    crawler: &RetryingFetcher<CrawlerClient>,
+    db: &impl CrawlDb,
+    s3: &S3Client,
+    dry_run: bool,
+) -> Result<()> {
+    let contests = sql_entities::contests::Entity::find().all(db).await?;
+
+    for contest in contests {
+        tracing::info!("Fetching standings for contest: {}", contest.id);
+        let key = standings_key(&contest)?;
+        let exisiting = s3.get_object(&key).await?;
+        if exisiting.is_some() {
+            tracing::info!("Standings for contest {} already exists", contest.id);
+            continue;
+        }
+        let standings = match crawler_utils::fetch_fixed_standings(crawler, &contest.id).await {
+            Ok(Some(standings)) => standings,
+            Ok(None) => continue,
+            Err(e @ CrawlerError::SessionExpired { .. }) => return Err(e.into()),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to fetch standings for {}, skipping: {}",
+                    contest.id,
+                    e
+                );
+                continue;
+            }
+        };
+
+        let json = serde_json::to_vec(&standings)?;
+        if dry_run {
+            tracing::info!("Dry run: skipping upload of {} ({} bytes)", key, json.len());
+            continue;
+        }
+        s3.put_object(&key, json).await?;
+    }
+    Ok(())
+}
+
+fn standings_key(contest: &sql_entities::contests::Model) -> Result<String> {
+    let path = PathBuf::from("standings")
+        .join(&contest.id)
+        .with_extension("json");
+    path.to_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("Failed to convert path to str: {}", path.display()).into())
+}
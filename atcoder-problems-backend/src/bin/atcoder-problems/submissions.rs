@@ -1,112 +1,123 @@
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
-};
+use std::collections::{HashMap, HashSet};
 
-use atcoder_problems_backend::crawler_utils;
-use crawler::CrawlerClient;
+use atcoder_problems_backend::crawler_utils::{self, CrawlDb, CrawlError, LivePolling, Mode};
+use crawler::{CrawlerClient, CrawlerError, RetryingFetcher};
 use rand::seq::SliceRandom;
 use sea_orm::{
-    ColumnTrait, Database, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
-    sea_query::Expr, sea_query::ExprTrait,
+    ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, sea_query::Expr,
+    sea_query::ExprTrait,
 };
 
-type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .json()
-        .init();
-    let args: Vec<String> = std::env::args().collect();
-    let mode_arg = args.get(1).ok_or("mode argument is required")?;
-    let mode = Mode::from_str(mode_arg)?;
-
-    let db = setup_db().await?;
-    let crawler = setup_crawler()?;
-
-    let contest_ids = extract_contest_ids(&db, mode).await?;
+use crate::Result;
+
+pub async fn run(
+    crawler: &RetryingFetcher<CrawlerClient>,
+    db: &impl CrawlDb,
+    mode: Mode,
+    concurrency: usize,
+    detail_limit: u64,
+) -> Result<()> {
+    if mode == Mode::Live {
+        crawler_utils::crawl_live_contests(crawler, db, LivePolling::default()).await?;
+        tracing::info!("Finished following running contests");
+        return Ok(());
+    }
 
-    tracing::info!("Extracted {} contest ids", contest_ids.len());
-    for contest_id in contest_ids {
-        tracing::info!("Fetching submissions for contest {}", contest_id);
-
-        let mut trial_count = 0;
-        for page in 1.. {
-            tracing::info!(
-                "Fetching submissions for contest {} page {}",
-                contest_id,
-                page
-            );
-            let submissions = crawler_utils::fetch_submissions(&crawler, &contest_id, page).await;
-            if submissions.is_empty() {
-                tracing::info!("No more submissions for contest {}", contest_id);
-                break;
-            }
+    if mode == Mode::Users && crawler_utils::is_dry_run() {
+        crawl_user_jobs_unclaimed(crawler, db).await?;
+        return Ok(());
+    }
 
-            tracing::info!("Inserting {} submissions", submissions.len());
-            let inserted = crawler_utils::upsert_submissions(&db, submissions).await?;
-            tracing::info!("Inserted {} submissions", inserted);
+    if mode == Mode::Users {
+        let worker_id = format!("{}-{}", std::process::id(), uuid::Uuid::new_v4());
+        crawler_utils::run_user_crawl_jobs(crawler, db, &worker_id).await?;
+        tracing::info!("Finished fetching submissions of queued users");
+        return Ok(());
+    }
 
-            if inserted > 0 {
-                trial_count = 0;
-            } else {
-                trial_count += 1;
-            }
+    if mode == Mode::Details {
+        crawler_utils::crawl_submission_details(crawler, db, detail_limit).await?;
+        tracing::info!("Finished fetching submission details");
+        return Ok(());
+    }
 
-            if trial_count >= 5 && (mode == Mode::New || mode == Mode::VirtualContests) {
-                tracing::info!("No new submissions for contest {}", contest_id);
-                break;
-            }
+    let contest_ids = extract_contest_ids(db, mode).await?;
 
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-        }
+    tracing::info!("Extracted {} contest ids", contest_ids.len());
+    if crawler_utils::is_dry_run() {
+        crawl_contests_unqueued(crawler, db, &contest_ids, mode).await?;
+        return Ok(());
+    }
+    let enqueued = crawler_utils::enqueue_contests(db, &contest_ids, mode).await?;
+    if !enqueued.unknown.is_empty() {
+        tracing::warn!(
+            "Skipped {} contest ids missing from contests: {}",
+            enqueued.unknown.len(),
+            enqueued.unknown.join(", ")
+        );
+    }
 
-        tracing::info!("Finished fetching submissions for contest {}", contest_id);
+    let worker_id = format!("{}-{}", std::process::id(), uuid::Uuid::new_v4());
+    let summary = crawler_utils::run_crawl_queue(crawler, db, &worker_id, concurrency).await?;
+    if !summary.failed.is_empty() {
+        return Err(format!(
+            "Failed to crawl submissions of {} contests: {}",
+            summary.failed.len(),
+            summary.failed.join(", ")
+        )
+        .into());
     }
 
     tracing::info!("Finished fetching submissions");
     Ok(())
 }
 
-async fn setup_db() -> Result<DatabaseConnection> {
-    let database_url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?;
-    let db = Database::connect(&database_url).await?;
-    Ok(db)
-}
-
-fn setup_crawler() -> Result<CrawlerClient> {
-    let revel_session = std::env::var("REVEL_SESSION").map_err(|_| "REVEL_SESSION must be set")?;
-    let crawler = CrawlerClient::new(revel_session)?;
-    Ok(crawler)
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Mode {
-    All,
-    Recent,
-    New,
-    VirtualContests,
+/// Crawls the contests one by one for a dry run, which cannot claim them from
+/// the crawl queue.
+async fn crawl_contests_unqueued(
+    crawler: &RetryingFetcher<CrawlerClient>,
+    db: &impl CrawlDb,
+    contest_ids: &[String],
+    mode: Mode,
+) -> Result<()> {
+    for contest_id in contest_ids {
+        match crawler_utils::crawl_submissions(crawler, db, contest_id, mode).await {
+            Ok(_) => {}
+            Err(CrawlError::Crawler(e)) if !matches!(e, CrawlerError::SessionExpired { .. }) => {
+                tracing::error!("Failed to crawl submissions of {}: {}", contest_id, e);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
 }
 
-impl FromStr for Mode {
-    type Err = String;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s {
-            "all" => Ok(Mode::All),
-            "recent" => Ok(Mode::Recent),
-            "new" => Ok(Mode::New),
-            "virtual-contests" => Ok(Mode::VirtualContests),
-            _ => Err("Invalid mode".to_string()),
-        }
+/// Crawls the submissions of the queued users for a dry run, which cannot
+/// claim their jobs.
+async fn crawl_user_jobs_unclaimed(
+    crawler: &RetryingFetcher<CrawlerClient>,
+    db: &impl CrawlDb,
+) -> Result<()> {
+    let jobs = sql_entities::user_crawl_jobs::Entity::find()
+        .all(db)
+        .await?;
+    for job in jobs {
+        crawler_utils::crawl_user_submissions(
+            crawler,
+            db,
+            &job.atcoder_user_id,
+            job.last_contest_id.as_deref(),
+            |_| async { Ok(()) },
+        )
+        .await?;
     }
+    Ok(())
 }
 
-async fn extract_contest_ids(db: &DatabaseConnection, mode: Mode) -> Result<HashSet<String>> {
+async fn extract_contest_ids(db: &impl CrawlDb, mode: Mode) -> Result<Vec<String>> {
     let contest_ids = match mode {
-        Mode::All | Mode::New => {
+        Mode::All => crawler_utils::contest_ids_for_full_crawl(db).await?,
+        Mode::New => {
             let mut contests = sql_entities::contests::Entity::find().all(db).await?;
             contests.shuffle(&mut rand::rng());
             contests.into_iter().map(|contest| contest.id).collect()
@@ -204,6 +215,9 @@ async fn extract_contest_ids(db: &DatabaseConnection, mode: Mode) -> Result<Hash
                 .map(|(contest_id, _)| contest_id)
                 .collect()
         }
+        Mode::Pending => crawler_utils::contest_ids_with_pending_submissions(db).await?,
+        // These modes do not crawl a list of contests.
+        Mode::Details | Mode::Live | Mode::Users => Vec::new(),
     };
     Ok(contest_ids)
 }
@@ -0,0 +1,574 @@
+use std::{
+    io::{BufRead, Read},
+    path::Path,
+};
+
+use crawler::Submission;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, sqlx};
+use serde::Serialize;
+
+use crate::crawler_utils::is_judge_progress;
+
+/// Bytes of CSV buffered before they are sent to `COPY`.
+const COPY_BUFFER_SIZE: usize = 1 << 20;
+
+/// Length limit of the `VARCHAR(255)` columns of `submissions`.
+const MAX_TEXT_LENGTH: usize = 255;
+
+/// Columns of a CSV dump: the field of [`Submission`] followed by the name of
+/// the column of `submissions` it is stored in, when that differs.
+const CSV_COLUMNS: [(&str, Option<&str>); 11] = [
+    ("id", None),
+    ("epoch_second", None),
+    ("problem_id", None),
+    ("contest_id", None),
+    ("user", Some("user_id")),
+    ("language", None),
+    ("score", Some("point")),
+    ("code_length", Some("length")),
+    ("result", None),
+    ("execution_time", None),
+    ("memory", None),
+];
+
+/// Columns a CSV dump may leave out; the values are then `NULL`.
+const OPTIONAL_CSV_COLUMNS: [&str; 2] = ["execution_time", "memory"];
+
+/// Staging table of one load. `line` keeps the order of the dump, so that the
+/// last row of a submission listed twice wins like in `upsert_submissions`.
+const CREATE_STAGING_TABLE: &str = "
+CREATE TEMPORARY TABLE submissions_staging (
+  line            BIGINT NOT NULL,
+  id              BIGINT NOT NULL,
+  epoch_second    BIGINT NOT NULL,
+  problem_id      VARCHAR(255) NOT NULL,
+  contest_id      VARCHAR(255) NOT NULL,
+  user_id         VARCHAR(255) NOT NULL,
+  language        VARCHAR(255) NOT NULL,
+  point           DOUBLE PRECISION NOT NULL,
+  length          INT NOT NULL,
+  result          VARCHAR(255) NOT NULL,
+  execution_time  INT,
+  memory          INT
+) ON COMMIT DROP";
+
+const COPY_STAGING_TABLE: &str = "
+COPY submissions_staging (
+  line, id, epoch_second, problem_id, contest_id, user_id, language, point, length, result,
+  execution_time, memory
+) FROM STDIN (FORMAT csv)";
+
+/// Result changes between the stored submissions and the staged ones, as
+/// `(id, old_result, new_result)`.
+const SELECT_RESULT_CHANGES: &str = "
+SELECT s.id, old.result, s.result
+FROM (
+  SELECT DISTINCT ON (id) * FROM submissions_staging ORDER BY id, line DESC
+) s
+JOIN submissions old ON old.id = s.id
+WHERE old.result IS DISTINCT FROM s.result OR old.point IS DISTINCT FROM s.point";
+
+/// Records the result changes of the submissions whose ids are bound to `$2`
+/// like `upsert_submissions`.
+const INSERT_RESULT_CHANGES: &str = "
+INSERT INTO submission_history (
+  submission_id, changed_epoch_second, old_result, new_result, old_point, new_point
+)
+SELECT s.id, $1, old.result, s.result, old.point, s.point
+FROM (
+  SELECT DISTINCT ON (id) * FROM submissions_staging ORDER BY id, line DESC
+) s
+JOIN submissions old ON old.id = s.id
+WHERE s.id = ANY($2)
+ON CONFLICT (submission_id, changed_epoch_second) DO NOTHING";
+
+/// Upserts the staged submissions like `upsert_submissions`, rewriting only
+/// the stored rows that changed, and returns the numbers of inserted and
+/// updated rows.
+const MERGE_STAGING_TABLE: &str = "
+WITH written AS (
+  INSERT INTO submissions (
+    id, epoch_second, problem_id, contest_id, user_id, language, point, length, result,
+    execution_time, memory
+  )
+  SELECT DISTINCT ON (id)
+    id, epoch_second, problem_id, contest_id, user_id, language, point, length, result,
+    execution_time, memory
+  FROM submissions_staging
+  ORDER BY id, line DESC
+  ON CONFLICT (id) DO UPDATE SET
+    epoch_second = EXCLUDED.epoch_second,
+    problem_id = EXCLUDED.problem_id,
+    contest_id = EXCLUDED.contest_id,
+    user_id = EXCLUDED.user_id,
+    language = EXCLUDED.language,
+    point = EXCLUDED.point,
+    length = EXCLUDED.length,
+    result = EXCLUDED.result,
+    execution_time = EXCLUDED.execution_time,
+    memory = EXCLUDED.memory
+  WHERE submissions.epoch_second IS DISTINCT FROM EXCLUDED.epoch_second
+    OR submissions.problem_id IS DISTINCT FROM EXCLUDED.problem_id
+    OR submissions.contest_id IS DISTINCT FROM EXCLUDED.contest_id
+    OR submissions.user_id IS DISTINCT FROM EXCLUDED.user_id
+    OR submissions.language IS DISTINCT FROM EXCLUDED.language
+    OR submissions.point IS DISTINCT FROM EXCLUDED.point
+    OR submissions.length IS DISTINCT FROM EXCLUDED.length
+    OR submissions.result IS DISTINCT FROM EXCLUDED.result
+    OR submissions.execution_time IS DISTINCT FROM EXCLUDED.execution_time
+    OR submissions.memory IS DISTINCT FROM EXCLUDED.memory
+  RETURNING xmax = 0 AS inserted
+)
+SELECT
+  COUNT(*) FILTER (WHERE inserted),
+  COUNT(*) FILTER (WHERE NOT inserted)
+FROM written";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DumpFormat {
+    /// Comma-separated values with a header row naming the columns
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl DumpFormat {
+    /// Guesses the format from the extension of the dump.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(DumpFormat::Csv),
+            "jsonl" | "ndjson" => Some(DumpFormat::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// A row of a dump that was not loaded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reject {
+    /// Line of the dump where the row starts, counted from 1
+    pub line: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LoadReport {
+    /// Number of rows that passed validation and were staged
+    pub rows_staged: u64,
+    /// Number of submissions that were not stored yet
+    pub inserted: u64,
+    /// Number of stored submissions that changed
+    pub updated: u64,
+    pub rejects: Vec<Reject>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum LoadError {
+    #[error("failed to read the dump: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid CSV header: {0}")]
+    InvalidHeader(String),
+
+    #[error("more than {limit} rows were rejected, the last at line {}: {}", last.line, last.reason)]
+    TooManyRejects { limit: usize, last: Reject },
+
+    #[error("bulk loading needs Postgres, but the database is {0:?}")]
+    UnsupportedBackend(DbBackend),
+
+    #[error(transparent)]
+    Db(#[from] sqlx::Error),
+}
+
+/// A row of a dump with the line it starts at, or why it was not loaded.
+pub type DumpRow = (u64, Result<Submission, Reject>);
+
+/// A parsed row with the line it starts at, or why it could not be parsed.
+type ParsedRow = (u64, Result<Submission, String>);
+
+/// Reads the submissions of a dump one row at a time.
+pub struct DumpReader<R> {
+    source: DumpSource<R>,
+}
+
+enum DumpSource<R> {
+    Csv {
+        reader: csv::Reader<LineCounter<R>>,
+        /// Position of each of [`CSV_COLUMNS`] in a record
+        columns: Vec<Option<usize>>,
+    },
+    Jsonl {
+        reader: R,
+        /// Number of lines read so far
+        line: u64,
+    },
+}
+
+impl<R: BufRead> DumpReader<R> {
+    /// Starts reading a dump, checking the header of a CSV dump.
+    pub fn new(reader: R, format: DumpFormat) -> Result<Self, LoadError> {
+        let source = match format {
+            DumpFormat::Csv => {
+                // Rows with missing fields are rejected one by one, not as an
+                // error of the whole dump.
+                let mut reader = csv::ReaderBuilder::new()
+                    .flexible(true)
+                    .from_reader(LineCounter::new(reader));
+                let header = match reader.headers() {
+                    Ok(header) => header,
+                    Err(e) => return Err(LoadError::InvalidHeader(csv_error(e)?)),
+                };
+                if header.is_empty() {
+                    return Err(LoadError::InvalidHeader("the dump is empty".to_string()));
+                }
+                let columns = csv_columns(header)?;
+                DumpSource::Csv { reader, columns }
+            }
+            DumpFormat::Jsonl => DumpSource::Jsonl { reader, line: 0 },
+        };
+        Ok(Self { source })
+    }
+
+    /// Returns the next row with the line it starts at, or `None` at the end
+    /// of the dump. A row that cannot be parsed or fails
+    /// [`validate_submission`] is returned as a [`Reject`].
+    pub fn next_row(&mut self) -> Result<Option<DumpRow>, LoadError> {
+        let row = match &mut self.source {
+            DumpSource::Csv { reader, columns } => read_csv_row(reader, columns)?,
+            DumpSource::Jsonl { reader, line } => {
+                read_json_line(reader, line)?.map(|(line, text)| {
+                    let submission =
+                        serde_json::from_str::<Submission>(&text).map_err(|e| e.to_string());
+                    (line, submission)
+                })
+            }
+        };
+        Ok(row.map(|(line, submission)| {
+            let submission = submission
+                .and_then(|submission| validate_submission(&submission).map(|()| submission))
+                .map_err(|reason| Reject { line, reason });
+            (line, submission)
+        }))
+    }
+}
+
+/// Reads the next CSV record that is not blank and parses it.
+fn read_csv_row<R: BufRead>(
+    reader: &mut csv::Reader<LineCounter<R>>,
+    columns: &[Option<usize>],
+) -> Result<Option<ParsedRow>, LoadError> {
+    let mut record = csv::StringRecord::new();
+    loop {
+        let read = reader.read_record(&mut record);
+        let LineCounter {
+            line_breaks, ended, ..
+        } = *reader.get_ref();
+        match read {
+            Ok(false) => return Ok(None),
+            Ok(true) if record.len() == 1 && record[0].trim().is_empty() => continue,
+            Ok(true) => {
+                // The line break ending the record was read with it, unless
+                // the record ends the dump.
+                let inner = record.iter().flat_map(str::bytes).filter(|&b| b == b'\n');
+                let line = line_breaks - inner.count() as u64 + u64::from(ended);
+                return Ok(Some((line, parse_csv_record(columns, &record))));
+            }
+            Err(e) => return Ok(Some((line_breaks + 1, Err(csv_error(e)?)))),
+        }
+    }
+}
+
+/// Hands a dump to [`csv::Reader`] one line at a time to count its lines,
+/// since the positions the reader reports skip neither the blank lines nor
+/// the `\n` of a `\r\n` before a record.
+///
+/// The reader only asks for more once it used up what it was given, so the
+/// line breaks given to it end with the line where the record it just read
+/// ends.
+struct LineCounter<R> {
+    reader: R,
+    /// Number of line breaks given to the CSV reader
+    line_breaks: u64,
+    /// Whether the CSV reader was told that the dump ended
+    ended: bool,
+}
+
+impl<R> LineCounter<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line_breaks: 0,
+            ended: false,
+        }
+    }
+}
+
+impl<R: BufRead> Read for LineCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.reader.fill_buf()?;
+        let line_end = available
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(available.len(), |i| i + 1);
+        let n = line_end.min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        if buf[..n].ends_with(b"\n") {
+            self.line_breaks += 1;
+        }
+        self.ended |= n == 0 && !buf.is_empty();
+        self.reader.consume(n);
+        Ok(n)
+    }
+}
+
+/// Reads the next non-blank line, counting the lines read in `line`.
+fn read_json_line(
+    reader: &mut impl BufRead,
+    line: &mut u64,
+) -> Result<Option<(u64, String)>, LoadError> {
+    loop {
+        let mut text = String::new();
+        if reader.read_line(&mut text)? == 0 {
+            return Ok(None);
+        }
+        *line += 1;
+        if !text.trim().is_empty() {
+            return Ok(Some((*line, text)));
+        }
+    }
+}
+
+/// Returns why a CSV record could not be read, or the error of the reader
+/// itself.
+fn csv_error(e: csv::Error) -> Result<String, LoadError> {
+    let reason = e.to_string();
+    match e.into_kind() {
+        csv::ErrorKind::Io(e) => Err(LoadError::Io(e)),
+        _ => Ok(reason),
+    }
+}
+
+fn parse_csv_record(
+    columns: &[Option<usize>],
+    record: &csv::StringRecord,
+) -> Result<Submission, String> {
+    let column_count = columns.iter().flatten().max().map_or(0, |last| last + 1);
+    if record.len() < column_count {
+        return Err(format!(
+            "expected at least {} fields, got {}",
+            column_count,
+            record.len()
+        ));
+    }
+    let field = |index: usize| columns[index].map(|i| &record[i]);
+    let required = |index: usize| field(index).unwrap_or_default();
+    Ok(Submission {
+        id: parse_field(CSV_COLUMNS[0].0, required(0))?,
+        epoch_second: parse_field(CSV_COLUMNS[1].0, required(1))?,
+        problem_id: required(2).to_string(),
+        contest_id: required(3).to_string(),
+        user: required(4).to_string(),
+        language: required(5).to_string(),
+        score: parse_field(CSV_COLUMNS[6].0, required(6))?,
+        code_length: parse_field(CSV_COLUMNS[7].0, required(7))?,
+        result: required(8).to_string(),
+        execution_time: parse_optional_field(CSV_COLUMNS[9].0, field(9))?,
+        memory: parse_optional_field(CSV_COLUMNS[10].0, field(10))?,
+    })
+}
+
+/// Returns why a submission cannot be stored, if it cannot.
+pub fn validate_submission(submission: &Submission) -> Result<(), String> {
+    if submission.id <= 0 {
+        return Err(format!("id must be positive, got {}", submission.id));
+    }
+    if submission.epoch_second <= 0 {
+        return Err(format!(
+            "epoch_second must be positive, got {}",
+            submission.epoch_second
+        ));
+    }
+    for (name, value) in [
+        ("problem_id", &submission.problem_id),
+        ("contest_id", &submission.contest_id),
+        ("user", &submission.user),
+        ("language", &submission.language),
+        ("result", &submission.result),
+    ] {
+        if value.is_empty() {
+            return Err(format!("{} is empty", name));
+        }
+        if value.chars().count() > MAX_TEXT_LENGTH {
+            return Err(format!(
+                "{} is longer than {} characters",
+                name, MAX_TEXT_LENGTH
+            ));
+        }
+    }
+    if !submission.score.is_finite() || submission.score < 0.0 {
+        return Err(format!(
+            "score must be a non-negative number, got {}",
+            submission.score
+        ));
+    }
+    for (name, value) in [
+        ("code_length", Some(submission.code_length)),
+        ("execution_time", submission.execution_time),
+        ("memory", submission.memory),
+    ] {
+        if let Some(value) = value.filter(|value| *value < 0) {
+            return Err(format!("{} must not be negative, got {}", name, value));
+        }
+    }
+    Ok(())
+}
+
+/// Loads a dump into `submissions` through a staging table filled by `COPY`.
+///
+/// Every row is merged with the same rules as
+/// [`crate::crawler_utils::upsert_submissions`]: the last row of a submission
+/// listed twice wins, stored rows are only rewritten when they changed, and
+/// result changes are recorded in `submission_history`. Rows that fail
+/// validation are skipped and reported.
+///
+/// # Arguments
+///
+/// * `max_rejects` - Number of rejected rows after which the load is
+///   aborted, since the dump is then more likely in the wrong format
+/// * `dry_run` - Roll back the load instead of committing it
+pub async fn load_submissions<R: BufRead>(
+    db: &DatabaseConnection,
+    mut dump: DumpReader<R>,
+    max_rejects: usize,
+    dry_run: bool,
+) -> Result<LoadReport, LoadError> {
+    let backend = db.get_database_backend();
+    if backend != DbBackend::Postgres {
+        return Err(LoadError::UnsupportedBackend(backend));
+    }
+    let mut txn = db.get_postgres_connection_pool().begin().await?;
+    sqlx::query(CREATE_STAGING_TABLE).execute(&mut *txn).await?;
+
+    let mut report = LoadReport::default();
+    let mut copy = txn.copy_in_raw(COPY_STAGING_TABLE).await?;
+    let mut buffer = Vec::with_capacity(COPY_BUFFER_SIZE);
+    let staged = loop {
+        let (line, submission) = match dump.next_row() {
+            Ok(Some(row)) => row,
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(e),
+        };
+        match submission {
+            Ok(submission) => {
+                write_copy_row(&mut buffer, line, &submission);
+                report.rows_staged += 1;
+            }
+            Err(reject) if report.rejects.len() >= max_rejects => {
+                break Err(LoadError::TooManyRejects {
+                    limit: max_rejects,
+                    last: reject,
+                });
+            }
+            Err(reject) => report.rejects.push(reject),
+        }
+        if buffer.len() >= COPY_BUFFER_SIZE {
+            copy.send(buffer.as_slice()).await?;
+            buffer.clear();
+        }
+    };
+    if let Err(e) = staged {
+        copy.abort(e.to_string()).await?;
+        return Err(e);
+    }
+    if !buffer.is_empty() {
+        copy.send(buffer.as_slice()).await?;
+    }
+    copy.finish().await?;
+
+    // The progress of a running judge is skipped in Rust, where the pending
+    // results are defined.
+    let changes: Vec<(i64, String, String)> = sqlx::query_as(SELECT_RESULT_CHANGES)
+        .fetch_all(&mut *txn)
+        .await?;
+    let changed_ids: Vec<i64> = changes
+        .into_iter()
+        .filter(|(_, old_result, new_result)| !is_judge_progress(old_result, new_result))
+        .map(|(id, _, _)| id)
+        .collect();
+    sqlx::query(INSERT_RESULT_CHANGES)
+        .bind(chrono::Utc::now().timestamp())
+        .bind(changed_ids)
+        .execute(&mut *txn)
+        .await?;
+    let (inserted, updated): (i64, i64) = sqlx::query_as(MERGE_STAGING_TABLE)
+        .fetch_one(&mut *txn)
+        .await?;
+    report.inserted = inserted as u64;
+    report.updated = updated as u64;
+
+    if dry_run {
+        txn.rollback().await?;
+    } else {
+        txn.commit().await?;
+    }
+    Ok(report)
+}
+
+/// Maps each of [`CSV_COLUMNS`] to its position in the header.
+fn csv_columns(header: &csv::StringRecord) -> Result<Vec<Option<usize>>, LoadError> {
+    CSV_COLUMNS
+        .iter()
+        .map(|&(name, column)| {
+            let position = header
+                .iter()
+                .position(|h| h.trim() == name || Some(h.trim()) == column);
+            match position {
+                Some(position) => Ok(Some(position)),
+                None if OPTIONAL_CSV_COLUMNS.contains(&name) => Ok(None),
+                None => Err(LoadError::InvalidHeader(format!("missing column {}", name))),
+            }
+        })
+        .collect()
+}
+
+fn parse_field<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid {}: {:?}", name, value))
+}
+
+/// Parses a nullable field, which is empty or missing when it is `NULL`.
+fn parse_optional_field<T: std::str::FromStr>(
+    name: &str,
+    value: Option<&str>,
+) -> Result<Option<T>, String> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => parse_field(name, value).map(Some),
+    }
+}
+
+/// Appends a row of the staging table in the CSV format of `COPY`, where an
+/// unquoted empty field is `NULL`.
+fn write_copy_row(buffer: &mut Vec<u8>, line: u64, submission: &Submission) {
+    let quote = |value: &str| format!("\"{}\"", value.replace('"', "\"\""));
+    let optional = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+    let row = [
+        line.to_string(),
+        submission.id.to_string(),
+        submission.epoch_second.to_string(),
+        quote(&submission.problem_id),
+        quote(&submission.contest_id),
+        quote(&submission.user),
+        quote(&submission.language),
+        submission.score.to_string(),
+        submission.code_length.to_string(),
+        quote(&submission.result),
+        optional(submission.execution_time),
+        optional(submission.memory),
+    ]
+    .join(",");
+    buffer.extend_from_slice(row.as_bytes());
+    buffer.push(b'\n');
+}
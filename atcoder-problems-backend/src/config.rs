@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use crawler::RateLimit;
+use serde::Deserialize;
+
+/// Settings shared by every `atcoder-problems` subcommand.
+///
+/// They are read from an optional TOML file and then overridden by the
+/// environment variables the separate binaries used to read, so that existing
+/// deployments keep working without a file:
+///
+/// | Key                           | Environment variable          |
+/// |-------------------------------|-------------------------------|
+/// | `database_url`                | `DATABASE_URL`                |
+/// | `crawler.session`             | `REVEL_SESSION`               |
+/// | `crawler.requests_per_second` | `CRAWLER_REQUESTS_PER_SECOND` |
+/// | `crawler.burst`               | `CRAWLER_BURST`               |
+/// | `s3.bucket`                   | `S3_BUCKET_NAME`              |
+/// | `server.port`                 | `PORT`                        |
+/// | `server.client_id`            | `CLIENT_ID`                   |
+/// | `server.client_secret`        | `CLIENT_SECRET`               |
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub database_url: Option<String>,
+    pub crawler: CrawlerConfig,
+    pub s3: S3Config,
+    pub server: ServerConfig,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrawlerConfig {
+    /// AtCoder `REVEL_SESSION` cookie
+    pub session: Option<String>,
+    /// Sustained number of requests per second allowed for each host
+    pub requests_per_second: Option<f64>,
+    /// Number of requests that may be sent back to back after being idle
+    pub burst: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct S3Config {
+    /// Destination bucket of the dumped datasets and standings
+    pub bucket: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+    /// GitHub OAuth client_id used by the login function
+    pub client_id: Option<String>,
+    /// GitHub OAuth client_secret used by the login function
+    pub client_secret: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            client_id: None,
+            client_secret: None,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid config file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("{name} must be {expected}, got {value:?}")]
+    InvalidEnv {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+
+    #[error("{key} must be {expected}, got {value}")]
+    InvalidValue {
+        key: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+
+    #[error("{key} is not configured: set it in the config file or {env}")]
+    Missing {
+        key: &'static str,
+        env: &'static str,
+    },
+}
+
+impl Config {
+    /// Reads the given TOML file, if any, and applies the environment on top.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = match path {
+            Some(path) => {
+                let toml = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+                Self::from_toml(&toml)?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Overrides the settings with the environment variables that are set.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookup` - Returns the value of an environment variable, or `None`
+    ///   when it is not set
+    pub fn apply_env(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        if let Some(value) = lookup("DATABASE_URL") {
+            self.database_url = Some(value);
+        }
+        if let Some(value) = lookup("REVEL_SESSION") {
+            self.crawler.session = Some(value);
+        }
+        if let Some(value) = lookup("CRAWLER_REQUESTS_PER_SECOND") {
+            self.crawler.requests_per_second =
+                Some(parse_env("CRAWLER_REQUESTS_PER_SECOND", value, "a number")?);
+        }
+        if let Some(value) = lookup("CRAWLER_BURST") {
+            self.crawler.burst = Some(parse_env("CRAWLER_BURST", value, "a non-negative integer")?);
+        }
+        if let Some(value) = lookup("S3_BUCKET_NAME") {
+            self.s3.bucket = Some(value);
+        }
+        if let Some(value) = lookup("PORT") {
+            self.server.port = parse_env("PORT", value, "a port number")?;
+        }
+        if let Some(value) = lookup("CLIENT_ID") {
+            self.server.client_id = Some(value);
+        }
+        if let Some(value) = lookup("CLIENT_SECRET") {
+            self.server.client_secret = Some(value);
+        }
+        Ok(())
+    }
+
+    pub fn database_url(&self) -> Result<&str, ConfigError> {
+        required(&self.database_url, "database_url", "DATABASE_URL")
+    }
+
+    pub fn session(&self) -> Result<&str, ConfigError> {
+        required(&self.crawler.session, "crawler.session", "REVEL_SESSION")
+    }
+
+    pub fn bucket(&self) -> Result<&str, ConfigError> {
+        required(&self.s3.bucket, "s3.bucket", "S3_BUCKET_NAME")
+    }
+
+    pub fn client_id(&self) -> Result<&str, ConfigError> {
+        required(&self.server.client_id, "server.client_id", "CLIENT_ID")
+    }
+
+    pub fn client_secret(&self) -> Result<&str, ConfigError> {
+        required(
+            &self.server.client_secret,
+            "server.client_secret",
+            "CLIENT_SECRET",
+        )
+    }
+
+    /// Returns the request budget of the crawlers, falling back to the
+    /// default for the settings that are not configured.
+    pub fn rate_limit(&self) -> Result<RateLimit, ConfigError> {
+        let mut limit = RateLimit::default();
+        if let Some(requests_per_second) = self.crawler.requests_per_second {
+            if !requests_per_second.is_finite() || requests_per_second <= 0.0 {
+                return Err(ConfigError::InvalidValue {
+                    key: "crawler.requests_per_second",
+                    value: requests_per_second.to_string(),
+                    expected: "positive",
+                });
+            }
+            limit.requests_per_second = requests_per_second;
+        }
+        if let Some(burst) = self.crawler.burst {
+            limit.burst = burst;
+        }
+        Ok(limit)
+    }
+}
+
+fn required<'a>(
+    value: &'a Option<String>,
+    key: &'static str,
+    env: &'static str,
+) -> Result<&'a str, ConfigError> {
+    value.as_deref().ok_or(ConfigError::Missing { key, env })
+}
+
+fn parse_env<T: std::str::FromStr>(
+    name: &'static str,
+    value: String,
+    expected: &'static str,
+) -> Result<T, ConfigError> {
+    value.parse().map_err(|_| ConfigError::InvalidEnv {
+        name,
+        value,
+        expected,
+    })
+}
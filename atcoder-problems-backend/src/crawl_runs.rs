@@ -0,0 +1,189 @@
+use std::{
+    collections::BTreeSet,
+    future::Future,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crawler::FetchStats;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set};
+
+/// Number of `crawl_run_contests` rows inserted by one statement.
+const RUN_CONTEST_INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Age after which a run still marked `running` is assumed to have been killed
+/// before it could record its outcome.
+pub const STALE_RUN_SECOND: i64 = 24 * 3600;
+
+tokio::task_local! {
+    static CURRENT_RUN: Arc<RunStats>;
+}
+
+/// What the crawl functions wrote during a run tracked with [`track_run`].
+#[derive(Debug, Default)]
+pub struct RunStats {
+    rows_inserted: AtomicU64,
+    rows_updated: AtomicU64,
+    contest_ids: Mutex<BTreeSet<String>>,
+}
+
+impl RunStats {
+    pub fn rows_inserted(&self) -> u64 {
+        self.rows_inserted.load(Ordering::Relaxed)
+    }
+
+    pub fn rows_updated(&self) -> u64 {
+        self.rows_updated.load(Ordering::Relaxed)
+    }
+
+    /// Returns the contests whose submissions were crawled to the end.
+    pub fn contest_ids(&self) -> BTreeSet<String> {
+        self.contest_ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// Runs `future` and returns what the crawl functions it called wrote.
+pub async fn track_run<F: Future>(future: F) -> (F::Output, Arc<RunStats>) {
+    let stats = Arc::new(RunStats::default());
+    let output = CURRENT_RUN.scope(stats.clone(), future).await;
+    (output, stats)
+}
+
+/// Counts rows written by the tracked run; does nothing outside of one.
+pub(crate) fn record_rows(inserted: usize, updated: usize) {
+    let _ = CURRENT_RUN.try_with(|stats| {
+        stats
+            .rows_inserted
+            .fetch_add(inserted as u64, Ordering::Relaxed);
+        stats
+            .rows_updated
+            .fetch_add(updated as u64, Ordering::Relaxed);
+    });
+}
+
+/// Records that the tracked run crawled the submissions of a contest to the
+/// end; does nothing outside of one.
+pub(crate) fn record_contest(contest_id: &str) {
+    let _ = CURRENT_RUN.try_with(|stats| {
+        stats
+            .contest_ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(contest_id.to_string());
+    });
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// Killed before it recorded its outcome, see [`abandon_stale_runs`]
+    Abandoned,
+}
+
+impl RunStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunStatus::Running => "running",
+            RunStatus::Succeeded => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::Abandoned => "abandoned",
+        }
+    }
+}
+
+/// Inserts a `running` row for a run that starts now and returns its id.
+///
+/// # Arguments
+///
+/// * `command` - Crawl target, e.g. `submissions`
+/// * `mode` - Variant of the target, e.g. the submission crawl mode
+pub async fn start_run(
+    db: &DatabaseConnection,
+    command: &str,
+    mode: Option<&str>,
+    now: i64,
+) -> Result<i64, DbErr> {
+    let run = sql_entities::crawl_runs::ActiveModel {
+        command: Set(command.to_string()),
+        mode: Set(mode.map(str::to_string)),
+        status: Set(RunStatus::Running.as_str().to_string()),
+        started_epoch_second: Set(now),
+        finished_epoch_second: Set(None),
+        pages_fetched: Set(0),
+        rows_inserted: Set(0),
+        rows_updated: Set(0),
+        errors: Set(serde_json::json!({})),
+        error_message: Set(None),
+        ..Default::default()
+    };
+    let result = sql_entities::crawl_runs::Entity::insert(run)
+        .exec(db)
+        .await?;
+    Ok(result.last_insert_id)
+}
+
+/// Marks the runs that have been `running` for longer than
+/// [`STALE_RUN_SECOND`] as `abandoned` and returns how many there were.
+pub async fn abandon_stale_runs(db: &DatabaseConnection, now: i64) -> Result<u64, DbErr> {
+    use sql_entities::crawl_runs::{Column, Entity};
+    let result = Entity::update_many()
+        .col_expr(Column::Status, RunStatus::Abandoned.as_str().into())
+        .filter(Column::Status.eq(RunStatus::Running.as_str()))
+        .filter(Column::StartedEpochSecond.lt(now - STALE_RUN_SECOND))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+/// Completes the row of a run with its outcome and statistics.
+///
+/// # Arguments
+///
+/// * `error` - Error that aborted the run, or `None` if it succeeded
+pub async fn finish_run(
+    db: &DatabaseConnection,
+    run_id: i64,
+    stats: &RunStats,
+    fetch_stats: &FetchStats,
+    error: Option<&str>,
+    now: i64,
+) -> Result<(), DbErr> {
+    let status = match error {
+        Some(_) => RunStatus::Failed,
+        None => RunStatus::Succeeded,
+    };
+    sql_entities::crawl_runs::Entity::update(sql_entities::crawl_runs::ActiveModel {
+        id: Set(run_id),
+        status: Set(status.as_str().to_string()),
+        finished_epoch_second: Set(Some(now)),
+        pages_fetched: Set(fetch_stats.pages_fetched() as i64),
+        rows_inserted: Set(stats.rows_inserted() as i64),
+        rows_updated: Set(stats.rows_updated() as i64),
+        errors: Set(serde_json::json!(fetch_stats.errors())),
+        error_message: Set(error.map(str::to_string)),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    let contest_ids: Vec<String> = stats.contest_ids().into_iter().collect();
+    for chunk in contest_ids.chunks(RUN_CONTEST_INSERT_CHUNK_SIZE) {
+        let models = chunk
+            .iter()
+            .map(|contest_id| sql_entities::crawl_run_contests::ActiveModel {
+                run_id: Set(run_id),
+                contest_id: Set(contest_id.clone()),
+            });
+        sql_entities::crawl_run_contests::Entity::insert_many(models)
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
@@ -1,12 +1,25 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    time::Duration,
+};
 
 use crawler::{
-    Contest, ContestFetcher, CrawlerClient, CrawlerError, Problem, ProblemFetcher, Submission,
+    Contest, ContestFetcher, ContestType, CrawlerError, Editorial, EditorialFetcher, Problem,
+    ProblemFetcher, RatedRange, Standings, StandingsFetcher, Submission, SubmissionDetail,
+    SubmissionFetcher, UserFetcher, is_pending_result,
 };
+use futures::{StreamExt, stream};
+use rand::seq::SliceRandom;
 use sea_orm::{
-    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set, sea_query::OnConflict,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityName, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+    sea_query::{
+        Alias, BinOper, Expr, Func, LockBehavior, LockType, OnConflict, Query, SimpleExpr,
+    },
 };
 
+use crate::crawl_runs;
+
 const ATCODER_WEEKDAY_CONTEST_CATEGORY: u32 = 20;
 const ATCODER_DAILY_TRAINING_CATEGORY: u32 = 60;
 
@@ -18,84 +31,852 @@ const FILTERED_ARCHIVE_CATEGORIES: &[(u32, &str)] = &[
     (ATCODER_DAILY_TRAINING_CATEGORY, "AtCoder Daily Training"),
 ];
 
+/// Number of consecutive pages without any change after which an incremental
+/// submission crawl assumes it has caught up with the stored submissions.
+const MAX_UNCHANGED_SUBMISSION_PAGES: u32 = 5;
+
+/// Number of rows written by one multi-row `INSERT`, which keeps its bind
+/// parameters below the limits of Postgres (65535) and SQLite (32766).
+const UPSERT_CHUNK_SIZE: usize = 1000;
+
+/// Number of most solved problems whose accepted submissions are picked for
+/// judge case crawling.
+const DETAIL_POPULAR_PROBLEM_COUNT: u64 = 100;
+
+/// Contests lasting at least this long (e.g. practice contests) are permanent
+/// rather than running.
+const PERMANENT_CONTEST_DURATION_SECOND: i64 = 365 * 24 * 3600;
+
+/// Period after the end of a contest during which it still counts as active
+/// when ordering the crawl queue and picking the contests of a user crawl.
+const ACTIVE_CONTEST_WINDOW_SECOND: i64 = 30 * 24 * 3600;
+
+/// How long a worker holds a claimed queue entry; the entry of a worker that
+/// died is claimed by another one once this has passed.
+const CRAWL_QUEUE_LEASE_SECOND: i64 = 6 * 3600;
+
+/// Number of failed crawls after which a queued contest is left alone until it
+/// is enqueued again.
+const MAX_CRAWL_QUEUE_ATTEMPTS: i32 = 3;
+
+/// Number of queued entries inserted by one statement.
+const CRAWL_QUEUE_INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Users who submitted within this period count as active when refreshing
+/// profiles.
+const ACTIVE_USER_WINDOW_SECOND: i64 = 30 * 24 * 3600;
+
+/// Profiles crawled more recently than this are not refreshed again.
+const USER_PROFILE_REFRESH_INTERVAL_SECOND: i64 = 24 * 3600;
+
+/// Period after the end of a contest during which its editorial list is
+/// crawled on every incremental run, since editorials are mostly added then.
+const EDITORIAL_UPDATE_WINDOW_SECOND: i64 = 14 * 24 * 3600;
+
+/// Most contests a pruning reconcile deletes at once. More missing contests,
+/// or more than a tenth of the stored ones, more likely means that an archive
+/// page was cut short than that AtCoder removed them.
+const MAX_PRUNED_CONTESTS: usize = 20;
+
+/// Which contests `crawl submissions` visits and how far it paginates.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, clap::ValueEnum)]
+pub enum Mode {
+    /// Crawls every page of every contest, resuming where an interrupted run
+    /// stopped.
+    All,
+    /// Crawls the five contests that started most recently.
+    Recent,
+    /// Crawls every contest until the newest stored submission is reached.
+    New,
+    /// Crawls the contests whose problems are used by running virtual
+    /// contests.
+    VirtualContests,
+    /// Crawls judge case results of recent accepted submissions instead of
+    /// submission pages.
+    Details,
+    /// Re-crawls the newest pages of contests with pending submissions until
+    /// the oldest pending one is reached.
+    Pending,
+    /// Follows the running contests until they end.
+    Live,
+    /// Crawls the submissions of the users queued in `user_crawl_jobs` in the
+    /// contests they took part in or may have submitted to.
+    Users,
+}
+
+impl Mode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Mode::All => "all",
+            Mode::Recent => "recent",
+            Mode::New => "new",
+            Mode::VirtualContests => "virtual-contests",
+            Mode::Details => "details",
+            Mode::Pending => "pending",
+            Mode::Live => "live",
+            Mode::Users => "users",
+        }
+    }
+
+    /// Returns true if pagination should stop once it only sees submissions
+    /// that are already stored.
+    pub fn stops_on_unchanged_pages(self) -> bool {
+        matches!(self, Mode::New | Mode::VirtualContests | Mode::Live)
+    }
+}
+
+/// Connection the crawlers read and write through.
+pub trait CrawlDb: ConnectionTrait + TransactionTrait {}
+
+impl<T: ConnectionTrait + TransactionTrait> CrawlDb for T {}
+
+tokio::task_local! {
+    static DRY_RUN: ();
+}
+
+/// Runs `future` as a dry run: the crawl functions it calls fetch, parse and
+/// read the database as usual, but only log the rows they would write.
+///
+/// The crawl queue and user crawl jobs are claimed by writing to them, so a
+/// dry run has to visit their contests and users without claiming them.
+pub async fn dry_run<F: Future>(future: F) -> F::Output {
+    DRY_RUN.scope((), future).await
+}
+
+/// Returns true inside [`dry_run`].
+pub fn is_dry_run() -> bool {
+    DRY_RUN.try_with(|_| ()).is_ok()
+}
+
+/// Returns true, after logging it, if `rows` rows of `table` are not to be
+/// written because of a dry run.
+fn skip_write(table: &str, rows: usize) -> bool {
+    let skip = is_dry_run();
+    if skip {
+        tracing::info!("Dry run: skipping the write of {} rows of {}", rows, table);
+    }
+    skip
+}
+
+/// Errors that abort a crawl.
+#[derive(thiserror::Error, Debug)]
+pub enum CrawlError {
+    #[error("database error: {0}")]
+    Db(#[from] DbErr),
+
+    #[error("crawler error: {0}")]
+    Crawler(#[from] CrawlerError),
+}
+
+/// Fetches a page of submissions, treating a missing page as an empty one.
+///
+/// Any other error, including running out of retries, is returned so that
+/// pagination stops loudly instead of mistaking an outage for the last page.
 pub async fn fetch_submissions(
-    crawler: &CrawlerClient,
+    fetcher: &dyn SubmissionFetcher,
     contest_id: &str,
     page: i32,
-) -> Vec<Submission> {
-    const MAX_RETRIES: u32 = 10;
-    let mut retry_count = 0;
-    let mut retry_delay = 2000;
-
-    while retry_count < MAX_RETRIES {
-        match crawler.fetch_submissions(contest_id, page).await {
-            Ok(submissions) => {
-                return submissions;
+) -> Result<Vec<Submission>, CrawlerError> {
+    match fetcher.fetch_submissions(contest_id, page).await {
+        Ok(submissions) => Ok(submissions),
+        Err(CrawlerError::NotFound) => {
+            tracing::warn!(
+                "Submissions page not found for contest {} page {} (404), skipping",
+                contest_id,
+                page
+            );
+            Ok(vec![])
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Crawls the submission pages of a contest and upserts them into the database.
+///
+/// Pages are fetched from the newest one until an empty page is reached, and
+/// the progress is kept in the `crawl_state` table:
+///
+/// - In modes that [stop on unchanged pages](Mode::stops_on_unchanged_pages),
+///   the crawl ends at the first page reaching the newest submission stored by
+///   the previous crawl. A contest without such a record instead ends after
+///   several consecutive pages that change nothing.
+/// - In [`Mode::Pending`], the crawl ends at the page of the oldest submission
+///   whose result is still pending.
+/// - In [`Mode::All`], every page stored is recorded, so that a crawl that was
+///   interrupted resumes from the following page.
+///
+/// The newest submission is only recorded once the crawl completes, so that an
+/// interrupted crawl never hides the pages it did not reach.
+///
+/// Returns the total number of submissions inserted/updated.
+pub async fn crawl_submissions(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    contest_id: &str,
+    mode: Mode,
+) -> Result<usize, CrawlError> {
+    tracing::info!("Fetching submissions for contest {}", contest_id);
+
+    let mut state = sql_entities::crawl_state::Entity::find_by_id(contest_id)
+        .one(db)
+        .await?
+        .unwrap_or_else(|| sql_entities::crawl_state::Model {
+            contest_id: contest_id.to_string(),
+            max_submission_id: None,
+            last_crawled_page: 0,
+            last_crawled_epoch_second: None,
+        });
+    let boundary = match mode {
+        Mode::Pending => match oldest_pending_submission_id(db, contest_id).await? {
+            Some(id) => Some(id),
+            None => {
+                tracing::info!("No pending submissions for contest {}", contest_id);
+                return Ok(0);
             }
-            Err(CrawlerError::NotFound) => {
-                tracing::warn!(
-                    "Submissions page not found for contest {} page {} (404), skipping",
-                    contest_id,
-                    page
-                );
-                return vec![];
+        },
+        _ if mode.stops_on_unchanged_pages() => state.max_submission_id,
+        _ => None,
+    };
+    let first_page = if mode == Mode::All {
+        state.last_crawled_page + 1
+    } else {
+        1
+    };
+    if first_page > 1 {
+        tracing::info!(
+            "Resuming submissions for contest {} from page {}",
+            contest_id,
+            first_page
+        );
+    }
+
+    let mut total_inserted = 0;
+    let mut unchanged_pages = 0;
+    let mut max_submission_id = state.max_submission_id;
+    for page in first_page.. {
+        tracing::info!(
+            "Fetching submissions for contest {} page {}",
+            contest_id,
+            page
+        );
+        let submissions = fetch_submissions(fetcher, contest_id, page).await?;
+        if submissions.is_empty() {
+            tracing::info!("No more submissions for contest {}", contest_id);
+            break;
+        }
+
+        let reached_boundary =
+            boundary.is_some_and(|boundary| submissions.iter().any(|s| s.id <= boundary));
+        max_submission_id = submissions
+            .iter()
+            .map(|s| s.id)
+            .chain(max_submission_id)
+            .max();
+
+        tracing::info!("Inserting {} submissions", submissions.len());
+        let inserted = upsert_submissions(db, submissions).await?;
+        tracing::info!("Inserted {} submissions", inserted);
+        total_inserted += inserted;
+
+        if mode == Mode::All {
+            state.last_crawled_page = page;
+            save_crawl_state(db, state.clone()).await?;
+        }
+
+        if reached_boundary {
+            tracing::info!("Reached known submissions of contest {}", contest_id);
+            break;
+        }
+
+        if inserted > 0 {
+            unchanged_pages = 0;
+        } else {
+            unchanged_pages += 1;
+        }
+
+        if unchanged_pages >= MAX_UNCHANGED_SUBMISSION_PAGES && mode.stops_on_unchanged_pages() {
+            tracing::info!("No new submissions for contest {}", contest_id);
+            break;
+        }
+    }
+
+    state.max_submission_id = max_submission_id;
+    if mode == Mode::All {
+        state.last_crawled_page = 0;
+    }
+    state.last_crawled_epoch_second = Some(chrono::Utc::now().timestamp());
+    save_crawl_state(db, state).await?;
+    crawl_runs::record_contest(contest_id);
+
+    tracing::info!("Finished fetching submissions for contest {}", contest_id);
+    Ok(total_inserted)
+}
+
+/// Returns the condition matching submissions whose result is not final.
+///
+/// Mirrors [`crawler::is_pending_result`].
+fn pending_result_condition() -> Condition {
+    Condition::any()
+        .add(sql_entities::submissions::Column::Result.is_in(["WJ", "WR"]))
+        .add(sql_entities::submissions::Column::Result.like("%/%"))
+        .add(sql_entities::submissions::Column::Result.like("% WJ"))
+}
+
+async fn oldest_pending_submission_id(
+    db: &impl CrawlDb,
+    contest_id: &str,
+) -> Result<Option<i64>, DbErr> {
+    let submission = sql_entities::submissions::Entity::find()
+        .filter(sql_entities::submissions::Column::ContestId.eq(contest_id))
+        .filter(pending_result_condition())
+        .order_by_asc(sql_entities::submissions::Column::Id)
+        .one(db)
+        .await?;
+    Ok(submission.map(|s| s.id))
+}
+
+/// Returns the ids of the contests that have submissions with a pending
+/// result, to be refreshed by [`Mode::Pending`].
+pub async fn contest_ids_with_pending_submissions(db: &impl CrawlDb) -> Result<Vec<String>, DbErr> {
+    sql_entities::submissions::Entity::find()
+        .select_only()
+        .column(sql_entities::submissions::Column::ContestId)
+        .distinct()
+        .filter(pending_result_condition())
+        .order_by_asc(sql_entities::submissions::Column::ContestId)
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+async fn save_crawl_state(
+    db: &impl CrawlDb,
+    state: sql_entities::crawl_state::Model,
+) -> Result<(), DbErr> {
+    let model = sql_entities::crawl_state::ActiveModel {
+        contest_id: Set(state.contest_id),
+        max_submission_id: Set(state.max_submission_id),
+        last_crawled_page: Set(state.last_crawled_page),
+        last_crawled_epoch_second: Set(state.last_crawled_epoch_second),
+    };
+    if skip_write("crawl_state", 1) {
+        return Ok(());
+    }
+    sql_entities::crawl_state::Entity::insert(model)
+        .on_conflict(
+            OnConflict::column(sql_entities::crawl_state::Column::ContestId)
+                .update_columns([
+                    sql_entities::crawl_state::Column::MaxSubmissionId,
+                    sql_entities::crawl_state::Column::LastCrawledPage,
+                    sql_entities::crawl_state::Column::LastCrawledEpochSecond,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Returns the ids of all contests in the order a full crawl visits them.
+///
+/// Contests whose crawl was interrupted come first, followed by the ones that
+/// have gone the longest without a completed crawl, so that a full crawl that
+/// was stopped picks up where it left off.
+pub async fn contest_ids_for_full_crawl(db: &impl CrawlDb) -> Result<Vec<String>, DbErr> {
+    let states = sql_entities::crawl_state::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|state| (state.contest_id.clone(), state))
+        .collect::<HashMap<_, _>>();
+    let mut contest_ids = sql_entities::contests::Entity::find()
+        .order_by_asc(sql_entities::contests::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|contest| contest.id)
+        .collect::<Vec<_>>();
+    contest_ids.sort_by_key(|contest_id| match states.get(contest_id) {
+        Some(state) => (
+            state.last_crawled_page == 0,
+            state.last_crawled_epoch_second,
+        ),
+        None => (true, None),
+    });
+    Ok(contest_ids)
+}
+
+/// Returns true if the contest is running at `now`, ignoring permanent
+/// contests that never end.
+pub fn is_live_contest(contest: &sql_entities::contests::Model, now: i64) -> bool {
+    contest.duration_second < PERMANENT_CONTEST_DURATION_SECOND
+        && contest.start_epoch_second <= now
+        && now < contest.start_epoch_second + contest.duration_second
+}
+
+/// Returns the contests running at `now`.
+pub async fn live_contests(
+    db: &impl CrawlDb,
+    now: i64,
+) -> Result<Vec<sql_entities::contests::Model>, DbErr> {
+    let contests = sql_entities::contests::Entity::find()
+        .filter(sql_entities::contests::Column::StartEpochSecond.lte(now))
+        .order_by_asc(sql_entities::contests::Column::Id)
+        .all(db)
+        .await?;
+    Ok(contests
+        .into_iter()
+        .filter(|contest| is_live_contest(contest, now))
+        .collect())
+}
+
+/// Bounds of the interval between two polls of a running contest.
+///
+/// The interval is halved after a poll that stored something and doubled after
+/// one that did not, so busy contests are followed closely and quiet ones are
+/// not polled in vain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivePolling {
+    pub min_interval: Duration,
+    pub max_interval: Duration,
+}
+
+impl Default for LivePolling {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(10),
+            max_interval: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Polls the submissions of the running contests until all of them end.
+///
+/// Returns the total number of submissions inserted/updated.
+pub async fn crawl_live_contests(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    polling: LivePolling,
+) -> Result<usize, CrawlError> {
+    let contests = live_contests(db, chrono::Utc::now().timestamp()).await?;
+    if contests.is_empty() {
+        tracing::info!("No contest is running");
+        return Ok(0);
+    }
+    tracing::info!("Following {} running contests", contests.len());
+
+    let results = futures::future::join_all(
+        contests
+            .iter()
+            .map(|contest| crawl_live_contest(fetcher, db, contest, polling)),
+    )
+    .await;
+    let mut total_inserted = 0;
+    for result in results {
+        total_inserted += result?;
+    }
+    Ok(total_inserted)
+}
+
+/// Polls the submissions of a running contest until it ends.
+///
+/// Each poll walks the newest pages down to the submissions stored by the
+/// previous one. A poll that fails is logged and retried at the next interval
+/// unless the session has expired, and one last poll after the end picks up
+/// the final submissions.
+///
+/// Returns the total number of submissions inserted/updated.
+pub async fn crawl_live_contest(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    contest: &sql_entities::contests::Model,
+    polling: LivePolling,
+) -> Result<usize, CrawlError> {
+    let end = contest.start_epoch_second + contest.duration_second;
+    let remaining = (end - chrono::Utc::now().timestamp()).max(0);
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(remaining.unsigned_abs());
+
+    let mut total_inserted = 0;
+    let mut interval = polling.min_interval;
+    loop {
+        let ended = tokio::time::Instant::now() >= deadline;
+        match crawl_submissions(fetcher, db, &contest.id, Mode::Live).await {
+            Ok(inserted) => {
+                total_inserted += inserted;
+                interval = if inserted > 0 {
+                    interval / 2
+                } else {
+                    interval * 2
+                };
+                interval = interval.clamp(polling.min_interval, polling.max_interval);
             }
-            Err(e) => {
-                retry_count += 1;
-                tracing::warn!(
-                    "Failed to fetch submissions (attempt {}/{}): {}",
-                    retry_count,
-                    MAX_RETRIES,
-                    e
-                );
-                tokio::time::sleep(std::time::Duration::from_millis(retry_delay)).await;
-                retry_delay *= 2;
+            Err(CrawlError::Db(e)) => return Err(e.into()),
+            Err(CrawlError::Crawler(e @ CrawlerError::SessionExpired { .. })) => {
+                return Err(e.into());
             }
+            Err(CrawlError::Crawler(e)) => {
+                tracing::error!("Failed to poll submissions of {}: {}", contest.id, e);
+            }
+        }
+        if ended {
+            break;
         }
+        tracing::debug!("Polling {} again in {:?}", contest.id, interval);
+        tokio::time::sleep_until(deadline.min(tokio::time::Instant::now() + interval)).await;
     }
 
-    tracing::error!("Failed to fetch submissions after {} retries", MAX_RETRIES);
-    vec![]
+    tracing::info!("Contest {} has ended", contest.id);
+    Ok(total_inserted)
+}
+
+/// Returns how urgently a contest should be crawled; higher goes first.
+///
+/// Running contests come first, then contests whose full crawl was
+/// interrupted. The rest are ordered by the seconds since their last completed
+/// crawl, plus a bonus for contests that ended recently and still receive
+/// submissions.
+pub fn crawl_priority(
+    contest: &sql_entities::contests::Model,
+    state: Option<&sql_entities::crawl_state::Model>,
+    now: i64,
+) -> i64 {
+    if is_live_contest(contest, now) {
+        return i64::MAX;
+    }
+    if state.is_some_and(|state| state.last_crawled_page > 0) {
+        return i64::MAX - 1;
+    }
+    let last_crawled = state
+        .and_then(|state| state.last_crawled_epoch_second)
+        .unwrap_or(0);
+    let staleness = (now - last_crawled).max(0);
+    let since_end = (now - contest.start_epoch_second - contest.duration_second).max(0);
+    let activity = (ACTIVE_CONTEST_WINDOW_SECOND - since_end).max(0);
+    staleness.saturating_add(activity)
+}
+
+/// Contests added to the crawl queue by [`enqueue_contests`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Enqueued {
+    /// Number of contests queued.
+    pub queued: usize,
+    /// Requested contest ids missing from `contests`, which are not queued.
+    pub unknown: Vec<String>,
+}
+
+/// Adds the contests to the crawl queue, or refreshes their priority and
+/// mode if they are queued already.
+///
+/// Failed attempts of waiting entries are reset. An entry being crawled keeps
+/// its claim and is marked to be requeued, so that the worker puts it back
+/// with the new mode when it is done instead of removing it.
+pub async fn enqueue_contests(
+    db: &impl CrawlDb,
+    contest_ids: &[String],
+    mode: Mode,
+) -> Result<Enqueued, DbErr> {
+    use sql_entities::crawl_queue::{Column, Entity};
+
+    let now = chrono::Utc::now().timestamp();
+    let mut enqueued = Enqueued::default();
+    for chunk in contest_ids.chunks(CRAWL_QUEUE_INSERT_CHUNK_SIZE) {
+        let contests = sql_entities::contests::Entity::find()
+            .filter(sql_entities::contests::Column::Id.is_in(chunk))
+            .all(db)
+            .await?;
+        let known = contests
+            .iter()
+            .map(|contest| contest.id.as_str())
+            .collect::<HashSet<_>>();
+        enqueued.unknown.extend(
+            chunk
+                .iter()
+                .filter(|contest_id| !known.contains(contest_id.as_str()))
+                .cloned(),
+        );
+        if contests.is_empty() {
+            continue;
+        }
+        let states = sql_entities::crawl_state::Entity::find()
+            .filter(sql_entities::crawl_state::Column::ContestId.is_in(chunk))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|state| (state.contest_id.clone(), state))
+            .collect::<HashMap<_, _>>();
+
+        let entries = contests
+            .iter()
+            .map(|contest| sql_entities::crawl_queue::ActiveModel {
+                contest_id: Set(contest.id.clone()),
+                mode: Set(mode.as_str().to_string()),
+                priority: Set(crawl_priority(contest, states.get(&contest.id), now)),
+                attempts: Set(0),
+                claimed_by: Set(None),
+                claimed_until: Set(None),
+                requeue: Set(false),
+                enqueued_epoch_second: Set(now),
+            })
+            .collect::<Vec<_>>();
+        enqueued.queued += entries.len();
+
+        let claimed_until = || Expr::col((Entity, Column::ClaimedUntil));
+        let unclaimed = claimed_until().is_null().or(claimed_until().lte(now));
+        let txn = db.begin().await?;
+        Entity::insert_many(entries)
+            .on_conflict(
+                OnConflict::column(Column::ContestId)
+                    .update_columns([
+                        Column::Mode,
+                        Column::Priority,
+                        Column::Attempts,
+                        Column::EnqueuedEpochSecond,
+                    ])
+                    .action_and_where(unclaimed)
+                    .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+        Entity::update_many()
+            .col_expr(Column::Mode, Expr::value(mode.as_str()))
+            .col_expr(Column::Requeue, Expr::value(true))
+            .col_expr(Column::EnqueuedEpochSecond, Expr::value(now))
+            .filter(Column::ContestId.is_in(chunk))
+            .filter(Column::ClaimedUntil.gt(now))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+    }
+    Ok(enqueued)
+}
+
+/// Claims up to `limit` queued contests with the highest priority for
+/// `worker_id`.
+///
+/// Entries are locked with `FOR UPDATE SKIP LOCKED`, so concurrent workers
+/// sharing the database never claim the same contest. An entry stays claimed
+/// until its lease expires or the worker releases it.
+pub async fn claim_contests(
+    db: &impl CrawlDb,
+    worker_id: &str,
+    limit: u64,
+) -> Result<Vec<sql_entities::crawl_queue::Model>, DbErr> {
+    let now = chrono::Utc::now().timestamp();
+    let txn = db.begin().await?;
+    let mut entries = sql_entities::crawl_queue::Entity::find()
+        .filter(sql_entities::crawl_queue::Column::Attempts.lt(MAX_CRAWL_QUEUE_ATTEMPTS))
+        .filter(
+            Condition::any()
+                .add(sql_entities::crawl_queue::Column::ClaimedUntil.is_null())
+                .add(sql_entities::crawl_queue::Column::ClaimedUntil.lte(now)),
+        )
+        .order_by_desc(sql_entities::crawl_queue::Column::Priority)
+        .order_by_asc(sql_entities::crawl_queue::Column::ContestId)
+        .limit(limit)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .all(&txn)
+        .await?;
+    if entries.is_empty() {
+        txn.commit().await?;
+        return Ok(entries);
+    }
+
+    let claimed_until = now + CRAWL_QUEUE_LEASE_SECOND;
+    sql_entities::crawl_queue::Entity::update_many()
+        .col_expr(
+            sql_entities::crawl_queue::Column::ClaimedBy,
+            Expr::value(worker_id),
+        )
+        .col_expr(
+            sql_entities::crawl_queue::Column::ClaimedUntil,
+            Expr::value(claimed_until),
+        )
+        .filter(
+            sql_entities::crawl_queue::Column::ContestId
+                .is_in(entries.iter().map(|entry| entry.contest_id.as_str())),
+        )
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+
+    for entry in &mut entries {
+        entry.claimed_by = Some(worker_id.to_string());
+        entry.claimed_until = Some(claimed_until);
+    }
+    Ok(entries)
+}
+
+/// Outcome of [`run_crawl_queue`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CrawlQueueSummary {
+    /// Total number of submissions inserted/updated.
+    pub inserted: usize,
+    /// Contests whose last attempt in this run failed.
+    pub failed: Vec<String>,
+}
+
+/// Crawls queued contests until the queue is drained, keeping up to
+/// `concurrency` contests in flight.
+///
+/// All contests share `fetcher`, so a [`crawler::CrawlerClient`] keeps a single
+/// rate budget across them. Several processes can run this against the same
+/// database to share the queue. A contest that fails is released and retried
+/// later, up to a few attempts, and is reported in the summary; database
+/// errors and an expired session abort the run.
+pub async fn run_crawl_queue(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    worker_id: &str,
+    concurrency: usize,
+) -> Result<CrawlQueueSummary, CrawlError> {
+    let claims = stream::unfold(false, |failed| async move {
+        if failed {
+            return None;
+        }
+        match claim_contests(db, worker_id, 1).await {
+            Ok(mut entries) => entries.pop().map(|entry| (Ok(entry), false)),
+            Err(e) => Some((Err(e), true)),
+        }
+    });
+    let crawls = claims
+        .map(|claim| async move {
+            let entry = claim?;
+            let contest_id = entry.contest_id.clone();
+            let inserted = crawl_queued_contest(fetcher, db, worker_id, entry).await?;
+            Ok::<_, CrawlError>((contest_id, inserted))
+        })
+        .buffer_unordered(concurrency.max(1));
+    let mut crawls = std::pin::pin!(crawls);
+
+    let mut inserted = 0;
+    let mut failed = BTreeSet::new();
+    while let Some(result) = crawls.next().await {
+        match result? {
+            (contest_id, Some(count)) => {
+                inserted += count;
+                failed.remove(&contest_id);
+            }
+            (contest_id, None) => {
+                failed.insert(contest_id);
+            }
+        }
+    }
+    Ok(CrawlQueueSummary {
+        inserted,
+        failed: failed.into_iter().collect(),
+    })
+}
+
+/// Crawls a claimed entry and removes it from the queue, or puts it back when
+/// it was enqueued again meanwhile.
+///
+/// Returns `None` when the crawl failed and the entry was released for a
+/// later attempt.
+async fn crawl_queued_contest(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    worker_id: &str,
+    entry: sql_entities::crawl_queue::Model,
+) -> Result<Option<usize>, CrawlError> {
+    use sql_entities::crawl_queue::{Column, Entity};
+
+    let owned = Condition::all()
+        .add(Column::ContestId.eq(&entry.contest_id))
+        .add(Column::ClaimedBy.eq(worker_id));
+    let release = || {
+        Entity::update_many()
+            .col_expr(Column::ClaimedBy, Expr::value(Option::<String>::None))
+            .col_expr(Column::ClaimedUntil, Expr::value(Option::<i64>::None))
+            .col_expr(Column::Requeue, Expr::value(false))
+            .filter(owned.clone())
+    };
+    let result = match <Mode as clap::ValueEnum>::from_str(&entry.mode, false) {
+        Ok(mode) => crawl_submissions(fetcher, db, &entry.contest_id, mode).await,
+        Err(e) => {
+            tracing::error!("Dropping {} from the crawl queue: {}", entry.contest_id, e);
+            Ok(0)
+        }
+    };
+    match result {
+        Ok(inserted) => {
+            Entity::delete_many()
+                .filter(owned.clone())
+                .filter(Column::Requeue.eq(false))
+                .exec(db)
+                .await?;
+            // Left only when enqueued again while being crawled.
+            release()
+                .col_expr(Column::Attempts, Expr::value(0))
+                .exec(db)
+                .await?;
+            Ok(Some(inserted))
+        }
+        Err(CrawlError::Db(e)) => Err(e.into()),
+        Err(CrawlError::Crawler(e @ CrawlerError::SessionExpired { .. })) => {
+            // Not the contest's fault, so the attempt is not counted.
+            release().exec(db).await?;
+            Err(e.into())
+        }
+        Err(CrawlError::Crawler(e)) => {
+            tracing::error!(
+                "Failed to crawl submissions of {} (attempt {}): {}",
+                entry.contest_id,
+                entry.attempts + 1,
+                e
+            );
+            // Enqueuing again resets the attempts, as for a waiting entry.
+            let attempts =
+                Expr::case(Column::Requeue.eq(true), 0).finally(Expr::col(Column::Attempts).add(1));
+            release()
+                .col_expr(Column::Attempts, attempts.into())
+                .exec(db)
+                .await?;
+            Ok(None)
+        }
+    }
 }
 
 pub async fn upsert_submissions(
-    db: &DatabaseConnection,
+    db: &impl CrawlDb,
     new_submissions: Vec<Submission>,
 ) -> Result<usize, DbErr> {
-    let existing_submissions = sql_entities::submissions::Entity::find()
-        .filter(sql_entities::submissions::Column::Id.is_in(new_submissions.iter().map(|s| s.id)))
+    let new_submissions = dedupe_by_key(new_submissions, |s| s.id);
+    let stored_results: HashMap<i64, (String, f64)> = sql_entities::submissions::Entity::find()
+        .select_only()
+        .columns([
+            sql_entities::submissions::Column::Id,
+            sql_entities::submissions::Column::Result,
+            sql_entities::submissions::Column::Point,
+        ])
+        .filter(sql_entities::submissions::Column::Id.is_in(new_submissions.keys().copied()))
+        .into_tuple::<(i64, String, f64)>()
         .all(db)
         .await?
         .into_iter()
-        .map(|s| {
-            (
-                s.id,
-                Submission {
-                    id: s.id,
-                    epoch_second: s.epoch_second,
-                    problem_id: s.problem_id,
-                    contest_id: s.contest_id,
-                    user: s.user_id,
-                    language: s.language,
-                    score: s.point,
-                    code_length: s.length,
-                    result: s.result,
-                    execution_time: s.execution_time,
-                },
-            )
-        })
-        .collect::<HashMap<_, _>>();
+        .map(|(id, result, point)| (id, (result, point)))
+        .collect();
 
-    let mut inserted_submissions = 0;
-    for new_submission in new_submissions {
-        let existing_submission = existing_submissions.get(&new_submission.id);
-        if let Some(existing_submission) = existing_submission
-            && existing_submission == &new_submission
-        {
-            continue;
+    let changed_epoch_second = chrono::Utc::now().timestamp();
+    let mut new_rows = 0;
+    let mut changed_results = 0;
+    let mut history = Vec::new();
+    let mut models = Vec::with_capacity(new_submissions.len());
+    for new_submission in new_submissions.into_values() {
+        match stored_results.get(&new_submission.id) {
+            Some((old_result, old_point)) => {
+                if *old_result != new_submission.result || *old_point != new_submission.score {
+                    changed_results += 1;
+                }
+                history.extend(result_change(
+                    old_result,
+                    *old_point,
+                    &new_submission,
+                    changed_epoch_second,
+                ));
+            }
+            None => new_rows += 1,
         }
-
-        let submission = sql_entities::submissions::ActiveModel {
+        models.push(sql_entities::submissions::ActiveModel {
             id: Set(new_submission.id),
             epoch_second: Set(new_submission.epoch_second),
             problem_id: Set(new_submission.problem_id),
@@ -106,28 +887,589 @@ pub async fn upsert_submissions(
             length: Set(new_submission.code_length),
             result: Set(new_submission.result),
             execution_time: Set(new_submission.execution_time),
+            memory: Set(new_submission.memory),
+        });
+    }
+    if skip_write("submissions", models.len()) {
+        // Pagination stops on pages without changes, which are told apart by
+        // the stored results alone.
+        return Ok(new_rows + changed_results);
+    }
+
+    let txn = db.begin().await?;
+    upsert_chunked(
+        &txn,
+        history,
+        OnConflict::columns([
+            sql_entities::submission_history::Column::SubmissionId,
+            sql_entities::submission_history::Column::ChangedEpochSecond,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .await?;
+    let written = upsert_chunked(
+        &txn,
+        models,
+        upsert_if_changed(
+            [sql_entities::submissions::Column::Id],
+            [
+                sql_entities::submissions::Column::EpochSecond,
+                sql_entities::submissions::Column::ProblemId,
+                sql_entities::submissions::Column::ContestId,
+                sql_entities::submissions::Column::UserId,
+                sql_entities::submissions::Column::Language,
+                sql_entities::submissions::Column::Point,
+                sql_entities::submissions::Column::Length,
+                sql_entities::submissions::Column::Result,
+                sql_entities::submissions::Column::ExecutionTime,
+                sql_entities::submissions::Column::Memory,
+            ],
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    crawl_runs::record_rows(new_rows, written.saturating_sub(new_rows));
+    Ok(written)
+}
+
+/// Returns true if a result changing from `old_result` to `new_result` is only
+/// the progress of a running judge, e.g. from `3/20 WJ` to `5/20 WJ`, which is
+/// not a change worth keeping in `submission_history`.
+pub(crate) fn is_judge_progress(old_result: &str, new_result: &str) -> bool {
+    is_pending_result(old_result) && is_pending_result(new_result)
+}
+
+/// Returns the `submission_history` row recording a change of the result or
+/// score of a stored submission, if there is one worth keeping, see
+/// [`is_judge_progress`].
+fn result_change(
+    old_result: &str,
+    old_point: f64,
+    new: &Submission,
+    changed_epoch_second: i64,
+) -> Option<sql_entities::submission_history::ActiveModel> {
+    if old_result == new.result && old_point == new.score {
+        return None;
+    }
+    if is_judge_progress(old_result, &new.result) {
+        return None;
+    }
+    tracing::info!(
+        "Result of submission {} changed from {} to {}",
+        new.id,
+        old_result,
+        new.result
+    );
+    Some(sql_entities::submission_history::ActiveModel {
+        submission_id: Set(new.id),
+        changed_epoch_second: Set(changed_epoch_second),
+        old_result: Set(old_result.to_string()),
+        new_result: Set(new.result.clone()),
+        old_point: Set(old_point),
+        new_point: Set(new.score),
+    })
+}
+
+/// Returns an upsert that overwrites a stored row only when one of
+/// `update_columns` differs from the proposed row, so that unchanged rows are
+/// neither rewritten nor counted as affected.
+fn upsert_if_changed<C: ColumnTrait>(
+    key_columns: impl IntoIterator<Item = C>,
+    update_columns: impl IntoIterator<Item = C> + Clone,
+) -> OnConflict {
+    upsert_if_changed_keeping_known(key_columns, update_columns, std::iter::empty())
+}
+
+/// Like [`upsert_if_changed`], but a NULL proposed for one of `known_columns`
+/// keeps the stored value, for metadata that a failed fetch leaves unknown.
+fn upsert_if_changed_keeping_known<C: ColumnTrait>(
+    key_columns: impl IntoIterator<Item = C>,
+    update_columns: impl IntoIterator<Item = C> + Clone,
+    known_columns: impl IntoIterator<Item = C> + Clone,
+) -> OnConflict {
+    let distinct = |column: C| {
+        Expr::col((column.entity_name(), column)).binary(
+            BinOper::Custom("IS DISTINCT FROM"),
+            Expr::col((Alias::new("excluded"), column)),
+        )
+    };
+    let changed = update_columns
+        .clone()
+        .into_iter()
+        .map(distinct)
+        .chain(known_columns.clone().into_iter().map(|column| {
+            Expr::col((Alias::new("excluded"), column))
+                .is_not_null()
+                .and(distinct(column))
+        }))
+        .reduce(SimpleExpr::or);
+    let known_values = known_columns.into_iter().map(|column| {
+        let value: SimpleExpr = Func::coalesce([
+            Expr::col((Alias::new("excluded"), column)).into(),
+            Expr::col((column.entity_name(), column)).into(),
+        ])
+        .into();
+        (column, value)
+    });
+    OnConflict::columns(key_columns)
+        .update_columns(update_columns)
+        .values(known_values)
+        .action_and_where_option(changed)
+        .to_owned()
+}
+
+/// Keys `items` by `key`, keeping the last of the items that share one.
+///
+/// Postgres rejects a statement that updates the same row twice, so the rows
+/// of an upsert are deduplicated by their primary key first.
+fn dedupe_by_key<T, K: Ord>(
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> K,
+) -> BTreeMap<K, T> {
+    items.into_iter().map(|item| (key(&item), item)).collect()
+}
+
+/// Inserts `models` with `on_conflict` in statements of at most
+/// [`UPSERT_CHUNK_SIZE`] rows.
+///
+/// Returns the number of rows inserted or updated.
+async fn upsert_chunked<A>(
+    db: &impl ConnectionTrait,
+    models: Vec<A>,
+    on_conflict: OnConflict,
+) -> Result<usize, DbErr>
+where
+    A: ActiveModelTrait,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+{
+    if skip_write(A::Entity::default().table_name(), models.len()) {
+        return Ok(0);
+    }
+    let mut written = 0;
+    let mut models = models.into_iter().peekable();
+    while models.peek().is_some() {
+        let chunk: Vec<A> = models.by_ref().take(UPSERT_CHUNK_SIZE).collect();
+        written += A::Entity::insert_many(chunk)
+            .on_conflict(on_conflict.clone())
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(written as usize)
+}
+
+/// Crawls the submissions of a user in the contests they may have submitted
+/// to after `after_contest_id`, in the order of contest ids.
+///
+/// Each contest costs at least one rate-limited request, so only the contests
+/// in the user's stored rating history, those they already have submissions
+/// in, and those that are still active (including permanent ones) are
+/// crawled. The rating history of a linked user is stored by the profile
+/// refresh.
+///
+/// `on_contest_done` is called with the id of each contest once it has been
+/// crawled, so that the progress can be saved.
+///
+/// Returns the total number of submissions inserted/updated.
+pub async fn crawl_user_submissions<F, Fut>(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    user_id: &str,
+    after_contest_id: Option<&str>,
+    mut on_contest_done: F,
+) -> Result<usize, CrawlError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<(), DbErr>>,
+{
+    use sql_entities::contests::Column;
+
+    let now = chrono::Utc::now().timestamp();
+    let rated_contest_ids = Query::select()
+        .column(sql_entities::user_rating_history::Column::ContestId)
+        .from(sql_entities::user_rating_history::Entity)
+        .and_where(sql_entities::user_rating_history::Column::UserId.eq(user_id))
+        .to_owned();
+    let submitted_contest_ids = Query::select()
+        .distinct()
+        .column(sql_entities::submissions::Column::ContestId)
+        .from(sql_entities::submissions::Entity)
+        .and_where(sql_entities::submissions::Column::UserId.eq(user_id))
+        .to_owned();
+    let recently_active =
+        Expr::expr(Expr::col(Column::StartEpochSecond).add(Expr::col(Column::DurationSecond)))
+            .gte(now - ACTIVE_CONTEST_WINDOW_SECOND);
+    let mut query = sql_entities::contests::Entity::find()
+        .filter(
+            Condition::any()
+                .add(Column::Id.in_subquery(rated_contest_ids))
+                .add(Column::Id.in_subquery(submitted_contest_ids))
+                .add(recently_active),
+        )
+        .order_by_asc(Column::Id);
+    if let Some(after_contest_id) = after_contest_id {
+        query = query.filter(Column::Id.gt(after_contest_id));
+    }
+    let contests = query.all(db).await?;
+    tracing::info!(
+        "Fetching submissions of {} in {} contests",
+        user_id,
+        contests.len()
+    );
+
+    let mut total_inserted = 0;
+    for contest in contests {
+        for page in 1.. {
+            let submissions = match fetcher
+                .fetch_user_submissions(&contest.id, user_id, page)
+                .await
+            {
+                Ok(submissions) => submissions,
+                Err(CrawlerError::NotFound) => vec![],
+                Err(e) => return Err(e.into()),
+            };
+            if submissions.is_empty() {
+                break;
+            }
+            total_inserted += upsert_submissions(db, submissions).await?;
+        }
+        on_contest_done(contest.id).await?;
+    }
+
+    tracing::info!(
+        "Finished fetching submissions of {}, total inserted: {}",
+        user_id,
+        total_inserted
+    );
+    Ok(total_inserted)
+}
+
+/// Claims the oldest user crawl job that no other worker holds.
+///
+/// Like [`claim_contests`], the job is locked with `FOR UPDATE SKIP LOCKED`
+/// and held until its lease expires.
+pub async fn claim_user_crawl_job(
+    db: &impl CrawlDb,
+    worker_id: &str,
+) -> Result<Option<sql_entities::user_crawl_jobs::Model>, DbErr> {
+    let now = chrono::Utc::now().timestamp();
+    let txn = db.begin().await?;
+    let job = sql_entities::user_crawl_jobs::Entity::find()
+        .filter(sql_entities::user_crawl_jobs::Column::Attempts.lt(MAX_CRAWL_QUEUE_ATTEMPTS))
+        .filter(
+            Condition::any()
+                .add(sql_entities::user_crawl_jobs::Column::ClaimedUntil.is_null())
+                .add(sql_entities::user_crawl_jobs::Column::ClaimedUntil.lte(now)),
+        )
+        .order_by_asc(sql_entities::user_crawl_jobs::Column::RequestedEpochSecond)
+        .order_by_asc(sql_entities::user_crawl_jobs::Column::AtcoderUserId)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .one(&txn)
+        .await?;
+    let Some(mut job) = job else {
+        txn.commit().await?;
+        return Ok(None);
+    };
+
+    let claimed_until = now + CRAWL_QUEUE_LEASE_SECOND;
+    sql_entities::user_crawl_jobs::Entity::update_many()
+        .col_expr(
+            sql_entities::user_crawl_jobs::Column::ClaimedBy,
+            Expr::value(worker_id),
+        )
+        .col_expr(
+            sql_entities::user_crawl_jobs::Column::ClaimedUntil,
+            Expr::value(claimed_until),
+        )
+        .filter(sql_entities::user_crawl_jobs::Column::AtcoderUserId.eq(&job.atcoder_user_id))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+
+    job.claimed_by = Some(worker_id.to_string());
+    job.claimed_until = Some(claimed_until);
+    Ok(Some(job))
+}
+
+/// Runs the queued user crawl jobs until none is left.
+///
+/// A job resumes after the last contest finished by a previous attempt. A job
+/// that fails is released and retried later, up to a few attempts; database
+/// errors abort the run.
+///
+/// Returns the total number of submissions inserted/updated.
+pub async fn run_user_crawl_jobs(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    worker_id: &str,
+) -> Result<usize, CrawlError> {
+    let mut total_inserted = 0;
+    while let Some(job) = claim_user_crawl_job(db, worker_id).await? {
+        let owned = Condition::all()
+            .add(sql_entities::user_crawl_jobs::Column::AtcoderUserId.eq(&job.atcoder_user_id))
+            .add(sql_entities::user_crawl_jobs::Column::ClaimedBy.eq(worker_id));
+        let result = crawl_user_submissions(
+            fetcher,
+            db,
+            &job.atcoder_user_id,
+            job.last_contest_id.as_deref(),
+            |contest_id| {
+                let owned = owned.clone();
+                async move {
+                    sql_entities::user_crawl_jobs::Entity::update_many()
+                        .col_expr(
+                            sql_entities::user_crawl_jobs::Column::LastContestId,
+                            Expr::value(contest_id),
+                        )
+                        .filter(owned)
+                        .exec(db)
+                        .await?;
+                    Ok(())
+                }
+            },
+        )
+        .await;
+
+        match result {
+            Ok(inserted) => {
+                total_inserted += inserted;
+                sql_entities::user_crawl_jobs::Entity::delete_many()
+                    .filter(owned)
+                    .exec(db)
+                    .await?;
+            }
+            Err(CrawlError::Db(e)) => return Err(e.into()),
+            Err(CrawlError::Crawler(e @ CrawlerError::SessionExpired { .. })) => {
+                sql_entities::user_crawl_jobs::Entity::update_many()
+                    .col_expr(
+                        sql_entities::user_crawl_jobs::Column::ClaimedBy,
+                        Expr::value(Option::<String>::None),
+                    )
+                    .col_expr(
+                        sql_entities::user_crawl_jobs::Column::ClaimedUntil,
+                        Expr::value(Option::<i64>::None),
+                    )
+                    .filter(owned)
+                    .exec(db)
+                    .await?;
+                return Err(e.into());
+            }
+            Err(CrawlError::Crawler(e)) => {
+                tracing::error!(
+                    "Failed to crawl submissions of {} (attempt {}): {}",
+                    job.atcoder_user_id,
+                    job.attempts + 1,
+                    e
+                );
+                sql_entities::user_crawl_jobs::Entity::update_many()
+                    .col_expr(
+                        sql_entities::user_crawl_jobs::Column::Attempts,
+                        Expr::col(sql_entities::user_crawl_jobs::Column::Attempts).add(1),
+                    )
+                    .col_expr(
+                        sql_entities::user_crawl_jobs::Column::ClaimedBy,
+                        Expr::value(Option::<String>::None),
+                    )
+                    .col_expr(
+                        sql_entities::user_crawl_jobs::Column::ClaimedUntil,
+                        Expr::value(Option::<i64>::None),
+                    )
+                    .filter(owned)
+                    .exec(db)
+                    .await?;
+            }
+        }
+    }
+    Ok(total_inserted)
+}
+
+/// Outcome of crawling the detail page of a submission, in
+/// `submission_details.status`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DetailStatus {
+    Crawled,
+    Failed,
+}
+
+impl DetailStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DetailStatus::Crawled => "crawled",
+            DetailStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Crawls the detail pages of up to `limit` submissions and stores their judge
+/// case results, memory usage and compile error flag.
+///
+/// Submissions are the most recent accepted ones on the most solved problems
+/// whose detail page was not crawled yet. Every submission picked gets a row in
+/// `submission_details`, including the ones whose page has no judge cases or
+/// cannot be fetched (`failed`), so that they are not picked again and do not
+/// crowd out newer submissions.
+///
+/// Returns the number of submissions whose detail page was stored.
+pub async fn crawl_submission_details(
+    fetcher: &dyn SubmissionFetcher,
+    db: &impl CrawlDb,
+    limit: u64,
+) -> Result<usize, CrawlError> {
+    let popular_problem_ids = sql_entities::solver::Entity::find()
+        .order_by_desc(sql_entities::solver::Column::UserCount)
+        .limit(DETAIL_POPULAR_PROBLEM_COUNT)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|solver| solver.problem_id);
+    let crawled_submission_ids = Query::select()
+        .column(sql_entities::submission_details::Column::SubmissionId)
+        .from(sql_entities::submission_details::Entity)
+        .to_owned();
+    // Judge cases stored before `submission_details` existed
+    let submission_ids_with_judge_cases = Query::select()
+        .column(sql_entities::submission_judge_cases::Column::SubmissionId)
+        .from(sql_entities::submission_judge_cases::Entity)
+        .to_owned();
+    let submissions = sql_entities::submissions::Entity::find()
+        .filter(sql_entities::submissions::Column::Result.eq("AC"))
+        .filter(sql_entities::submissions::Column::ProblemId.is_in(popular_problem_ids))
+        .filter(sql_entities::submissions::Column::Id.not_in_subquery(crawled_submission_ids))
+        .filter(
+            sql_entities::submissions::Column::Id.not_in_subquery(submission_ids_with_judge_cases),
+        )
+        .order_by_desc(sql_entities::submissions::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    tracing::info!(
+        "Found {} submissions without judge cases",
+        submissions.len()
+    );
+
+    let mut total_crawled = 0;
+    for submission in submissions {
+        let detail = match fetcher
+            .fetch_submission_detail(&submission.contest_id, submission.id)
+            .await
+        {
+            Ok(detail) => detail,
+            Err(e @ CrawlerError::SessionExpired { .. }) => return Err(e.into()),
+            Err(e) => {
+                tracing::warn!("Failed to fetch submission {}: {}", submission.id, e);
+                save_detail_status(db, submission.id, DetailStatus::Failed, None, false).await?;
+                continue;
+            }
         };
-        sql_entities::submissions::Entity::insert(submission)
+        if detail.judge_cases.is_empty() && !detail.compile_error {
+            tracing::warn!("No judge cases found for submission {}", submission.id);
+        }
+
+        save_submission_detail(db, detail).await?;
+        total_crawled += 1;
+    }
+
+    tracing::info!(
+        "Finished crawling submission details, total crawled: {}",
+        total_crawled
+    );
+    Ok(total_crawled)
+}
+
+/// Stores the judge cases of a detail page together with its
+/// `submission_details` row.
+async fn save_submission_detail(db: &impl CrawlDb, detail: SubmissionDetail) -> Result<(), DbErr> {
+    let submission_id = detail.submission_id;
+    let judge_case_count = detail.judge_cases.len();
+    let models: Vec<_> = detail
+        .judge_cases
+        .into_iter()
+        .map(
+            |judge_case| sql_entities::submission_judge_cases::ActiveModel {
+                submission_id: Set(submission_id),
+                case_name: Set(judge_case.case_name),
+                verdict: Set(judge_case.verdict),
+                execution_time: Set(judge_case.time_ms),
+                memory: Set(judge_case.memory_kb),
+            },
+        )
+        .collect();
+    if skip_write("submission_judge_cases", judge_case_count) {
+        return save_detail_status(
+            db,
+            submission_id,
+            DetailStatus::Crawled,
+            detail.memory_kb,
+            detail.compile_error,
+        )
+        .await;
+    }
+
+    let txn = db.begin().await?;
+    if !models.is_empty() {
+        sql_entities::submission_judge_cases::Entity::insert_many(models)
             .on_conflict(
-                OnConflict::column(sql_entities::submissions::Column::Id)
-                    .update_columns([
-                        sql_entities::submissions::Column::EpochSecond,
-                        sql_entities::submissions::Column::ProblemId,
-                        sql_entities::submissions::Column::ContestId,
-                        sql_entities::submissions::Column::UserId,
-                        sql_entities::submissions::Column::Language,
-                        sql_entities::submissions::Column::Point,
-                        sql_entities::submissions::Column::Length,
-                        sql_entities::submissions::Column::Result,
-                        sql_entities::submissions::Column::ExecutionTime,
-                    ])
-                    .to_owned(),
+                OnConflict::columns([
+                    sql_entities::submission_judge_cases::Column::SubmissionId,
+                    sql_entities::submission_judge_cases::Column::CaseName,
+                ])
+                .update_columns([
+                    sql_entities::submission_judge_cases::Column::Verdict,
+                    sql_entities::submission_judge_cases::Column::ExecutionTime,
+                    sql_entities::submission_judge_cases::Column::Memory,
+                ])
+                .to_owned(),
             )
-            .exec(db)
+            .exec(&txn)
             .await?;
-        inserted_submissions += 1;
     }
-    Ok(inserted_submissions)
+    save_detail_status(
+        &txn,
+        submission_id,
+        DetailStatus::Crawled,
+        detail.memory_kb,
+        detail.compile_error,
+    )
+    .await?;
+    txn.commit().await?;
+    crawl_runs::record_rows(judge_case_count + 1, 0);
+    Ok(())
+}
+
+async fn save_detail_status(
+    db: &impl ConnectionTrait,
+    submission_id: i64,
+    status: DetailStatus,
+    memory_kb: Option<i32>,
+    compile_error: bool,
+) -> Result<(), DbErr> {
+    if skip_write("submission_details", 1) {
+        return Ok(());
+    }
+    sql_entities::submission_details::Entity::insert(
+        sql_entities::submission_details::ActiveModel {
+            submission_id: Set(submission_id),
+            status: Set(status.as_str().to_string()),
+            memory_kb: Set(memory_kb),
+            compile_error: Set(compile_error),
+            crawled_epoch_second: Set(chrono::Utc::now().timestamp()),
+        },
+    )
+    .on_conflict(
+        OnConflict::column(sql_entities::submission_details::Column::SubmissionId)
+            .update_columns([
+                sql_entities::submission_details::Column::Status,
+                sql_entities::submission_details::Column::MemoryKb,
+                sql_entities::submission_details::Column::CompileError,
+                sql_entities::submission_details::Column::CrawledEpochSecond,
+            ])
+            .to_owned(),
+    )
+    .exec(db)
+    .await?;
+    Ok(())
 }
 
 /// Crawls problems for all contests that don't have problems yet.
@@ -139,11 +1481,14 @@ pub async fn upsert_submissions(
 /// 4. Crawls problems for each contest
 /// 5. Upserts the problems into the database
 ///
+/// A contest whose tasks page cannot be fetched is logged and skipped, since it
+/// still has no problems and will be picked up again by the next run.
+///
 /// Returns the total number of problems inserted/updated.
 pub async fn crawl_problems(
     fetcher: &dyn ProblemFetcher,
-    db: &DatabaseConnection,
-) -> Result<usize, DbErr> {
+    db: &impl CrawlDb,
+) -> Result<usize, CrawlError> {
     // Build a map of contest_id -> problem_ids from contest_problem table
     let contest_problems: HashMap<String, Vec<String>> =
         sql_entities::contest_problem::Entity::find()
@@ -170,129 +1515,502 @@ pub async fn crawl_problems(
         contests_without_problems.len()
     );
 
-    let mut total_inserted = 0;
+    let mut total_inserted = 0;
+
+    for contest_id in contests_without_problems {
+        tracing::info!("Fetching problems for contest {}", contest_id);
+
+        let problems = match fetcher.fetch_problems(&contest_id).await {
+            Ok(problems) => problems,
+            Err(CrawlerError::NotFound) => {
+                tracing::warn!(
+                    "Tasks page not found for contest {} (404), skipping",
+                    contest_id
+                );
+                continue;
+            }
+            Err(e @ CrawlerError::SessionExpired { .. }) => return Err(e.into()),
+            Err(e) => {
+                tracing::error!("Failed to fetch problems for contest {}: {}", contest_id, e);
+                continue;
+            }
+        };
+
+        if problems.is_empty() {
+            tracing::warn!("No problems found for contest {}", contest_id);
+            continue;
+        }
+
+        let mut problems = problems;
+        for problem in &mut problems {
+            problem.point = fetch_problem_point(fetcher, problem).await?;
+        }
+
+        tracing::info!(
+            "Inserting {} problems for contest {}",
+            problems.len(),
+            contest_id
+        );
+
+        let inserted = upsert_problems(db, problems).await?;
+        tracing::info!("Inserted {} problems for contest {}", inserted, contest_id);
+        total_inserted += inserted;
+    }
+
+    tracing::info!(
+        "Finished crawling problems, total inserted: {}",
+        total_inserted
+    );
+
+    Ok(total_inserted)
+}
+
+/// Fetches the point value of a problem from its task page.
+///
+/// The point value is optional metadata, so a failure is logged and the
+/// problem is stored without it rather than skipping the whole contest; a
+/// point stored before is kept. Only an
+/// expired session is returned, since every following page would fail too.
+async fn fetch_problem_point(
+    fetcher: &dyn ProblemFetcher,
+    problem: &Problem,
+) -> Result<Option<f64>, CrawlerError> {
+    match fetcher
+        .fetch_problem_point(&problem.contest_id, &problem.id)
+        .await
+    {
+        Ok(point) => Ok(point),
+        Err(e @ CrawlerError::SessionExpired { .. }) => Err(e),
+        Err(e) => {
+            tracing::warn!("Failed to fetch point of problem {}: {}", problem.id, e);
+            Ok(None)
+        }
+    }
+}
+
+async fn upsert_problems(db: &impl CrawlDb, new_problems: Vec<Problem>) -> Result<usize, DbErr> {
+    let stored = new_problems.len();
+    let existing_ids: HashSet<String> = sql_entities::problems::Entity::find()
+        .select_only()
+        .column(sql_entities::problems::Column::Id)
+        .filter(sql_entities::problems::Column::Id.is_in(new_problems.iter().map(|p| &p.id)))
+        .into_tuple()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let contest_problems: Vec<_> =
+        dedupe_by_key(&new_problems, |p| (p.contest_id.clone(), p.id.clone()))
+            .into_values()
+            .map(contest_problem_model)
+            .collect();
+    let problems = dedupe_by_key(new_problems, |p| p.id.clone());
+    let new_rows = problems
+        .keys()
+        .filter(|id| !existing_ids.contains(*id))
+        .count();
+    let problems = problems
+        .into_values()
+        .map(|problem| {
+            let title = problem.title();
+            sql_entities::problems::ActiveModel {
+                id: Set(problem.id),
+                contest_id: Set(problem.contest_id),
+                problem_index: Set(problem.problem_index),
+                name: Set(problem.name),
+                title: Set(title),
+                time_limit_ms: Set(problem.time_limit_ms),
+                memory_limit_kb: Set(problem.memory_limit_kb),
+                point: Set(problem.point),
+                orphaned_epoch_second: Set(None),
+            }
+        })
+        .collect();
+
+    let txn = db.begin().await?;
+    let written = upsert_chunked(
+        &txn,
+        problems,
+        // The point is left empty when the task page fails, which must not
+        // erase the one crawled before.
+        upsert_if_changed_keeping_known(
+            [sql_entities::problems::Column::Id],
+            [
+                sql_entities::problems::Column::ContestId,
+                sql_entities::problems::Column::ProblemIndex,
+                sql_entities::problems::Column::Name,
+                sql_entities::problems::Column::Title,
+                sql_entities::problems::Column::TimeLimitMs,
+                sql_entities::problems::Column::MemoryLimitKb,
+                sql_entities::problems::Column::OrphanedEpochSecond,
+            ],
+            [sql_entities::problems::Column::Point],
+        ),
+    )
+    .await?;
+    upsert_chunked(
+        &txn,
+        contest_problems,
+        upsert_if_changed(
+            [
+                sql_entities::contest_problem::Column::ContestId,
+                sql_entities::contest_problem::Column::ProblemId,
+            ],
+            [sql_entities::contest_problem::Column::ProblemIndex],
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    crawl_runs::record_rows(new_rows, written.saturating_sub(new_rows));
+    Ok(stored)
+}
+
+fn contest_problem_model(problem: &Problem) -> sql_entities::contest_problem::ActiveModel {
+    sql_entities::contest_problem::ActiveModel {
+        contest_id: Set(problem.contest_id.clone()),
+        problem_id: Set(problem.id.clone()),
+        problem_index: Set(problem.problem_index.clone()),
+    }
+}
+
+/// Records that the problem appears in its contest at its index.
+async fn upsert_contest_problem(db: &impl CrawlDb, problem: &Problem) -> Result<(), DbErr> {
+    if skip_write("contest_problem", 1) {
+        return Ok(());
+    }
+    sql_entities::contest_problem::Entity::insert(contest_problem_model(problem))
+        .on_conflict(
+            OnConflict::columns([
+                sql_entities::contest_problem::Column::ContestId,
+                sql_entities::contest_problem::Column::ProblemId,
+            ])
+            .update_column(sql_entities::contest_problem::Column::ProblemIndex)
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Kind of record a [`Change`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeEntity {
+    Contest,
+    Problem,
+}
 
-    for contest_id in contests_without_problems {
-        tracing::info!("Fetching problems for contest {}", contest_id);
+impl ChangeEntity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeEntity::Contest => "contest",
+            ChangeEntity::Problem => "problem",
+        }
+    }
+}
 
-        let problems = fetch_problems_with_retry(fetcher, &contest_id).await;
+/// What a reconciling crawl found different from the stored records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    /// The name of a problem or the title of a contest changed.
+    Renamed {
+        old: String,
+        new: String,
+    },
+    Removed,
+    /// The index of a problem in the contest changed.
+    Reindexed {
+        old: String,
+        new: String,
+    },
+    /// The problem no longer belongs to any contest.
+    Orphaned,
+}
 
-        if problems.is_empty() {
-            tracing::warn!("No problems found for contest {}", contest_id);
-            continue;
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Renamed { .. } => "renamed",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Reindexed { .. } => "reindexed",
+            ChangeKind::Orphaned => "orphaned",
         }
+    }
+}
 
-        tracing::info!(
-            "Inserting {} problems for contest {}",
-            problems.len(),
-            contest_id
-        );
-
-        let inserted = upsert_problems(db, problems).await?;
-        tracing::info!("Inserted {} problems for contest {}", inserted, contest_id);
-        total_inserted += inserted;
+/// An entry of the change log of a reconciling crawl.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub entity: ChangeEntity,
+    pub id: String,
+    /// Contest the change was found in, which is the contest itself for
+    /// contest changes.
+    pub contest_id: String,
+    pub kind: ChangeKind,
+}
 
-        // Rate limiting
-        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+impl Change {
+    fn new(entity: ChangeEntity, id: &str, contest_id: &str, kind: ChangeKind) -> Self {
+        Self {
+            entity,
+            id: id.to_string(),
+            contest_id: contest_id.to_string(),
+            kind,
+        }
     }
 
-    tracing::info!(
-        "Finished crawling problems, total inserted: {}",
-        total_inserted
-    );
-
-    Ok(total_inserted)
+    /// Writes the change to the log as structured fields.
+    fn log(&self) {
+        let (old, new) = match &self.kind {
+            ChangeKind::Renamed { old, new } | ChangeKind::Reindexed { old, new } => {
+                (Some(old.as_str()), Some(new.as_str()))
+            }
+            _ => (None, None),
+        };
+        tracing::info!(
+            change = self.kind.as_str(),
+            entity = self.entity.as_str(),
+            id = %self.id,
+            contest_id = %self.contest_id,
+            old,
+            new,
+            "{} {} {}",
+            self.entity.as_str(),
+            self.id,
+            self.kind.as_str()
+        );
+    }
 }
 
-async fn fetch_problems_with_retry(fetcher: &dyn ProblemFetcher, contest_id: &str) -> Vec<Problem> {
-    const MAX_RETRIES: u32 = 10;
-    let mut retry_count = 0;
-    let mut retry_delay = 2000;
+/// Re-crawls the task lists of contests that already have problems and applies
+/// what changed on AtCoder since they were crawled.
+///
+/// `sample` limits the run to that many randomly chosen contests, and `None`
+/// re-crawls all of them. Problems missing from a task list are detached from
+/// the contest, and every problem left without a contest is flagged in
+/// `orphaned_epoch_second`. A contest whose tasks page cannot be fetched or
+/// lists nothing, including one that is gone, is skipped.
+///
+/// Returns the change log, which is also written to the tracing log.
+pub async fn reconcile_problems(
+    fetcher: &dyn ProblemFetcher,
+    db: &impl CrawlDb,
+    sample: Option<usize>,
+) -> Result<Vec<Change>, CrawlError> {
+    let mut contest_problems: HashMap<String, Vec<sql_entities::contest_problem::Model>> =
+        sql_entities::contest_problem::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .fold(HashMap::new(), |mut map, cp| {
+                map.entry(cp.contest_id.clone()).or_default().push(cp);
+                map
+            });
+    let problems: HashMap<String, sql_entities::problems::Model> =
+        sql_entities::problems::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|problem| (problem.id.clone(), problem))
+            .collect();
+
+    let mut contest_ids: Vec<String> = contest_problems.keys().cloned().collect();
+    contest_ids.sort();
+    if let Some(sample) = sample {
+        contest_ids.shuffle(&mut rand::rng());
+        contest_ids.truncate(sample);
+    }
+    tracing::info!("Reconciling problems of {} contests", contest_ids.len());
 
-    while retry_count < MAX_RETRIES {
-        match fetcher.fetch_problems(contest_id).await {
-            Ok(problems) => {
-                return problems;
+    let mut changes = Vec::new();
+    for contest_id in contest_ids {
+        let fetched = match fetcher.fetch_problems(&contest_id).await {
+            Ok(fetched) if fetched.is_empty() => {
+                tracing::warn!("No problems found for contest {}, skipping", contest_id);
+                continue;
             }
+            Ok(fetched) => fetched,
             Err(CrawlerError::NotFound) => {
                 tracing::warn!(
-                    "Tasks page not found for contest {} (404), skipping",
+                    "Tasks page of contest {} is gone (404), skipping",
                     contest_id
                 );
-                return vec![];
+                continue;
             }
+            Err(e @ CrawlerError::SessionExpired { .. }) => return Err(e.into()),
             Err(e) => {
-                retry_count += 1;
-                tracing::warn!(
-                    "Failed to fetch problems (attempt {}/{}): {}",
-                    retry_count,
-                    MAX_RETRIES,
-                    e
-                );
-                tokio::time::sleep(std::time::Duration::from_millis(retry_delay)).await;
-                retry_delay *= 2;
+                tracing::error!("Failed to fetch problems for contest {}: {}", contest_id, e);
+                continue;
+            }
+        };
+        let stored = contest_problems.remove(&contest_id).unwrap_or_default();
+        changes.extend(
+            reconcile_contest_problems(fetcher, db, &contest_id, &stored, fetched, &problems)
+                .await?,
+        );
+    }
+    changes.extend(flag_orphaned_problems(db).await?);
+
+    for change in &changes {
+        change.log();
+    }
+    tracing::info!("Finished reconciling problems, {} changes", changes.len());
+    Ok(changes)
+}
+
+/// Applies the difference between the stored and the fetched task list of a
+/// contest.
+///
+/// The problem record itself is only rewritten for new problems and for the
+/// ones belonging to this contest, so that a problem shared with an earlier
+/// contest keeps its original contest and index.
+async fn reconcile_contest_problems(
+    fetcher: &dyn ProblemFetcher,
+    db: &impl CrawlDb,
+    contest_id: &str,
+    stored: &[sql_entities::contest_problem::Model],
+    fetched: Vec<Problem>,
+    problems: &HashMap<String, sql_entities::problems::Model>,
+) -> Result<Vec<Change>, CrawlError> {
+    let fetched_ids: HashSet<String> = fetched.iter().map(|problem| problem.id.clone()).collect();
+    let mut changes = Vec::new();
+    for mut problem in fetched {
+        let existing = problems.get(&problem.id);
+        let mut changed = false;
+        match stored.iter().find(|cp| cp.problem_id == problem.id) {
+            None => {
+                changes.push(Change::new(
+                    ChangeEntity::Problem,
+                    &problem.id,
+                    contest_id,
+                    ChangeKind::Added,
+                ));
+                changed = true;
+            }
+            Some(cp) => {
+                if cp.problem_index != problem.problem_index {
+                    changes.push(Change::new(
+                        ChangeEntity::Problem,
+                        &problem.id,
+                        contest_id,
+                        ChangeKind::Reindexed {
+                            old: cp.problem_index.clone(),
+                            new: problem.problem_index.clone(),
+                        },
+                    ));
+                    changed = true;
+                }
+                if let Some(existing) = existing.filter(|existing| existing.name != problem.name) {
+                    changes.push(Change::new(
+                        ChangeEntity::Problem,
+                        &problem.id,
+                        contest_id,
+                        ChangeKind::Renamed {
+                            old: existing.name.clone(),
+                            new: problem.name.clone(),
+                        },
+                    ));
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            continue;
+        }
+
+        match existing {
+            Some(existing) if existing.contest_id != contest_id => {
+                upsert_contest_problem(db, &problem).await?;
+            }
+            Some(existing) => {
+                problem.point = existing.point;
+                upsert_problems(db, vec![problem]).await?;
+            }
+            None => {
+                problem.point = fetch_problem_point(fetcher, &problem).await?;
+                upsert_problems(db, vec![problem]).await?;
             }
         }
     }
 
-    tracing::error!("Failed to fetch problems after {} retries", MAX_RETRIES);
-    vec![]
+    for cp in stored
+        .iter()
+        .filter(|cp| !fetched_ids.contains(cp.problem_id.as_str()))
+    {
+        if !skip_write("contest_problem", 1) {
+            sql_entities::contest_problem::Entity::delete_many()
+                .filter(sql_entities::contest_problem::Column::ContestId.eq(contest_id))
+                .filter(sql_entities::contest_problem::Column::ProblemId.eq(&cp.problem_id))
+                .exec(db)
+                .await?;
+        }
+        changes.push(Change::new(
+            ChangeEntity::Problem,
+            &cp.problem_id,
+            contest_id,
+            ChangeKind::Removed,
+        ));
+    }
+    Ok(changes)
 }
 
-async fn upsert_problems(
-    db: &DatabaseConnection,
-    new_problems: Vec<Problem>,
-) -> Result<usize, DbErr> {
-    for problem in &new_problems {
-        // Insert into problems table
-        let title = problem.title();
-        let model = sql_entities::problems::ActiveModel {
-            id: Set(problem.id.clone()),
-            contest_id: Set(problem.contest_id.clone()),
-            problem_index: Set(problem.problem_index.clone()),
-            name: Set(problem.name.clone()),
-            title: Set(title),
-        };
-        sql_entities::problems::Entity::insert(model)
-            .on_conflict(
-                OnConflict::column(sql_entities::problems::Column::Id)
-                    .update_columns([
-                        sql_entities::problems::Column::ContestId,
-                        sql_entities::problems::Column::ProblemIndex,
-                        sql_entities::problems::Column::Name,
-                        sql_entities::problems::Column::Title,
-                    ])
-                    .to_owned(),
+/// Flags the problems that belong to no contest and returns them as
+/// [`ChangeKind::Orphaned`] changes.
+///
+/// Problems flagged before keep their flag and are not reported again, while
+/// the ones that are back in a task list are unflagged.
+async fn flag_orphaned_problems(db: &impl CrawlDb) -> Result<Vec<Change>, DbErr> {
+    let listed_problem_ids = Query::select()
+        .column(sql_entities::contest_problem::Column::ProblemId)
+        .from(sql_entities::contest_problem::Entity)
+        .to_owned();
+    if !is_dry_run() {
+        sql_entities::problems::Entity::update_many()
+            .col_expr(
+                sql_entities::problems::Column::OrphanedEpochSecond,
+                Expr::value(Option::<i64>::None),
             )
+            .filter(sql_entities::problems::Column::OrphanedEpochSecond.is_not_null())
+            .filter(sql_entities::problems::Column::Id.in_subquery(listed_problem_ids.clone()))
             .exec(db)
             .await?;
+    }
 
-        // Insert into contest_problem table
-        let contest_problem = sql_entities::contest_problem::ActiveModel {
-            contest_id: Set(problem.contest_id.clone()),
-            problem_id: Set(problem.id.clone()),
-            problem_index: Set(problem.problem_index.clone()),
-        };
-        if let Err(e) = sql_entities::contest_problem::Entity::insert(contest_problem)
-            .on_conflict(
-                OnConflict::columns([
-                    sql_entities::contest_problem::Column::ContestId,
-                    sql_entities::contest_problem::Column::ProblemId,
-                ])
-                .update_column(sql_entities::contest_problem::Column::ProblemIndex)
-                .to_owned(),
+    let orphaned = Condition::all()
+        .add(sql_entities::problems::Column::OrphanedEpochSecond.is_null())
+        .add(sql_entities::problems::Column::Id.not_in_subquery(listed_problem_ids));
+    let problems = sql_entities::problems::Entity::find()
+        .filter(orphaned.clone())
+        .all(db)
+        .await?;
+    if problems.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if !skip_write("problems", problems.len()) {
+        sql_entities::problems::Entity::update_many()
+            .col_expr(
+                sql_entities::problems::Column::OrphanedEpochSecond,
+                Expr::value(chrono::Utc::now().timestamp()),
             )
+            .filter(orphaned)
             .exec(db)
-            .await
-        {
-            tracing::warn!(
-                "Failed to insert contest_problem for problem {}: {}",
-                problem.id,
-                e
-            );
-        }
+            .await?;
     }
-    Ok(new_problems.len())
+    Ok(problems
+        .into_iter()
+        .map(|problem| {
+            Change::new(
+                ChangeEntity::Problem,
+                &problem.id,
+                &problem.contest_id,
+                ChangeKind::Orphaned,
+            )
+        })
+        .collect())
 }
 
 /// Crawls contests from AtCoder and upserts them into the database.
@@ -304,16 +2022,58 @@ async fn upsert_problems(
 ///    from their category archives
 /// 4. Upserts all contests into the database
 ///
+/// A page that cannot be fetched aborts the crawl rather than being taken as the
+/// end of the archive.
+///
 /// Returns the total number of contests inserted/updated.
 pub async fn crawl_contests(
     fetcher: &dyn ContestFetcher,
-    db: &DatabaseConnection,
-) -> Result<usize, DbErr> {
+    db: &impl CrawlDb,
+) -> Result<usize, CrawlError> {
+    let all_contests = fetch_all_contests(fetcher).await?;
+
+    // Upsert contests
+    let inserted = upsert_contests(db, all_contests).await?;
+    tracing::info!("Finished crawling contests, total inserted: {}", inserted);
+
+    Ok(inserted)
+}
+
+/// Fills `rated_lower`, `rated_upper` and `contest_type` of the contests stored
+/// before the crawler parsed them, from their stored `rate_change`.
+///
+/// Such contests have the column defaults and would count as unrated until
+/// they are crawled again. Returns the number of contests updated.
+pub async fn backfill_contest_types(db: &impl CrawlDb) -> Result<usize, DbErr> {
+    let contests = sql_entities::contests::Entity::find()
+        .filter(sql_entities::contests::Column::ContestType.eq(ContestType::Other.as_str()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|contest| Contest {
+            id: contest.id,
+            start_epoch_second: contest.start_epoch_second,
+            duration_second: contest.duration_second,
+            title: contest.title,
+            rate_change: contest.rate_change,
+        })
+        .filter(|contest| contest.contest_type() != ContestType::Other)
+        .collect::<Vec<_>>();
+    if contests.is_empty() {
+        return Ok(0);
+    }
+    let updated = upsert_contests(db, contests).await?;
+    tracing::info!("Filled the rated range and type of {} contests", updated);
+    Ok(updated)
+}
+
+/// Fetches the permanent contests and every page of the contest archives.
+async fn fetch_all_contests(fetcher: &dyn ContestFetcher) -> Result<Vec<Contest>, CrawlError> {
     let mut all_contests: Vec<Contest> = Vec::new();
 
     // Fetch permanent contests
     tracing::info!("Fetching permanent contests...");
-    let permanent_contests = fetch_permanent_contests_with_retry(fetcher).await;
+    let permanent_contests = fetcher.fetch_permanent_contests().await?;
     tracing::info!("Fetched {} permanent contests", permanent_contests.len());
     all_contests.extend(permanent_contests);
 
@@ -322,7 +2082,7 @@ pub async fn crawl_contests(
     let mut page = 1;
     loop {
         tracing::info!("Fetching contests from archive page {}...", page);
-        let contests = fetch_contests_with_retry(fetcher, page).await;
+        let contests = fetcher.fetch_contests(page).await?;
 
         if contests.is_empty() {
             tracing::info!("No more contests found on page {}", page);
@@ -332,9 +2092,6 @@ pub async fn crawl_contests(
         tracing::info!("Fetched {} contests from page {}", contests.len(), page);
         all_contests.extend(contests);
         page += 1;
-
-        // Rate limiting
-        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
     }
 
     // AtCoder excludes some contest categories (e.g. Weekday Contests and
@@ -344,7 +2101,7 @@ pub async fn crawl_contests(
         let mut page = 1;
         loop {
             tracing::info!("Fetching {} from archive page {}...", label, page);
-            let contests = fetch_contests_in_category_with_retry(fetcher, page, category).await;
+            let contests = fetcher.fetch_contests_in_category(page, category).await?;
 
             if contests.is_empty() {
                 tracing::info!("No more {} found on page {}", label, page);
@@ -354,145 +2111,528 @@ pub async fn crawl_contests(
             tracing::info!("Fetched {} {} from page {}", contests.len(), label, page);
             all_contests.extend(contests);
             page += 1;
-
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
         }
     }
 
     tracing::info!("Total contests fetched: {}", all_contests.len());
+    Ok(all_contests)
+}
 
-    // Upsert contests
-    let inserted = upsert_contests(db, all_contests).await?;
-    tracing::info!("Finished crawling contests, total inserted: {}", inserted);
+async fn upsert_contests(db: &impl CrawlDb, contests: Vec<Contest>) -> Result<usize, DbErr> {
+    let stored = contests.len();
+    let existing_ids: HashSet<String> = sql_entities::contests::Entity::find()
+        .select_only()
+        .column(sql_entities::contests::Column::Id)
+        .filter(sql_entities::contests::Column::Id.is_in(contests.iter().map(|c| &c.id)))
+        .into_tuple()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
 
-    Ok(inserted)
+    for contest in &contests {
+        if contest.rated_range().is_none() && !RatedRange::is_unrated_label(&contest.rate_change) {
+            tracing::warn!(
+                "Contest {} has an unknown rated range {:?} and counts as unrated",
+                contest.id,
+                contest.rate_change
+            );
+        }
+    }
+    let contests = dedupe_by_key(contests, |c| c.id.clone());
+    let new_rows = contests
+        .keys()
+        .filter(|id| !existing_ids.contains(*id))
+        .count();
+    let models = contests
+        .into_values()
+        .map(|contest| {
+            let rated_range = contest.rated_range();
+            let contest_type = contest.contest_type().as_str().to_string();
+            sql_entities::contests::ActiveModel {
+                id: Set(contest.id),
+                start_epoch_second: Set(contest.start_epoch_second),
+                duration_second: Set(contest.duration_second),
+                title: Set(contest.title),
+                rate_change: Set(contest.rate_change),
+                rated_lower: Set(rated_range.map(|range| range.lower)),
+                rated_upper: Set(rated_range.and_then(|range| range.upper)),
+                contest_type: Set(contest_type),
+            }
+        })
+        .collect();
+
+    let txn = db.begin().await?;
+    let written = upsert_chunked(
+        &txn,
+        models,
+        upsert_if_changed(
+            [sql_entities::contests::Column::Id],
+            [
+                sql_entities::contests::Column::StartEpochSecond,
+                sql_entities::contests::Column::DurationSecond,
+                sql_entities::contests::Column::Title,
+                sql_entities::contests::Column::RateChange,
+                sql_entities::contests::Column::RatedLower,
+                sql_entities::contests::Column::RatedUpper,
+                sql_entities::contests::Column::ContestType,
+            ],
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    crawl_runs::record_rows(new_rows, written.saturating_sub(new_rows));
+    Ok(stored)
 }
 
-async fn fetch_permanent_contests_with_retry(fetcher: &dyn ContestFetcher) -> Vec<Contest> {
-    const MAX_RETRIES: u32 = 10;
-    let mut retry_count = 0;
-    let mut retry_delay = 2000;
+/// Re-crawls the contest archives and applies what changed on AtCoder since
+/// the contests were crawled.
+///
+/// Contests missing from the archives are only reported unless `prune` is
+/// set, in which case they are deleted together with their task lists and
+/// problems left without a contest are flagged like in [`reconcile_problems`].
+/// Nothing is deleted when too many contests are missing, since one archive
+/// page that failed ends the pagination early.
+///
+/// Returns the change log, which is also written to the tracing log.
+pub async fn reconcile_contests(
+    fetcher: &dyn ContestFetcher,
+    db: &impl CrawlDb,
+    prune: bool,
+) -> Result<Vec<Change>, CrawlError> {
+    let fetched = fetch_all_contests(fetcher).await?;
+    let stored: HashMap<String, sql_entities::contests::Model> =
+        sql_entities::contests::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|contest| (contest.id.clone(), contest))
+            .collect();
 
-    while retry_count < MAX_RETRIES {
-        match fetcher.fetch_permanent_contests().await {
-            Ok(contests) => {
-                return contests;
-            }
-            Err(e) => {
-                retry_count += 1;
-                tracing::warn!(
-                    "Failed to fetch permanent contests (attempt {}/{}): {}",
-                    retry_count,
-                    MAX_RETRIES,
-                    e
-                );
-                tokio::time::sleep(std::time::Duration::from_millis(retry_delay)).await;
-                retry_delay *= 2;
-            }
+    let mut changes = Vec::new();
+    let mut fetched_ids = HashSet::new();
+    for contest in &fetched {
+        if !fetched_ids.insert(contest.id.as_str()) {
+            continue;
+        }
+        match stored.get(&contest.id) {
+            None => changes.push(Change::new(
+                ChangeEntity::Contest,
+                &contest.id,
+                &contest.id,
+                ChangeKind::Added,
+            )),
+            Some(existing) if existing.title != contest.title => changes.push(Change::new(
+                ChangeEntity::Contest,
+                &contest.id,
+                &contest.id,
+                ChangeKind::Renamed {
+                    old: existing.title.clone(),
+                    new: contest.title.clone(),
+                },
+            )),
+            Some(_) => {}
         }
     }
 
-    tracing::error!(
-        "Failed to fetch permanent contests after {} retries",
-        MAX_RETRIES
+    let mut removed: Vec<String> = stored
+        .keys()
+        .filter(|id| !fetched_ids.contains(id.as_str()))
+        .cloned()
+        .collect();
+    removed.sort();
+    if !removed.is_empty() {
+        if !prune {
+            tracing::warn!(
+                "{} contests are missing from the archives, pass --prune to delete them",
+                removed.len()
+            );
+        } else if removed.len() > MAX_PRUNED_CONTESTS || removed.len() * 10 > stored.len() {
+            tracing::warn!(
+                "{} of {} contests are missing from the archives, keeping the stored contests",
+                removed.len(),
+                stored.len()
+            );
+        } else if !skip_write("contests", removed.len()) {
+            sql_entities::contest_problem::Entity::delete_many()
+                .filter(sql_entities::contest_problem::Column::ContestId.is_in(removed.clone()))
+                .exec(db)
+                .await?;
+            sql_entities::contests::Entity::delete_many()
+                .filter(sql_entities::contests::Column::Id.is_in(removed.clone()))
+                .exec(db)
+                .await?;
+        }
+    }
+    changes.extend(
+        removed
+            .iter()
+            .map(|id| Change::new(ChangeEntity::Contest, id, id, ChangeKind::Removed)),
     );
-    vec![]
+
+    upsert_contests(db, fetched).await?;
+    changes.extend(flag_orphaned_problems(db).await?);
+
+    for change in &changes {
+        change.log();
+    }
+    tracing::info!("Finished reconciling contests, {} changes", changes.len());
+    Ok(changes)
 }
 
-async fn fetch_contests_with_retry(fetcher: &dyn ContestFetcher, page: u32) -> Vec<Contest> {
-    const MAX_RETRIES: u32 = 10;
-    let mut retry_count = 0;
-    let mut retry_delay = 2000;
+/// Returns up to `limit` users whose profiles should be refreshed, never
+/// crawled and least recently crawled first.
+///
+/// These are the users who submitted recently and the ones linked to an
+/// internal account, except those whose profile was crawled within the last
+/// day.
+pub async fn users_to_refresh(
+    db: &impl CrawlDb,
+    now: i64,
+    limit: usize,
+) -> Result<Vec<String>, DbErr> {
+    let active: Vec<String> = sql_entities::submissions::Entity::find()
+        .select_only()
+        .column(sql_entities::submissions::Column::UserId)
+        .distinct()
+        .filter(sql_entities::submissions::Column::EpochSecond.gte(now - ACTIVE_USER_WINDOW_SECOND))
+        .into_tuple()
+        .all(db)
+        .await?;
+    let linked: Vec<Option<String>> = sql_entities::internal_users::Entity::find()
+        .select_only()
+        .column(sql_entities::internal_users::Column::AtcoderUserId)
+        .filter(sql_entities::internal_users::Column::AtcoderUserId.is_not_null())
+        .into_tuple()
+        .all(db)
+        .await?;
+    let crawled: HashMap<String, i64> = sql_entities::user_profiles::Entity::find()
+        .select_only()
+        .column(sql_entities::user_profiles::Column::UserId)
+        .column(sql_entities::user_profiles::Column::CrawledEpochSecond)
+        .into_tuple()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
 
-    while retry_count < MAX_RETRIES {
-        match fetcher.fetch_contests(page).await {
-            Ok(contests) => {
-                return contests;
-            }
-            Err(e) => {
-                retry_count += 1;
-                tracing::warn!(
-                    "Failed to fetch contests page {} (attempt {}/{}): {}",
-                    page,
-                    retry_count,
-                    MAX_RETRIES,
-                    e
-                );
-                tokio::time::sleep(std::time::Duration::from_millis(retry_delay)).await;
-                retry_delay *= 2;
-            }
+    let mut user_ids: Vec<String> = active
+        .into_iter()
+        .chain(linked.into_iter().flatten())
+        .filter(|user_id| !user_id.is_empty())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter(|user_id| {
+            crawled.get(user_id).is_none_or(|&crawled_epoch_second| {
+                crawled_epoch_second < now - USER_PROFILE_REFRESH_INTERVAL_SECOND
+            })
+        })
+        .collect();
+    user_ids.sort_by(|a, b| crawled.get(a).cmp(&crawled.get(b)).then_with(|| a.cmp(b)));
+    user_ids.truncate(limit);
+    Ok(user_ids)
+}
+
+/// Fetches the profile and the rating history of a user and stores them.
+///
+/// Returns false without storing anything if the user does not exist.
+pub async fn crawl_user_profile(
+    fetcher: &dyn UserFetcher,
+    db: &impl CrawlDb,
+    user_id: &str,
+) -> Result<bool, CrawlError> {
+    let profile = match fetcher.fetch_user_profile(user_id).await {
+        Ok(profile) => profile,
+        Err(CrawlerError::NotFound) => {
+            tracing::warn!("User {} not found (404), skipping", user_id);
+            return Ok(false);
         }
+        Err(e) => return Err(e.into()),
+    };
+    let history = fetcher.fetch_rating_history(user_id).await?;
+    let mut entries = Vec::with_capacity(history.len());
+    for entry in &history {
+        entries.push(sql_entities::user_rating_history::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            contest_id: Set(entry.contest_id().to_string()),
+            is_rated: Set(entry.is_rated),
+            place: Set(entry.place),
+            old_rating: Set(entry.old_rating),
+            new_rating: Set(entry.new_rating),
+            performance: Set(entry.performance),
+            inner_performance: Set(entry.inner_performance),
+            end_epoch_second: Set(entry.end_epoch_second()?),
+        });
+    }
+    if skip_write("user_profiles", 1) {
+        skip_write("user_rating_history", entries.len());
+        return Ok(true);
     }
 
-    tracing::error!(
-        "Failed to fetch contests page {} after {} retries",
-        page,
-        MAX_RETRIES
-    );
-    vec![]
+    let txn = db.begin().await?;
+    let profile_exists = sql_entities::user_profiles::Entity::find_by_id(user_id)
+        .one(&txn)
+        .await?
+        .is_some();
+    let stored_contest_ids: HashSet<String> = sql_entities::user_rating_history::Entity::find()
+        .select_only()
+        .column(sql_entities::user_rating_history::Column::ContestId)
+        .filter(sql_entities::user_rating_history::Column::UserId.eq(user_id))
+        .into_tuple()
+        .all(&txn)
+        .await?
+        .into_iter()
+        .collect();
+    let updated_entries = history
+        .iter()
+        .filter(|entry| stored_contest_ids.contains(entry.contest_id()))
+        .count();
+    sql_entities::user_profiles::Entity::insert(sql_entities::user_profiles::ActiveModel {
+        user_id: Set(profile.user_id),
+        country: Set(profile.country),
+        affiliation: Set(profile.affiliation),
+        birth_year: Set(profile.birth_year),
+        rating: Set(profile.rating),
+        highest_rating: Set(profile.highest_rating),
+        crawled_epoch_second: Set(chrono::Utc::now().timestamp()),
+    })
+    .on_conflict(
+        OnConflict::column(sql_entities::user_profiles::Column::UserId)
+            .update_columns([
+                sql_entities::user_profiles::Column::Country,
+                sql_entities::user_profiles::Column::Affiliation,
+                sql_entities::user_profiles::Column::BirthYear,
+                sql_entities::user_profiles::Column::Rating,
+                sql_entities::user_profiles::Column::HighestRating,
+                sql_entities::user_profiles::Column::CrawledEpochSecond,
+            ])
+            .to_owned(),
+    )
+    .exec(&txn)
+    .await?;
+    if !entries.is_empty() {
+        sql_entities::user_rating_history::Entity::insert_many(entries)
+            .on_conflict(
+                OnConflict::columns([
+                    sql_entities::user_rating_history::Column::UserId,
+                    sql_entities::user_rating_history::Column::ContestId,
+                ])
+                .update_columns([
+                    sql_entities::user_rating_history::Column::IsRated,
+                    sql_entities::user_rating_history::Column::Place,
+                    sql_entities::user_rating_history::Column::OldRating,
+                    sql_entities::user_rating_history::Column::NewRating,
+                    sql_entities::user_rating_history::Column::Performance,
+                    sql_entities::user_rating_history::Column::InnerPerformance,
+                    sql_entities::user_rating_history::Column::EndEpochSecond,
+                ])
+                .to_owned(),
+            )
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+    let updated = updated_entries + usize::from(profile_exists);
+    crawl_runs::record_rows(history.len() + 1 - updated, updated);
+    Ok(true)
 }
 
-async fn fetch_contests_in_category_with_retry(
-    fetcher: &dyn ContestFetcher,
-    page: u32,
-    category: u32,
-) -> Vec<Contest> {
-    const MAX_RETRIES: u32 = 10;
-    let mut retry_count = 0;
-    let mut retry_delay = 2000;
-
-    while retry_count < MAX_RETRIES {
-        match fetcher.fetch_contests_in_category(page, category).await {
-            Ok(contests) => {
-                return contests;
+/// Refreshes the profiles and rating histories of `user_ids` in order.
+///
+/// A user that cannot be crawled is logged and skipped; database errors and
+/// an expired session abort the run.
+///
+/// Returns the number of profiles stored.
+pub async fn refresh_user_profiles(
+    fetcher: &dyn UserFetcher,
+    db: &impl CrawlDb,
+    user_ids: &[String],
+) -> Result<usize, CrawlError> {
+    let mut refreshed = 0;
+    for user_id in user_ids {
+        match crawl_user_profile(fetcher, db, user_id).await {
+            Ok(true) => refreshed += 1,
+            Ok(false) => {}
+            Err(CrawlError::Crawler(e @ CrawlerError::SessionExpired { .. })) => {
+                return Err(e.into());
             }
-            Err(e) => {
-                retry_count += 1;
-                tracing::warn!(
-                    "Failed to fetch contests category {} page {} (attempt {}/{}): {}",
-                    category,
-                    page,
-                    retry_count,
-                    MAX_RETRIES,
-                    e
-                );
-                tokio::time::sleep(std::time::Duration::from_millis(retry_delay)).await;
-                retry_delay *= 2;
+            Err(CrawlError::Crawler(e)) => {
+                tracing::error!("Failed to crawl the profile of {}: {}", user_id, e);
             }
+            Err(e) => return Err(e),
         }
     }
-
-    tracing::error!(
-        "Failed to fetch contests category {} page {} after {} retries",
-        category,
-        page,
-        MAX_RETRIES
+    tracing::info!(
+        "Refreshed {} of {} user profiles",
+        refreshed,
+        user_ids.len()
     );
-    vec![]
+    Ok(refreshed)
 }
 
-async fn upsert_contests(db: &DatabaseConnection, contests: Vec<Contest>) -> Result<usize, DbErr> {
-    for contest in &contests {
-        let model = sql_entities::contests::ActiveModel {
-            id: Set(contest.id.clone()),
-            start_epoch_second: Set(contest.start_epoch_second),
-            duration_second: Set(contest.duration_second),
-            title: Set(contest.title.clone()),
-            rate_change: Set(contest.rate_change.clone()),
+/// Returns the ended contests whose editorial list might have changed since
+/// it was last crawled, most recent first.
+///
+/// These are the contests that were never crawled and the ones last crawled
+/// before their update window closed, so a recent contest is visited on every
+/// run and once more after the window.
+pub async fn contest_ids_for_editorial_crawl(
+    db: &impl CrawlDb,
+    now: i64,
+) -> Result<Vec<String>, DbErr> {
+    let crawled: HashMap<String, i64> = sql_entities::editorial_crawls::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|crawl| (crawl.contest_id, crawl.crawled_epoch_second))
+        .collect();
+    let mut contests: Vec<(i64, String)> = sql_entities::contests::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|contest| {
+            let end = contest.start_epoch_second + contest.duration_second;
+            let outdated = crawled
+                .get(&contest.id)
+                .is_none_or(|&crawled_epoch_second| {
+                    crawled_epoch_second < end + EDITORIAL_UPDATE_WINDOW_SECOND
+                });
+            (end <= now && outdated).then_some((end, contest.id))
+        })
+        .collect();
+    contests.sort_by(|a, b| b.cmp(a));
+    Ok(contests.into_iter().map(|(_, id)| id).collect())
+}
+
+/// Crawls the editorial lists of `contest_ids`, replacing the stored
+/// editorials of each contest.
+///
+/// A contest without an editorial page, or whose page lists none, is recorded
+/// as crawled and keeps the editorials stored before. Any other failure is
+/// logged and leaves the contest for the next run; database errors and an
+/// expired session abort the crawl.
+///
+/// Returns the total number of editorials stored.
+pub async fn crawl_editorials(
+    fetcher: &dyn EditorialFetcher,
+    db: &impl CrawlDb,
+    contest_ids: &[String],
+) -> Result<usize, CrawlError> {
+    let mut total_stored = 0;
+    for contest_id in contest_ids {
+        let editorials = match fetcher.fetch_editorials(contest_id).await {
+            Ok(editorials) => editorials,
+            Err(CrawlerError::NotFound) => {
+                tracing::info!("Contest {} has no editorial page", contest_id);
+                Vec::new()
+            }
+            Err(e @ CrawlerError::SessionExpired { .. }) => return Err(e.into()),
+            Err(e) => {
+                tracing::error!("Failed to fetch editorials of {}: {}", contest_id, e);
+                continue;
+            }
         };
-        sql_entities::contests::Entity::insert(model)
+        let stored = replace_editorials(db, contest_id, editorials).await?;
+        tracing::info!("Stored {} editorials of {}", stored, contest_id);
+        total_stored += stored;
+    }
+    Ok(total_stored)
+}
+
+async fn replace_editorials(
+    db: &impl CrawlDb,
+    contest_id: &str,
+    editorials: Vec<Editorial>,
+) -> Result<usize, DbErr> {
+    let models: Vec<_> = dedupe_by_key(editorials, |e| (e.problem_id.clone(), e.url.clone()))
+        .into_values()
+        .map(|editorial| sql_entities::problem_editorials::ActiveModel {
+            problem_id: Set(editorial.problem_id),
+            editorial_url: Set(editorial.url),
+            contest_id: Set(contest_id.to_string()),
+            language: Set(editorial.language),
+            is_official: Set(editorial.is_official),
+        })
+        .collect();
+    let stored = models.len();
+    if skip_write("problem_editorials", stored) {
+        skip_write("editorial_crawls", 1);
+        return Ok(stored);
+    }
+
+    let txn = db.begin().await?;
+    if models.is_empty() {
+        // An error page or a changed layout parses as no editorials, so the
+        // stored ones are kept rather than wiped.
+        let kept = sql_entities::problem_editorials::Entity::find()
+            .filter(sql_entities::problem_editorials::Column::ContestId.eq(contest_id))
+            .count(&txn)
+            .await?;
+        if kept > 0 {
+            tracing::warn!(
+                "No editorials found for {}, keeping the {} stored ones",
+                contest_id,
+                kept
+            );
+        }
+    } else {
+        sql_entities::problem_editorials::Entity::delete_many()
+            .filter(sql_entities::problem_editorials::Column::ContestId.eq(contest_id))
+            .exec(&txn)
+            .await?;
+        sql_entities::problem_editorials::Entity::insert_many(models)
             .on_conflict(
-                OnConflict::column(sql_entities::contests::Column::Id)
-                    .update_columns([
-                        sql_entities::contests::Column::StartEpochSecond,
-                        sql_entities::contests::Column::DurationSecond,
-                        sql_entities::contests::Column::Title,
-                        sql_entities::contests::Column::RateChange,
-                    ])
-                    .to_owned(),
+                OnConflict::columns([
+                    sql_entities::problem_editorials::Column::ProblemId,
+                    sql_entities::problem_editorials::Column::EditorialUrl,
+                ])
+                .update_columns([
+                    sql_entities::problem_editorials::Column::ContestId,
+                    sql_entities::problem_editorials::Column::Language,
+                    sql_entities::problem_editorials::Column::IsOfficial,
+                ])
+                .to_owned(),
             )
-            .exec(db)
+            .exec(&txn)
             .await?;
     }
-    Ok(contests.len())
+    sql_entities::editorial_crawls::Entity::insert(sql_entities::editorial_crawls::ActiveModel {
+        contest_id: Set(contest_id.to_string()),
+        crawled_epoch_second: Set(chrono::Utc::now().timestamp()),
+    })
+    .on_conflict(
+        OnConflict::column(sql_entities::editorial_crawls::Column::ContestId)
+            .update_column(sql_entities::editorial_crawls::Column::CrawledEpochSecond)
+            .to_owned(),
+    )
+    .exec(&txn)
+    .await?;
+    txn.commit().await?;
+    // The links of the contest are replaced, so every one of them is new.
+    crawl_runs::record_rows(stored, 0);
+    Ok(stored)
+}
+
+/// Fetches the standings of a contest if they are final.
+///
+/// Returns `None` when the contest has no standings or when they are not
+/// fixed yet, since uploading them would freeze a provisional result.
+/// Standings that are fixed but inconsistent are rejected with
+/// [`CrawlerError::InvalidStandings`].
+pub async fn fetch_fixed_standings(
+    fetcher: &dyn StandingsFetcher,
+    contest_id: &str,
+) -> Result<Option<Standings>, CrawlerError> {
+    let Some(standings) = fetcher.fetch_standings(contest_id).await? else {
+        tracing::warn!("Standings for contest {} not found", contest_id);
+        return Ok(None);
+    };
+
+    if !standings.fixed {
+        tracing::warn!("Standings for contest {} is not fixed", contest_id);
+        return Ok(None);
+    }
+    standings.validate()?;
+    Ok(Some(standings))
 }
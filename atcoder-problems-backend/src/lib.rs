@@ -1,2 +1,6 @@
+pub mod aggregator;
+pub mod bulk_load;
+pub mod config;
+pub mod crawl_runs;
 pub mod crawler_utils;
 pub mod server;
@@ -0,0 +1,40 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+use server_db::{
+    self as db,
+    crawl_runs::{CrawlRun, CrawlRunFilter},
+};
+
+use crate::server::{AppState, AuthedUser, ServerResult};
+
+const DEFAULT_CRAWL_RUN_LIMIT: u64 = 100;
+const MAX_CRAWL_RUN_LIMIT: u64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GetRecentRunsQuery {
+    command: Option<String>,
+    status: Option<String>,
+    contest_id: Option<String>,
+    limit: Option<u64>,
+}
+
+pub(crate) async fn get_recent_runs(
+    State(state): State<AppState>,
+    AuthedUser(_): AuthedUser,
+    Query(q): Query<GetRecentRunsQuery>,
+) -> ServerResult<Json<Vec<CrawlRun>>> {
+    let filter = CrawlRunFilter {
+        command: q.command.as_deref(),
+        status: q.status.as_deref(),
+        contest_id: q.contest_id.as_deref(),
+    };
+    let limit = q
+        .limit
+        .unwrap_or(DEFAULT_CRAWL_RUN_LIMIT)
+        .min(MAX_CRAWL_RUN_LIMIT);
+    let runs = db::crawl_runs::get_recent_runs(&state.db, filter, limit).await?;
+    Ok(Json(runs))
+}
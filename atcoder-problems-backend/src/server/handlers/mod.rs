@@ -1,4 +1,5 @@
 pub(crate) mod authorize;
+pub(crate) mod crawl_runs;
 pub(crate) mod healthcheck;
 pub(crate) mod internal_user;
 pub(crate) mod language_count;
@@ -110,4 +110,5 @@ fn internal_router() -> Router<AppState> {
             "/progress_reset/delete",
             post(progress_reset::delete_progress_reset_item),
         )
+        .route("/crawl_runs/recent", get(crawl_runs::get_recent_runs))
 }
@@ -68,6 +68,11 @@ async fn create_all_tables(db: &DatabaseConnection) -> Result<(), DbErr> {
     create!(sql_entities::accepted_count::Entity);
     create!(sql_entities::contest_problem::Entity);
     create!(sql_entities::contests::Entity);
+    create!(sql_entities::crawl_queue::Entity);
+    create!(sql_entities::crawl_run_contests::Entity);
+    create!(sql_entities::crawl_runs::Entity);
+    create!(sql_entities::crawl_state::Entity);
+    create!(sql_entities::editorial_crawls::Entity);
     create!(sql_entities::fastest::Entity);
     create!(sql_entities::first::Entity);
     create!(sql_entities::internal_problem_list_items::Entity);
@@ -79,13 +84,21 @@ async fn create_all_tables(db: &DatabaseConnection) -> Result<(), DbErr> {
     create!(sql_entities::internal_virtual_contests::Entity);
     create!(sql_entities::language_count::Entity);
     create!(sql_entities::max_streaks::Entity);
+    create!(sql_entities::merged_problems::Entity);
     create!(sql_entities::points::Entity);
     create!(sql_entities::predicted_rating::Entity);
+    create!(sql_entities::problem_editorials::Entity);
     create!(sql_entities::problems::Entity);
     create!(sql_entities::rated_point_sum::Entity);
     create!(sql_entities::shortest::Entity);
     create!(sql_entities::solver::Entity);
+    create!(sql_entities::submission_details::Entity);
+    create!(sql_entities::submission_history::Entity);
+    create!(sql_entities::submission_judge_cases::Entity);
     create!(sql_entities::submissions::Entity);
+    create!(sql_entities::user_crawl_jobs::Entity);
+    create!(sql_entities::user_profiles::Entity);
+    create!(sql_entities::user_rating_history::Entity);
     Ok(())
 }
 
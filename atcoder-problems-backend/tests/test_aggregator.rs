@@ -0,0 +1,709 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use atcoder_problems_backend::aggregator::{
+    EXCLUDED_USERS, Marts, compute_marts, recompute_marts, simplify_language,
+};
+use sea_orm::{
+    ConnectionTrait, Database, DatabaseBackend, DatabaseConnection, EntityTrait, IntoActiveModel,
+    Statement, TransactionTrait,
+};
+use sql_entities::{
+    accepted_count, contest_problem, contests, fastest, first, language_count, max_streaks,
+    merged_problems, points, problems, rated_point_sum, shortest, solver, submissions,
+};
+
+/// Postgres database with `postgres/schema.sql` applied. Its contests,
+/// problems, submissions and marts are truncated, so never point it at a
+/// database whose data matters.
+const POSTGRES_URL_ENV: &str = "TEST_POSTGRES_URL";
+
+/// Set by CI, whose workflow provides the Postgres database.
+const CI_ENV: &str = "CI";
+
+/// Schema that receives the dbt models in the parity test
+const DBT_SCHEMA: &str = "dbt_parity";
+
+/// Start of `abc100`, 21:26:40 JST
+const START: i64 = 1_600_000_000;
+
+/// Start of `arc100`, which shares `abc100_b` with `abc100`
+const ARC_START: i64 = START - 3600;
+
+const HOUR: i64 = 3600;
+
+fn contest(
+    id: &str,
+    start_epoch_second: i64,
+    rated_lower: Option<i32>,
+    contest_type: &str,
+) -> contests::Model {
+    contests::Model {
+        id: id.to_string(),
+        start_epoch_second,
+        duration_second: 6000,
+        title: id.to_uppercase(),
+        rate_change: "-".to_string(),
+        rated_lower,
+        rated_upper: None,
+        contest_type: contest_type.to_string(),
+    }
+}
+
+fn problem(id: &str, contest_id: &str, point: Option<f64>) -> problems::Model {
+    let index = id.rsplit('_').next().unwrap().to_uppercase();
+    problems::Model {
+        id: id.to_string(),
+        contest_id: contest_id.to_string(),
+        problem_index: index.clone(),
+        name: format!("Problem {index}"),
+        title: format!("{index}. Problem {index}"),
+        time_limit_ms: Some(2000),
+        memory_limit_kb: Some(1_048_576),
+        point,
+        orphaned_epoch_second: None,
+    }
+}
+
+fn contest_problem(contest_id: &str, problem_id: &str) -> contest_problem::Model {
+    contest_problem::Model {
+        contest_id: contest_id.to_string(),
+        problem_id: problem_id.to_string(),
+        problem_index: problem_id.rsplit('_').next().unwrap().to_uppercase(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn submission(
+    id: i64,
+    user_id: &str,
+    problem_id: &str,
+    contest_id: &str,
+    result: &str,
+    point: f64,
+    length: i32,
+    execution_time: Option<i32>,
+    epoch_second: i64,
+    language: &str,
+) -> submissions::Model {
+    submissions::Model {
+        id,
+        epoch_second,
+        problem_id: problem_id.to_string(),
+        contest_id: contest_id.to_string(),
+        user_id: user_id.to_string(),
+        language: language.to_string(),
+        point,
+        length,
+        result: result.to_string(),
+        execution_time,
+        memory: Some(1024),
+    }
+}
+
+const CPP: &str = "C++ 20 (gcc 12.2)";
+const PYTHON: &str = "Python (CPython 3.11.4)";
+const PYTHON3: &str = "Python3 (3.8)";
+const RUST: &str = "Rust (rustc 1.70.0)";
+
+/// Inserts contests that are rated (`abc100`, `arc100`) or not for each reason
+/// the dbt models check, and submissions for the edge cases of every mart.
+async fn insert_fixture(db: &DatabaseConnection) {
+    contests::Entity::insert_many(
+        [
+            contest("abc100", START, Some(0), "abc"),
+            contest("arc100", ARC_START, Some(1200), "arc"),
+            // Before the first AGC
+            contest("old001", 1_400_000_000, Some(0), "other"),
+            contest("ahc001", START, Some(0), "ahc"),
+            contest("unr001", START, None, "other"),
+        ]
+        .map(IntoActiveModel::into_active_model),
+    )
+    .exec(db)
+    .await
+    .unwrap();
+    contest_problem::Entity::insert_many(
+        [
+            contest_problem("abc100", "abc100_a"),
+            contest_problem("abc100", "abc100_b"),
+            contest_problem("arc100", "arc100_a"),
+            contest_problem("arc100", "abc100_b"),
+            contest_problem("old001", "old001_a"),
+            contest_problem("old001", "old001_b"),
+            contest_problem("ahc001", "ahc001_a"),
+            contest_problem("ahc001", "ahc001_b"),
+            contest_problem("unr001", "unr001_a"),
+            contest_problem("unr001", "unr001_b"),
+        ]
+        .map(IntoActiveModel::into_active_model),
+    )
+    .exec(db)
+    .await
+    .unwrap();
+    problems::Entity::insert_many(
+        [
+            problem("abc100_a", "abc100", Some(100.0)),
+            problem("abc100_b", "abc100", None),
+            problem("arc100_a", "arc100", Some(300.0)),
+            problem("old001_a", "old001", None),
+            problem("ahc001_a", "ahc001", None),
+            problem("unr001_a", "unr001", None),
+        ]
+        .map(IntoActiveModel::into_active_model),
+    )
+    .exec(db)
+    .await
+    .unwrap();
+    #[rustfmt::skip]
+    let submissions = [
+        // Writer's test before the contest
+        submission(1, "alice", "abc100_a", "abc100", "AC", 100.0, 50, Some(10), START - 100, CPP),
+        submission(2, "bob", "abc100_a", "abc100", "AC", 100.0, 80, Some(5), START + 100, PYTHON),
+        // As fast as 2 and as short as 4
+        submission(3, "carol", "abc100_a", "abc100", "AC", 100.0, 60, Some(5), START + 200, PYTHON3),
+        submission(4, "dave", "abc100_a", "abc100", "AC", 100.0, 60, None, START + 300, RUST),
+        submission(5, "vjudge1", "abc100_a", "abc100", "AC", 100.0, 1, Some(1), START + 50, "C"),
+        submission(6, "alice", "abc100_b", "abc100", "WA", 50.0, 100, Some(10), START + 100, CPP),
+        // The next JST day, the same UTC day
+        submission(7, "alice", "abc100_b", "abc100", "AC", 200.0, 100, Some(10), START + 3 * HOUR, CPP),
+        submission(8, "bob", "abc100_b", "abc100", "3/10 WJ", 500.0, 100, None, START + 200, PYTHON),
+        submission(9, "carol", "abc100_b", "abc100", "AC", 150.5, 100, Some(20), START + 500, PYTHON3),
+        // Before abc100 but during arc100, which shares the problem
+        submission(10, "bob", "abc100_b", "arc100", "AC", 150.0, 90, Some(30), START - 1000, PYTHON),
+        submission(11, "bob", "arc100_a", "arc100", "AC", 300.0, 100, Some(1), ARC_START - 10, PYTHON),
+        // Solved again two JST days later
+        submission(12, "alice", "abc100_a", "abc100", "AC", 100.0, 100, Some(10), START + 27 * HOUR, "Perl6 (rakudo 2023)"),
+        submission(13, "alice", "old001_a", "old001", "AC", 100.0, 10, Some(1), 1_400_000_100, "C"),
+        submission(14, "bob", "ahc001_a", "ahc001", "AC", 1000.0, 100, None, START + 100, CPP),
+        // Contest that is not stored
+        submission(15, "carol", "zzz_a", "zzz", "AC", 100.0, 100, Some(1), START + 100, RUST),
+        submission(16, "dave", "abc100_b", "abc100", "WJ", 100.0, 100, None, START + 100, RUST),
+        submission(17, "luogu_bot", "abc100_b", "abc100", "AC", 1000.0, 1, Some(1), START + 100, CPP),
+    ];
+    submissions::Entity::insert_many(submissions.map(IntoActiveModel::into_active_model))
+        .exec(db)
+        .await
+        .unwrap();
+}
+
+/// Marts of the fixture, worked out by hand from the dbt models
+fn expected_marts() -> Marts {
+    let shortest = |contest_id: &str, problem_id: &str, submission_id| shortest::Model {
+        contest_id: contest_id.to_string(),
+        problem_id: problem_id.to_string(),
+        submission_id,
+    };
+    let fastest = |contest_id: &str, problem_id: &str, submission_id| fastest::Model {
+        contest_id: contest_id.to_string(),
+        problem_id: problem_id.to_string(),
+        submission_id,
+    };
+    let first = |contest_id: &str, problem_id: &str, submission_id| first::Model {
+        contest_id: contest_id.to_string(),
+        problem_id: problem_id.to_string(),
+        submission_id,
+    };
+    let merged = |id: &str, contest_id: &str| {
+        let problem = problem(id, contest_id, None);
+        merged_problems::Model {
+            id: problem.id,
+            contest_id: problem.contest_id,
+            problem_index: problem.problem_index,
+            name: problem.name,
+            title: problem.title,
+            shortest_submission_id: None,
+            shortest_contest_id: None,
+            shortest_user_id: None,
+            fastest_submission_id: None,
+            fastest_contest_id: None,
+            fastest_user_id: None,
+            first_submission_id: None,
+            first_contest_id: None,
+            first_user_id: None,
+            source_code_length: None,
+            execution_time: None,
+            point: None,
+            solver_count: None,
+        }
+    };
+    let some = |s: &str| Some(s.to_string());
+
+    Marts {
+        accepted_count: [("alice", 3), ("bob", 4), ("carol", 3), ("dave", 1)]
+            .map(|(user_id, problem_count)| accepted_count::Model {
+                user_id: user_id.to_string(),
+                problem_count,
+            })
+            .to_vec(),
+        rated_point_sum: [("alice", 300), ("bob", 250), ("carol", 100), ("dave", 100)]
+            .map(|(user_id, point_sum)| rated_point_sum::Model {
+                user_id: user_id.to_string(),
+                point_sum,
+            })
+            .to_vec(),
+        max_streaks: [("alice", 2), ("bob", 1), ("carol", 1), ("dave", 1)]
+            .map(|(user_id, streak)| max_streaks::Model {
+                user_id: user_id.to_string(),
+                streak,
+            })
+            .to_vec(),
+        language_count: [
+            ("alice", "C", 1),
+            ("alice", "C++", 2),
+            ("alice", "Raku", 1),
+            ("bob", "C++", 1),
+            ("bob", "Python", 3),
+            ("carol", "Python", 2),
+            ("carol", "Rust", 1),
+            ("dave", "Rust", 1),
+        ]
+        .map(
+            |(user_id, simplified_language, problem_count)| language_count::Model {
+                user_id: user_id.to_string(),
+                simplified_language: simplified_language.to_string(),
+                problem_count,
+            },
+        )
+        .to_vec(),
+        shortest: vec![
+            shortest("abc100", "abc100_a", 3),
+            shortest("arc100", "abc100_b", 10),
+            shortest("ahc001", "ahc001_a", 14),
+            shortest("old001", "old001_a", 13),
+        ],
+        fastest: vec![
+            fastest("abc100", "abc100_a", 2),
+            fastest("abc100", "abc100_b", 7),
+            fastest("old001", "old001_a", 13),
+        ],
+        first: vec![
+            first("abc100", "abc100_a", 2),
+            first("abc100", "abc100_b", 7),
+            first("ahc001", "ahc001_a", 14),
+            first("old001", "old001_a", 13),
+        ],
+        solver: [
+            ("abc100_a", 4),
+            ("abc100_b", 3),
+            ("ahc001_a", 1),
+            ("arc100_a", 1),
+            ("old001_a", 1),
+            ("zzz_a", 1),
+        ]
+        .map(|(problem_id, user_count)| solver::Model {
+            problem_id: problem_id.to_string(),
+            user_count,
+        })
+        .to_vec(),
+        points: [
+            ("abc100_a", 100.0),
+            ("abc100_b", 200.0),
+            ("arc100_a", 300.0),
+        ]
+        .map(|(problem_id, point)| points::Model {
+            problem_id: problem_id.to_string(),
+            point: Some(point),
+            predict: None,
+        })
+        .to_vec(),
+        merged_problems: vec![
+            merged_problems::Model {
+                shortest_submission_id: Some(3),
+                shortest_contest_id: some("abc100"),
+                shortest_user_id: some("carol"),
+                fastest_submission_id: Some(2),
+                fastest_contest_id: some("abc100"),
+                fastest_user_id: some("bob"),
+                first_submission_id: Some(2),
+                first_contest_id: some("abc100"),
+                first_user_id: some("bob"),
+                source_code_length: Some(60),
+                execution_time: Some(5),
+                point: Some(100.0),
+                solver_count: Some(4),
+                ..merged("abc100_a", "abc100")
+            },
+            merged_problems::Model {
+                shortest_submission_id: Some(10),
+                shortest_contest_id: some("arc100"),
+                shortest_user_id: some("bob"),
+                fastest_submission_id: Some(7),
+                fastest_contest_id: some("abc100"),
+                fastest_user_id: some("alice"),
+                first_submission_id: Some(7),
+                first_contest_id: some("abc100"),
+                first_user_id: some("alice"),
+                source_code_length: Some(90),
+                execution_time: Some(10),
+                point: Some(200.0),
+                solver_count: Some(3),
+                ..merged("abc100_b", "abc100")
+            },
+            merged_problems::Model {
+                shortest_submission_id: Some(14),
+                shortest_contest_id: some("ahc001"),
+                shortest_user_id: some("bob"),
+                first_submission_id: Some(14),
+                first_contest_id: some("ahc001"),
+                first_user_id: some("bob"),
+                source_code_length: Some(100),
+                solver_count: Some(1),
+                ..merged("ahc001_a", "ahc001")
+            },
+            merged_problems::Model {
+                point: Some(300.0),
+                solver_count: Some(1),
+                ..merged("arc100_a", "arc100")
+            },
+            merged_problems::Model {
+                shortest_submission_id: Some(13),
+                shortest_contest_id: some("old001"),
+                shortest_user_id: some("alice"),
+                fastest_submission_id: Some(13),
+                fastest_contest_id: some("old001"),
+                fastest_user_id: some("alice"),
+                first_submission_id: Some(13),
+                first_contest_id: some("old001"),
+                first_user_id: some("alice"),
+                source_code_length: Some(10),
+                execution_time: Some(1),
+                solver_count: Some(1),
+                ..merged("old001_a", "old001")
+            },
+            merged("unr001_a", "unr001"),
+        ],
+    }
+}
+
+/// Reads every mart back from the tables of `db`.
+async fn stored_marts(db: &impl ConnectionTrait) -> Marts {
+    Marts {
+        accepted_count: accepted_count::Entity::find().all(db).await.unwrap(),
+        rated_point_sum: rated_point_sum::Entity::find().all(db).await.unwrap(),
+        max_streaks: max_streaks::Entity::find().all(db).await.unwrap(),
+        language_count: language_count::Entity::find().all(db).await.unwrap(),
+        shortest: shortest::Entity::find().all(db).await.unwrap(),
+        fastest: fastest::Entity::find().all(db).await.unwrap(),
+        first: first::Entity::find().all(db).await.unwrap(),
+        solver: solver::Entity::find().all(db).await.unwrap(),
+        points: points::Entity::find().all(db).await.unwrap(),
+        merged_problems: merged_problems::Entity::find().all(db).await.unwrap(),
+    }
+    .sorted()
+}
+
+trait Sorted {
+    fn sorted(self) -> Self;
+}
+
+impl Sorted for Marts {
+    /// Sorts every mart by its primary key, the order of [`compute_marts`].
+    fn sorted(mut self) -> Self {
+        self.accepted_count
+            .sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        self.rated_point_sum
+            .sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        self.max_streaks.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        self.language_count.sort_by(|a, b| {
+            (&a.user_id, &a.simplified_language).cmp(&(&b.user_id, &b.simplified_language))
+        });
+        self.shortest
+            .sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+        self.fastest.sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+        self.first.sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+        self.solver.sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+        self.points.sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+        self.merged_problems.sort_by(|a, b| a.id.cmp(&b.id));
+        self
+    }
+}
+
+#[test]
+fn test_excluded_users_match_the_dbt_seed() {
+    let seed = include_str!("../../dbt/seeds/excluded_users.csv");
+    let mut lines = seed.lines().map(str::trim).filter(|l| !l.is_empty());
+    assert_eq!(lines.next(), Some("user_id"));
+    assert_eq!(lines.collect::<Vec<_>>(), EXCLUDED_USERS);
+}
+
+#[test]
+fn test_simplify_language() {
+    for (language, simplified) in [
+        ("C++ 20 (gcc 12.2)", "C++"),
+        ("C++14 (GCC 5.4.1)", "C++"),
+        ("Python3 (3.8)", "Python"),
+        ("Python (CPython 3.11.4)", "Python"),
+        ("Common Lisp (SBCL 2.0.3)", "Common Lisp"),
+        ("Text (cat) (1)", "Text"),
+        ("Perl6 (rakudo-star 2016.01)", "Raku"),
+        ("Raku (Rakudo 2023.06)", "Raku"),
+        ("Bash", "Bash"),
+        ("Unclosed (", "Unclosed ("),
+        // The regex only takes the digits right before the version.
+        ("C# 11.0 (.NET 7.0.7)", "C# 11."),
+    ] {
+        assert_eq!(simplify_language(language), simplified, "{language}");
+    }
+}
+
+#[tokio::test]
+async fn test_compute_marts() {
+    let db = common::setup_db().await;
+    insert_fixture(&db).await;
+    let marts = compute_marts(&db).await.unwrap();
+    assert_eq!(marts, expected_marts());
+}
+
+#[tokio::test]
+async fn test_recompute_marts_replaces_every_row() {
+    let db = common::setup_db().await;
+    insert_fixture(&db).await;
+    let stale = accepted_count::Model {
+        user_id: "gone".to_string(),
+        problem_count: 1,
+    };
+    accepted_count::Entity::insert(stale.clone().into_active_model())
+        .exec(&db)
+        .await
+        .unwrap();
+
+    // A dry run computes the marts but leaves the tables as they were.
+    let marts = recompute_marts(&db, true).await.unwrap();
+    assert_eq!(marts, expected_marts());
+    assert_eq!(
+        stored_marts(&db).await,
+        Marts {
+            accepted_count: vec![stale],
+            ..Marts::default()
+        }
+    );
+
+    recompute_marts(&db, false).await.unwrap();
+    assert_eq!(stored_marts(&db).await, expected_marts());
+
+    // Recomputing without changes writes the same rows.
+    recompute_marts(&db, false).await.unwrap();
+    assert_eq!(stored_marts(&db).await, expected_marts());
+}
+
+/// Turns the dbt model `name` of `dir` into a view in [`DBT_SCHEMA`].
+async fn create_dbt_view(db: &DatabaseConnection, dir: &str, name: &str) {
+    let path = format!(
+        "{}/../dbt/models/{dir}/{name}.sql",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let mut sql = std::fs::read_to_string(&path).unwrap();
+    sql = sql
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("{{ config("))
+        .collect::<Vec<_>>()
+        .join("\n");
+    while let Some(start) = sql.find("{{") {
+        let end = start + sql[start..].find("}}").unwrap() + 2;
+        let call = &sql[start + 2..end - 2];
+        let args: Vec<&str> = call.split('\'').skip(1).step_by(2).collect();
+        let table = if call.trim_start().starts_with("source(") {
+            format!("public.{}", args[1])
+        } else {
+            format!("{DBT_SCHEMA}.{}", args[0])
+        };
+        sql.replace_range(start..end, &table);
+    }
+    db.execute_unprepared(&format!("CREATE VIEW {DBT_SCHEMA}.{name} AS {sql}"))
+        .await
+        .unwrap_or_else(|e| panic!("{path}: {e}"));
+}
+
+async fn dbt_mart<E: EntityTrait>(db: &DatabaseConnection, entity: E) -> Vec<E::Model> {
+    let sql = format!("SELECT * FROM {DBT_SCHEMA}.{}", entity.table_name());
+    E::find()
+        .from_raw_sql(Statement::from_string(DatabaseBackend::Postgres, sql))
+        .all(db)
+        .await
+        .unwrap()
+}
+
+/// Serializes the tests sharing the database of `TEST_POSTGRES_URL`.
+static POSTGRES: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Connects to the database of `TEST_POSTGRES_URL` and empties the tables the
+/// marts are computed from and stored in, or returns `None` if it is not set
+/// outside of CI.
+/// The database is held by the test until the guard is dropped.
+async fn setup_postgres() -> Option<(tokio::sync::MutexGuard<'static, ()>, DatabaseConnection)> {
+    let Ok(url) = std::env::var(POSTGRES_URL_ENV) else {
+        assert!(
+            std::env::var_os(CI_ENV).is_none(),
+            "{POSTGRES_URL_ENV} must be set in CI"
+        );
+        eprintln!("Skipping: {POSTGRES_URL_ENV} is not set");
+        return None;
+    };
+    let guard = POSTGRES.lock().await;
+    let db = Database::connect(&url).await.unwrap();
+    db.execute_unprepared(
+        "TRUNCATE contests, contest_problem, problems, submissions, accepted_count, \
+         rated_point_sum, max_streaks, language_count, shortest, fastest, first, solver, \
+         points, merged_problems",
+    )
+    .await
+    .unwrap();
+    Some((guard, db))
+}
+
+/// Creates the dbt models as views in [`DBT_SCHEMA`], over the tables of
+/// `public` and a copy of the `excluded_users` seed.
+async fn create_dbt_models(db: &DatabaseConnection) {
+    db.execute_unprepared(&format!(
+        "DROP SCHEMA IF EXISTS {DBT_SCHEMA} CASCADE; CREATE SCHEMA {DBT_SCHEMA}; \
+         CREATE TABLE {DBT_SCHEMA}.excluded_users (user_id VARCHAR(255) NOT NULL)"
+    ))
+    .await
+    .unwrap();
+    let values: Vec<String> = EXCLUDED_USERS.iter().map(|u| format!("('{u}')")).collect();
+    db.execute_unprepared(&format!(
+        "INSERT INTO {DBT_SCHEMA}.excluded_users VALUES {}",
+        values.join(", ")
+    ))
+    .await
+    .unwrap();
+    for (dir, names) in [
+        (
+            "staging",
+            &[
+                "stg_contests",
+                "stg_contest_problem",
+                "stg_problems",
+                "stg_submissions",
+            ][..],
+        ),
+        (
+            "intermediate",
+            &["int_accepted_submissions", "int_rated_contests"][..],
+        ),
+        (
+            "marts",
+            &[
+                "accepted_count",
+                "rated_point_sum",
+                "max_streaks",
+                "language_count",
+                "shortest",
+                "fastest",
+                "first",
+                "solver",
+                "points",
+                "merged_problems",
+            ][..],
+        ),
+    ] {
+        for name in names {
+            create_dbt_view(db, dir, name).await;
+        }
+    }
+}
+
+/// Runs the dbt models themselves over the fixture and compares them with the
+/// aggregator; skipped unless `TEST_POSTGRES_URL` is set, which CI does.
+#[tokio::test]
+async fn test_marts_match_the_dbt_models_on_postgres() {
+    let Some((_guard, db)) = setup_postgres().await else {
+        return;
+    };
+    insert_fixture(&db).await;
+    create_dbt_models(&db).await;
+
+    let dbt = Marts {
+        accepted_count: dbt_mart(&db, accepted_count::Entity).await,
+        rated_point_sum: dbt_mart(&db, rated_point_sum::Entity).await,
+        max_streaks: dbt_mart(&db, max_streaks::Entity).await,
+        language_count: dbt_mart(&db, language_count::Entity).await,
+        shortest: dbt_mart(&db, shortest::Entity).await,
+        fastest: dbt_mart(&db, fastest::Entity).await,
+        first: dbt_mart(&db, first::Entity).await,
+        solver: dbt_mart(&db, solver::Entity).await,
+        points: dbt_mart(&db, points::Entity).await,
+        merged_problems: dbt_mart(&db, merged_problems::Entity).await,
+    }
+    .sorted();
+    db.execute_unprepared(&format!("DROP SCHEMA {DBT_SCHEMA} CASCADE"))
+        .await
+        .unwrap();
+
+    assert_eq!(dbt, expected_marts());
+    recompute_marts(&db, false).await.unwrap();
+    assert_eq!(stored_marts(&db).await, dbt);
+}
+
+/// Days are counted in JST whatever the time zone of the session running the
+/// model: `to_timestamp(..) at time zone 'UTC' at time zone 'Asia/Tokyo'`
+/// shifted them nine hours the wrong way, splitting this JST day in two.
+#[tokio::test]
+async fn test_max_streaks_model_counts_jst_days_in_any_session_time_zone() {
+    let Some((_guard, db)) = setup_postgres().await else {
+        return;
+    };
+    // 2024-01-02 00:30 and 23:30 JST, which are on two days in UTC
+    let first_ac = 1_704_123_000;
+    let last_ac = first_ac + 23 * HOUR;
+    submissions::Entity::insert_many(
+        [
+            submission(
+                1,
+                "alice",
+                "abc100_a",
+                "abc100",
+                "AC",
+                100.0,
+                10,
+                Some(1),
+                first_ac,
+                RUST,
+            ),
+            submission(
+                2,
+                "alice",
+                "abc100_b",
+                "abc100",
+                "AC",
+                200.0,
+                10,
+                Some(1),
+                last_ac,
+                RUST,
+            ),
+        ]
+        .map(IntoActiveModel::into_active_model),
+    )
+    .exec(&db)
+    .await
+    .unwrap();
+    create_dbt_models(&db).await;
+    let expected = vec![max_streaks::Model {
+        user_id: "alice".to_string(),
+        streak: 1,
+    }];
+
+    for time_zone in ["UTC", "Asia/Tokyo", "America/Los_Angeles"] {
+        let txn = db.begin().await.unwrap();
+        txn.execute_unprepared(&format!("SET LOCAL TIME ZONE '{time_zone}'"))
+            .await
+            .unwrap();
+        let streaks = max_streaks::Entity::find()
+            .from_raw_sql(Statement::from_string(
+                DatabaseBackend::Postgres,
+                format!("SELECT * FROM {DBT_SCHEMA}.max_streaks"),
+            ))
+            .all(&txn)
+            .await
+            .unwrap();
+        txn.rollback().await.unwrap();
+        assert_eq!(streaks, expected, "{time_zone}");
+    }
+    db.execute_unprepared(&format!("DROP SCHEMA {DBT_SCHEMA} CASCADE"))
+        .await
+        .unwrap();
+    assert_eq!(compute_marts(&db).await.unwrap().max_streaks, expected);
+}
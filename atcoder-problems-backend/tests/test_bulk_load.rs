@@ -0,0 +1,336 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use atcoder_problems_backend::bulk_load::{
+    DumpFormat, DumpReader, DumpRow, LoadError, Reject, load_submissions, validate_submission,
+};
+use crawler::Submission;
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, EntityTrait, Set};
+
+/// Postgres database with `postgres/schema.sql` applied. Its submissions are
+/// truncated, so never point it at a database whose data matters.
+const POSTGRES_URL_ENV: &str = "TEST_POSTGRES_URL";
+
+/// Set by CI, whose workflow provides the Postgres database.
+const CI_ENV: &str = "CI";
+
+fn submission(id: i64) -> Submission {
+    Submission {
+        id,
+        epoch_second: 1_700_000_000 + id,
+        problem_id: "abc001_a".to_string(),
+        contest_id: "abc001".to_string(),
+        user: "user".to_string(),
+        language: "Rust".to_string(),
+        score: 100.0,
+        code_length: 100,
+        result: "AC".to_string(),
+        execution_time: Some(1),
+        memory: Some(1024),
+    }
+}
+
+fn read_all(dump: &str, format: DumpFormat) -> Vec<DumpRow> {
+    let mut reader = DumpReader::new(dump.as_bytes(), format).unwrap();
+    let mut rows = vec![];
+    while let Some(row) = reader.next_row().unwrap() {
+        rows.push(row);
+    }
+    rows
+}
+
+fn reject(line: u64, reason: &str) -> Reject {
+    Reject {
+        line,
+        reason: reason.to_string(),
+    }
+}
+
+#[test]
+fn test_csv_dump_is_read_with_quoted_fields() {
+    let dump = "\
+id,epoch_second,problem_id,contest_id,user,language,score,code_length,result,execution_time,memory
+1,1700000001,abc001_a,abc001,user,Rust,100,100,AC,1,1024
+
+2,1700000002,abc001_a,abc001,user,\"C++, \"\"GCC\"\"
+ 12\",100,100,AC,,
+";
+    let rows = read_all(dump, DumpFormat::Csv);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0], (2, Ok(submission(1))));
+    assert_eq!(
+        rows[1],
+        (
+            4,
+            Ok(Submission {
+                language: "C++, \"GCC\"\n 12".to_string(),
+                execution_time: None,
+                memory: None,
+                ..submission(2)
+            })
+        )
+    );
+}
+
+#[test]
+fn test_csv_dump_accepts_the_columns_of_the_submissions_table() {
+    // As written by `\copy submissions TO 'dump.csv' CSV HEADER`, with the
+    // optional columns left out and the others in a different order.
+    let dump = "\
+user_id,id,epoch_second,problem_id,contest_id,language,point,length,result\r
+user,1,1700000001,abc001_a,abc001,Rust,100.0,100,AC\r
+";
+    let rows = read_all(dump, DumpFormat::Csv);
+    assert_eq!(
+        rows,
+        vec![(
+            2,
+            Ok(Submission {
+                execution_time: None,
+                memory: None,
+                ..submission(1)
+            })
+        )]
+    );
+}
+
+#[test]
+fn test_csv_dump_without_a_required_column_is_refused() {
+    let dump = "id,epoch_second,problem_id,contest_id,user,language,score,result\n";
+    let result = DumpReader::new(dump.as_bytes(), DumpFormat::Csv);
+    assert!(matches!(
+        result,
+        Err(LoadError::InvalidHeader(reason)) if reason == "missing column code_length"
+    ));
+}
+
+#[test]
+fn test_invalid_csv_rows_are_rejected_with_their_line() {
+    let dump = "\
+id,epoch_second,problem_id,contest_id,user,language,score,code_length,result
+x,1700000001,abc001_a,abc001,user,Rust,100,100,AC
+2,1700000002,abc001_a,abc001,user,Rust,100
+3,1700000003,abc001_a,abc001,user,Rust,100,100,
+4,1700000004,abc001_a,abc001,user,Rust,100,-100,AC
+5,1700000005,abc001_a,abc001,user,Rust,100,100,AC
+6,1700000006,abc001_a,abc001,user,\"Rust,100,100,AC
+";
+    let rows = read_all(dump, DumpFormat::Csv);
+    assert_eq!(
+        rows,
+        vec![
+            (2, Err(reject(2, "invalid id: \"x\""))),
+            (3, Err(reject(3, "expected at least 9 fields, got 7"))),
+            (4, Err(reject(4, "result is empty"))),
+            (
+                5,
+                Err(reject(5, "code_length must not be negative, got -100"))
+            ),
+            (
+                6,
+                Ok(Submission {
+                    execution_time: None,
+                    memory: None,
+                    ..submission(5)
+                })
+            ),
+            // An open quote runs to the end of the dump.
+            (7, Err(reject(7, "expected at least 9 fields, got 6"))),
+        ]
+    );
+}
+
+#[test]
+fn test_json_lines_dump_is_read_and_invalid_lines_are_rejected() {
+    let dump = r#"{"id":1,"epoch_second":1700000001,"problem_id":"abc001_a","contest_id":"abc001","user":"user","language":"Rust","score":100.0,"code_length":100,"result":"AC","execution_time":1,"memory":1024}
+
+{"id":2,"epoch_second":1700000002,"problem_id":"abc001_a","contest_id":"abc001","user_id":"user","language":"Rust","point":100.0,"length":100,"result":"AC","execution_time":null,"memory":null}
+{"id":3}
+{"id":4,"epoch_second":1700000004,"problem_id":"abc001_a","contest_id":"abc001","user":"user","language":"Rust","score":-1.0,"code_length":100,"result":"AC","execution_time":1,"memory":1024}
+"#;
+    let rows = read_all(dump, DumpFormat::Jsonl);
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0], (1, Ok(submission(1))));
+    assert_eq!(
+        rows[1],
+        (
+            3,
+            Ok(Submission {
+                execution_time: None,
+                memory: None,
+                ..submission(2)
+            })
+        )
+    );
+    let (line, Err(rejected)) = &rows[2] else {
+        panic!("{:?} was not rejected", rows[2]);
+    };
+    assert_eq!(*line, 4);
+    assert!(
+        rejected.reason.contains("missing field"),
+        "{}",
+        rejected.reason
+    );
+    assert_eq!(
+        rows[3],
+        (
+            5,
+            Err(reject(5, "score must be a non-negative number, got -1"))
+        )
+    );
+}
+
+#[test]
+fn test_validate_submission() {
+    assert_eq!(validate_submission(&submission(1)), Ok(()));
+    assert_eq!(
+        validate_submission(&submission(0)),
+        Err("id must be positive, got 0".to_string())
+    );
+    assert_eq!(
+        validate_submission(&Submission {
+            user: "a".repeat(256),
+            ..submission(1)
+        }),
+        Err("user is longer than 255 characters".to_string())
+    );
+    assert_eq!(
+        validate_submission(&Submission {
+            memory: Some(-1),
+            ..submission(1)
+        }),
+        Err("memory must not be negative, got -1".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_load_submissions_needs_postgres() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    let dump = DumpReader::new("".as_bytes(), DumpFormat::Jsonl).unwrap();
+    let result = load_submissions(&db, dump, 0, false).await;
+    assert!(matches!(result, Err(LoadError::UnsupportedBackend(_))));
+}
+
+async fn setup_postgres() -> Option<DatabaseConnection> {
+    let Ok(url) = std::env::var(POSTGRES_URL_ENV) else {
+        assert!(
+            std::env::var_os(CI_ENV).is_none(),
+            "{POSTGRES_URL_ENV} must be set in CI"
+        );
+        eprintln!("Skipping: {POSTGRES_URL_ENV} is not set");
+        return None;
+    };
+    let db = Database::connect(&url).await.unwrap();
+    db.execute_unprepared("TRUNCATE submissions, submission_history")
+        .await
+        .unwrap();
+    Some(db)
+}
+
+fn json_lines(submissions: &[Submission]) -> String {
+    submissions
+        .iter()
+        .map(|s| serde_json::to_string(s).unwrap() + "\n")
+        .collect()
+}
+
+async fn load(
+    db: &DatabaseConnection,
+    dump: &str,
+    max_rejects: usize,
+    dry_run: bool,
+) -> Result<atcoder_problems_backend::bulk_load::LoadReport, LoadError> {
+    let dump = DumpReader::new(dump.as_bytes(), DumpFormat::Jsonl).unwrap();
+    load_submissions(db, dump, max_rejects, dry_run).await
+}
+
+/// Exercises the merge on Postgres, where `COPY` is available; skipped unless
+/// `TEST_POSTGRES_URL` is set, which CI does.
+#[tokio::test]
+async fn test_load_submissions_merges_like_upsert_submissions() {
+    let Some(db) = setup_postgres().await else {
+        return;
+    };
+    for (id, result) in [(1, "WA"), (2, "3/20 WJ"), (3, "AC")] {
+        sql_entities::submissions::Entity::insert(sql_entities::submissions::ActiveModel {
+            id: Set(id),
+            epoch_second: Set(1_700_000_000 + id),
+            problem_id: Set("abc001_a".to_string()),
+            contest_id: Set("abc001".to_string()),
+            user_id: Set("user".to_string()),
+            language: Set("Rust".to_string()),
+            point: Set(100.0),
+            length: Set(100),
+            result: Set(result.to_string()),
+            execution_time: Set(Some(1)),
+            memory: Set(Some(1024)),
+        })
+        .exec(&db)
+        .await
+        .unwrap();
+    }
+
+    let dump = json_lines(&[
+        // Changed result
+        submission(1),
+        // Progress of the judge
+        Submission {
+            result: "5/20 WJ".to_string(),
+            ..submission(2)
+        },
+        // Unchanged
+        submission(3),
+        // New, listed twice
+        Submission {
+            result: "WA".to_string(),
+            ..submission(4)
+        },
+        submission(4),
+    ]) + "{}\n";
+
+    // A dry run reports the same but leaves nothing behind.
+    let report = load(&db, &dump, 10, true).await.unwrap();
+    assert_eq!((report.inserted, report.updated), (1, 2));
+    let stored = sql_entities::submissions::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 3);
+
+    let report = load(&db, &dump, 10, false).await.unwrap();
+    assert_eq!(report.rows_staged, 5);
+    assert_eq!(report.inserted, 1);
+    assert_eq!(report.updated, 2);
+    assert_eq!(report.rejects.len(), 1);
+    assert_eq!(report.rejects[0].line, 6);
+
+    let stored = sql_entities::submissions::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 4);
+    assert!(stored.iter().all(|s| s.id == 2 || s.result == "AC"));
+    let history = sql_entities::submission_history::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].submission_id, 1);
+    assert_eq!(history[0].old_result, "WA");
+    assert_eq!(history[0].new_result, "AC");
+
+    let report = load(&db, &dump, 10, false).await.unwrap();
+    assert_eq!((report.inserted, report.updated), (0, 0));
+
+    // Too many rejects abort the load and roll it back.
+    let dump = json_lines(&[submission(5)]) + "{}\n{}\n";
+    let result = load(&db, &dump, 1, false).await;
+    assert!(matches!(
+        result,
+        Err(LoadError::TooManyRejects { limit: 1, ref last }) if last.line == 3
+    ));
+    let stored = sql_entities::submissions::Entity::find_by_id(5)
+        .one(&db)
+        .await
+        .unwrap();
+    assert_eq!(stored, None);
+}
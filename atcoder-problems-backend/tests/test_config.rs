@@ -0,0 +1,123 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::collections::HashMap;
+
+use atcoder_problems_backend::config::{Config, ConfigError};
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn test_config_reads_toml_file() {
+    let config = Config::from_toml(
+        r#"
+database_url = "postgres://localhost/atcoder"
+
+[crawler]
+session = "session-cookie"
+requests_per_second = 0.5
+burst = 3
+
+[s3]
+bucket = "kenkoooo.com"
+
+[server]
+port = 9000
+client_id = "id"
+client_secret = "secret"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.database_url().unwrap(),
+        "postgres://localhost/atcoder"
+    );
+    assert_eq!(config.session().unwrap(), "session-cookie");
+    assert_eq!(config.bucket().unwrap(), "kenkoooo.com");
+    assert_eq!(config.server.port, 9000);
+    assert_eq!(config.client_id().unwrap(), "id");
+    assert_eq!(config.client_secret().unwrap(), "secret");
+    let rate_limit = config.rate_limit().unwrap();
+    assert_eq!(rate_limit.requests_per_second, 0.5);
+    assert_eq!(rate_limit.burst, 3);
+}
+
+#[test]
+fn test_config_environment_overrides_file() {
+    let mut config = Config::from_toml(
+        r#"
+database_url = "postgres://localhost/atcoder"
+
+[crawler]
+session = "old-cookie"
+requests_per_second = 0.5
+"#,
+    )
+    .unwrap();
+
+    config
+        .apply_env(env(&[
+            ("REVEL_SESSION", "new-cookie"),
+            ("CRAWLER_REQUESTS_PER_SECOND", "4"),
+            ("PORT", "3000"),
+        ]))
+        .unwrap();
+
+    assert_eq!(
+        config.database_url().unwrap(),
+        "postgres://localhost/atcoder"
+    );
+    assert_eq!(config.session().unwrap(), "new-cookie");
+    assert_eq!(config.rate_limit().unwrap().requests_per_second, 4.0);
+    assert_eq!(config.server.port, 3000);
+}
+
+#[test]
+fn test_config_defaults_and_missing_settings() {
+    let mut config = Config::from_toml("").unwrap();
+    config.apply_env(env(&[])).unwrap();
+
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.rate_limit().unwrap(), crawler::RateLimit::default());
+    assert!(matches!(
+        config.database_url(),
+        Err(ConfigError::Missing {
+            env: "DATABASE_URL",
+            ..
+        })
+    ));
+    assert!(matches!(
+        config.bucket(),
+        Err(ConfigError::Missing {
+            env: "S3_BUCKET_NAME",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_config_rejects_invalid_settings() {
+    assert!(matches!(
+        Config::from_toml("[crawler]\nsesion = \"typo\"\n"),
+        Err(ConfigError::Parse(_))
+    ));
+    assert!(matches!(
+        Config::default().apply_env(env(&[("PORT", "http")])),
+        Err(ConfigError::InvalidEnv { name: "PORT", .. })
+    ));
+
+    let config = Config::from_toml("[crawler]\nrequests_per_second = 0\n").unwrap();
+    assert!(matches!(
+        config.rate_limit(),
+        Err(ConfigError::InvalidValue {
+            key: "crawler.requests_per_second",
+            ..
+        })
+    ));
+}
@@ -0,0 +1,76 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use atcoder_problems_backend::crawler_utils::{self, Mode};
+use crawler::FixtureClient;
+use sea_orm::EntityTrait;
+
+/// Pages recorded from AtCoder with `--record`, replayed by the tests below
+fn snapshot() -> FixtureClient {
+    FixtureClient::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/snapshot"
+    ))
+}
+
+#[tokio::test]
+async fn test_crawl_contests_and_problems_from_snapshot() {
+    let db = common::setup_db().await;
+    let client = snapshot();
+
+    let contests = crawler_utils::crawl_contests(&client, &db).await.unwrap();
+    let problems = crawler_utils::crawl_problems(&client, &db).await.unwrap();
+
+    // 4 permanent contests and abc399 from the archive
+    assert_eq!(contests, 5);
+    let abc399 = sql_entities::contests::Entity::find_by_id("abc399")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(abc399.title, "AtCoder Beginner Contest 399");
+    assert_eq!(abc399.rated_lower, Some(0));
+    assert_eq!(abc399.rated_upper, Some(1999));
+    assert_eq!(abc399.contest_type, "abc");
+
+    // Only abc399 has a tasks page in the snapshot
+    assert_eq!(problems, 7);
+    let problem = sql_entities::problems::Entity::find_by_id("abc399_a")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(problem.title, "A. Hamming Distance");
+    assert_eq!(problem.time_limit_ms, Some(2000));
+    assert_eq!(problem.memory_limit_kb, Some(1024 * 1024));
+    assert_eq!(problem.point, Some(100.0));
+    let problem = sql_entities::problems::Entity::find_by_id("abc399_b")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(problem.point, None);
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_from_snapshot_stops_at_missing_page() {
+    let db = common::setup_db().await;
+    let client = snapshot();
+
+    let inserted = crawler_utils::crawl_submissions(&client, &db, "tenka1-2012-qualC", Mode::All)
+        .await
+        .unwrap();
+
+    assert_eq!(inserted, 20);
+    let submissions = sql_entities::submissions::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(submissions.len(), 20);
+    assert!(
+        submissions
+            .iter()
+            .all(|s| s.contest_id == "tenka1-2012-qualC")
+    );
+}
@@ -1,7 +1,12 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
 use async_trait::async_trait;
-use crawler::{Contest, ContestFetcher, CrawlerError, Problem, ProblemFetcher};
+use atcoder_problems_backend::crawler_utils::Mode;
+use crawler::{
+    Contest, ContestFetcher, CrawlerError, Editorial, EditorialFetcher, JudgeCase, Problem,
+    ProblemFetcher, RatingHistoryEntry, Standings, StandingsFetcher, Submission, SubmissionDetail,
+    SubmissionFetcher, UserFetcher, UserProfile,
+};
 use mockall::mock;
 use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbErr, EntityTrait, Schema, Set};
 
@@ -11,6 +16,11 @@ mock! {
     #[async_trait]
     impl ProblemFetcher for ProblemFetcher {
         async fn fetch_problems(&self, contest_id: &str) -> Result<Vec<Problem>, CrawlerError>;
+        async fn fetch_problem_point(
+            &self,
+            contest_id: &str,
+            problem_id: &str,
+        ) -> Result<Option<f64>, CrawlerError>;
     }
 }
 
@@ -29,6 +39,64 @@ mock! {
     }
 }
 
+mock! {
+    pub SubmissionFetcher {}
+
+    #[async_trait]
+    impl SubmissionFetcher for SubmissionFetcher {
+        async fn fetch_submissions(
+            &self,
+            contest_id: &str,
+            page: i32,
+        ) -> Result<Vec<Submission>, CrawlerError>;
+        async fn fetch_submission_detail(
+            &self,
+            contest_id: &str,
+            submission_id: i64,
+        ) -> Result<SubmissionDetail, CrawlerError>;
+        async fn fetch_user_submissions(
+            &self,
+            contest_id: &str,
+            user_id: &str,
+            page: i32,
+        ) -> Result<Vec<Submission>, CrawlerError>;
+    }
+}
+
+mock! {
+    pub StandingsFetcher {}
+
+    #[async_trait]
+    impl StandingsFetcher for StandingsFetcher {
+        async fn fetch_standings(
+            &self,
+            contest_id: &str,
+        ) -> Result<Option<Standings>, CrawlerError>;
+    }
+}
+
+mock! {
+    pub UserFetcher {}
+
+    #[async_trait]
+    impl UserFetcher for UserFetcher {
+        async fn fetch_user_profile(&self, user_id: &str) -> Result<UserProfile, CrawlerError>;
+        async fn fetch_rating_history(
+            &self,
+            user_id: &str,
+        ) -> Result<Vec<RatingHistoryEntry>, CrawlerError>;
+    }
+}
+
+mock! {
+    pub EditorialFetcher {}
+
+    #[async_trait]
+    impl EditorialFetcher for EditorialFetcher {
+        async fn fetch_editorials(&self, contest_id: &str) -> Result<Vec<Editorial>, CrawlerError>;
+    }
+}
+
 async fn setup_db() -> Result<DatabaseConnection, DbErr> {
     let db = Database::connect("sqlite::memory:").await?;
     let builder = db.get_database_backend();
@@ -46,6 +114,66 @@ async fn setup_db() -> Result<DatabaseConnection, DbErr> {
     let stmt = schema.create_table_from_entity(sql_entities::contest_problem::Entity);
     db.execute(builder.build(&stmt)).await?;
 
+    // Create submissions table
+    let stmt = schema.create_table_from_entity(sql_entities::submissions::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create solver table
+    let stmt = schema.create_table_from_entity(sql_entities::solver::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create submission_judge_cases table
+    let stmt = schema.create_table_from_entity(sql_entities::submission_judge_cases::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create submission_details table
+    let stmt = schema.create_table_from_entity(sql_entities::submission_details::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create submission_history table
+    let stmt = schema.create_table_from_entity(sql_entities::submission_history::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create crawl_state table
+    let stmt = schema.create_table_from_entity(sql_entities::crawl_state::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create user_crawl_jobs table
+    let stmt = schema.create_table_from_entity(sql_entities::user_crawl_jobs::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create crawl_queue table
+    let stmt = schema.create_table_from_entity(sql_entities::crawl_queue::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create internal_users table
+    let stmt = schema.create_table_from_entity(sql_entities::internal_users::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create user_profiles table
+    let stmt = schema.create_table_from_entity(sql_entities::user_profiles::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create user_rating_history table
+    let stmt = schema.create_table_from_entity(sql_entities::user_rating_history::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create problem_editorials table
+    let stmt = schema.create_table_from_entity(sql_entities::problem_editorials::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create editorial_crawls table
+    let stmt = schema.create_table_from_entity(sql_entities::editorial_crawls::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create crawl_runs table
+    let stmt = schema.create_table_from_entity(sql_entities::crawl_runs::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
+    // Create crawl_run_contests table
+    let stmt = schema.create_table_from_entity(sql_entities::crawl_run_contests::Entity);
+    db.execute(builder.build(&stmt)).await?;
+
     Ok(db)
 }
 
@@ -61,6 +189,9 @@ async fn test_crawl_problems_inserts_problems_for_contests_without_problems() {
             duration_second: Set(0),
             title: Set("Test Contest".to_string()),
             rate_change: Set("-".to_string()),
+            rated_lower: Set(None),
+            rated_upper: Set(None),
+            contest_type: Set("other".to_string()),
         })
         .exec(&db)
         .await
@@ -74,6 +205,10 @@ async fn test_crawl_problems_inserts_problems_for_contests_without_problems() {
         problem_index: Set("A".to_string()),
         name: Set("Existing Problem".to_string()),
         title: Set("A. Existing Problem".to_string()),
+        time_limit_ms: Set(None),
+        memory_limit_kb: Set(None),
+        point: Set(None),
+        orphaned_epoch_second: Set(None),
     })
     .exec(&db)
     .await
@@ -103,12 +238,18 @@ async fn test_crawl_problems_inserts_problems_for_contests_without_problems() {
                     contest_id: "abc002".to_string(),
                     problem_index: "A".to_string(),
                     name: "Problem A".to_string(),
+                    time_limit_ms: None,
+                    memory_limit_kb: None,
+                    point: None,
                 },
                 Problem {
                     id: "abc002_b".to_string(),
                     contest_id: "abc002".to_string(),
                     problem_index: "B".to_string(),
                     name: "Problem B".to_string(),
+                    time_limit_ms: None,
+                    memory_limit_kb: None,
+                    point: None,
                 },
             ])
         });
@@ -123,9 +264,17 @@ async fn test_crawl_problems_inserts_problems_for_contests_without_problems() {
                 contest_id: "abc003".to_string(),
                 problem_index: "A".to_string(),
                 name: "Problem A".to_string(),
+                time_limit_ms: None,
+                memory_limit_kb: None,
+                point: None,
             }])
         });
 
+    mock_fetcher
+        .expect_fetch_problem_point()
+        .times(3)
+        .returning(|_, _| Ok(None));
+
     // Run the crawl
     let inserted = atcoder_problems_backend::crawler_utils::crawl_problems(&mock_fetcher, &db)
         .await
@@ -186,6 +335,9 @@ async fn test_crawl_problems_skips_contests_with_existing_problems() {
         duration_second: Set(0),
         title: Set("Test Contest".to_string()),
         rate_change: Set("-".to_string()),
+        rated_lower: Set(None),
+        rated_upper: Set(None),
+        contest_type: Set("other".to_string()),
     })
     .exec(&db)
     .await
@@ -197,6 +349,10 @@ async fn test_crawl_problems_skips_contests_with_existing_problems() {
         problem_index: Set("A".to_string()),
         name: Set("Existing Problem".to_string()),
         title: Set("A. Existing Problem".to_string()),
+        time_limit_ms: Set(None),
+        memory_limit_kb: Set(None),
+        point: Set(None),
+        orphaned_epoch_second: Set(None),
     })
     .exec(&db)
     .await
@@ -234,6 +390,9 @@ async fn test_crawl_problems_handles_empty_response() {
         duration_second: Set(0),
         title: Set("Test Contest".to_string()),
         rate_change: Set("-".to_string()),
+        rated_lower: Set(None),
+        rated_upper: Set(None),
+        contest_type: Set("other".to_string()),
     })
     .exec(&db)
     .await
@@ -272,6 +431,9 @@ async fn test_crawl_problems_generates_correct_title() {
         duration_second: Set(0),
         title: Set("Test Contest".to_string()),
         rate_change: Set("-".to_string()),
+        rated_lower: Set(None),
+        rated_upper: Set(None),
+        contest_type: Set("other".to_string()),
     })
     .exec(&db)
     .await
@@ -288,9 +450,17 @@ async fn test_crawl_problems_generates_correct_title() {
                 contest_id: "abc001".to_string(),
                 problem_index: "A".to_string(),
                 name: "Test Problem".to_string(),
+                time_limit_ms: None,
+                memory_limit_kb: None,
+                point: None,
             }])
         });
 
+    mock_fetcher
+        .expect_fetch_problem_point()
+        .times(1)
+        .returning(|_, _| Ok(None));
+
     // Run the crawl
     atcoder_problems_backend::crawler_utils::crawl_problems(&mock_fetcher, &db)
         .await
@@ -321,6 +491,86 @@ async fn test_crawl_problems_generates_correct_title() {
     assert_eq!(contest_problem.problem_index, "A");
 }
 
+#[tokio::test]
+async fn test_crawl_problems_stores_limits_and_points() {
+    let db = setup_db().await.unwrap();
+
+    sql_entities::contests::Entity::insert(sql_entities::contests::ActiveModel {
+        id: Set("abc001".to_string()),
+        start_epoch_second: Set(0),
+        duration_second: Set(0),
+        title: Set("Test Contest".to_string()),
+        rate_change: Set("-".to_string()),
+        rated_lower: Set(None),
+        rated_upper: Set(None),
+        contest_type: Set("other".to_string()),
+    })
+    .exec(&db)
+    .await
+    .unwrap();
+
+    let mut mock_fetcher = MockProblemFetcher::new();
+    mock_fetcher
+        .expect_fetch_problems()
+        .times(1)
+        .returning(|_| {
+            Ok(vec![
+                Problem {
+                    id: "abc001_a".to_string(),
+                    contest_id: "abc001".to_string(),
+                    problem_index: "A".to_string(),
+                    name: "Problem A".to_string(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(1_048_576),
+                    point: None,
+                },
+                Problem {
+                    id: "abc001_b".to_string(),
+                    contest_id: "abc001".to_string(),
+                    problem_index: "B".to_string(),
+                    name: "Problem B".to_string(),
+                    time_limit_ms: Some(3000),
+                    memory_limit_kb: Some(262_144),
+                    point: None,
+                },
+            ])
+        });
+    mock_fetcher
+        .expect_fetch_problem_point()
+        .withf(|contest_id, problem_id| contest_id == "abc001" && problem_id == "abc001_a")
+        .times(1)
+        .returning(|_, _| Ok(Some(100.0)));
+    // A failing task page leaves the point empty instead of dropping the problem.
+    mock_fetcher
+        .expect_fetch_problem_point()
+        .withf(|contest_id, problem_id| contest_id == "abc001" && problem_id == "abc001_b")
+        .times(1)
+        .returning(|_, _| Err(CrawlerError::HttpError("503".to_string())));
+
+    let inserted = atcoder_problems_backend::crawler_utils::crawl_problems(&mock_fetcher, &db)
+        .await
+        .unwrap();
+    assert_eq!(inserted, 2);
+
+    let a = sql_entities::problems::Entity::find_by_id("abc001_a")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(a.time_limit_ms, Some(2000));
+    assert_eq!(a.memory_limit_kb, Some(1_048_576));
+    assert_eq!(a.point, Some(100.0));
+
+    let b = sql_entities::problems::Entity::find_by_id("abc001_b")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(b.time_limit_ms, Some(3000));
+    assert_eq!(b.memory_limit_kb, Some(262_144));
+    assert_eq!(b.point, None);
+}
+
 #[tokio::test]
 async fn test_crawl_contests_fetches_filtered_archive_categories() {
     let db = setup_db().await.unwrap();
@@ -402,3 +652,2218 @@ async fn test_crawl_contests_fetches_filtered_archive_categories() {
             .any(|contest| contest.id == "adt_all_20260612_2")
     );
 }
+
+#[tokio::test]
+async fn test_crawl_contests_fails_when_a_page_gives_up() {
+    let db = setup_db().await.unwrap();
+    let mut mock_fetcher = MockContestFetcher::new();
+
+    mock_fetcher
+        .expect_fetch_permanent_contests()
+        .times(1)
+        .returning(|| Ok(vec![]));
+    mock_fetcher
+        .expect_fetch_contests()
+        .with(mockall::predicate::eq(1))
+        .times(1)
+        .returning(|_| {
+            Err(CrawlerError::RetriesExhausted {
+                attempts: 10,
+                last: Box::new(CrawlerError::HttpError("503".to_string())),
+            })
+        });
+
+    let result = atcoder_problems_backend::crawler_utils::crawl_contests(&mock_fetcher, &db).await;
+
+    assert!(matches!(
+        result,
+        Err(
+            atcoder_problems_backend::crawler_utils::CrawlError::Crawler(
+                CrawlerError::RetriesExhausted { .. }
+            )
+        )
+    ));
+    let contests = sql_entities::contests::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert!(contests.is_empty());
+}
+
+async fn insert_problem(db: &DatabaseConnection, contest_id: &str, index: &str, name: &str) {
+    let id = format!("{}_{}", contest_id, index.to_lowercase());
+    sql_entities::problems::Entity::insert(sql_entities::problems::ActiveModel {
+        id: Set(id.clone()),
+        contest_id: Set(contest_id.to_string()),
+        problem_index: Set(index.to_string()),
+        name: Set(name.to_string()),
+        title: Set(format!("{}. {}", index, name)),
+        time_limit_ms: Set(None),
+        memory_limit_kb: Set(None),
+        point: Set(Some(100.0)),
+        orphaned_epoch_second: Set(None),
+    })
+    .exec(db)
+    .await
+    .unwrap();
+    sql_entities::contest_problem::Entity::insert(sql_entities::contest_problem::ActiveModel {
+        contest_id: Set(contest_id.to_string()),
+        problem_id: Set(id),
+        problem_index: Set(index.to_string()),
+    })
+    .exec(db)
+    .await
+    .unwrap();
+}
+
+fn fetched_problem(id: &str, index: &str, name: &str) -> Problem {
+    Problem {
+        id: id.to_string(),
+        contest_id: "abc001".to_string(),
+        problem_index: index.to_string(),
+        name: name.to_string(),
+        time_limit_ms: None,
+        memory_limit_kb: None,
+        point: None,
+    }
+}
+
+#[tokio::test]
+async fn test_crawl_problems_keeps_known_point_when_task_page_fails() {
+    let db = setup_db().await.unwrap();
+    insert_contest(&db, "abc001").await;
+    insert_contest(&db, "arc001").await;
+    // Shared with abc001, whose problems are not crawled yet
+    insert_problem(&db, "arc001", "A", "Shared").await;
+    sql_entities::problems::Entity::update_many()
+        .col_expr(
+            sql_entities::problems::Column::Point,
+            sea_orm::sea_query::Expr::value(200.0),
+        )
+        .exec(&db)
+        .await
+        .unwrap();
+
+    let mut mock_fetcher = MockProblemFetcher::new();
+    mock_fetcher
+        .expect_fetch_problems()
+        .with(mockall::predicate::eq("abc001"))
+        .times(1)
+        .returning(|_| Ok(vec![fetched_problem("arc001_a", "C", "Shared")]));
+    mock_fetcher
+        .expect_fetch_problem_point()
+        .times(1)
+        .returning(|_, _| Err(CrawlerError::HttpError("503".to_string())));
+
+    atcoder_problems_backend::crawler_utils::crawl_problems(&mock_fetcher, &db)
+        .await
+        .unwrap();
+
+    let shared = sql_entities::problems::Entity::find_by_id("arc001_a")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shared.point, Some(200.0));
+}
+
+#[tokio::test]
+async fn test_reconcile_problems_applies_and_logs_changes() {
+    use atcoder_problems_backend::crawler_utils::{ChangeEntity, ChangeKind};
+
+    let db = setup_db().await.unwrap();
+    insert_contest(&db, "abc001").await;
+    insert_problem(&db, "abc001", "A", "Old Name").await;
+    insert_problem(&db, "abc001", "B", "Dropped").await;
+    insert_problem(&db, "abc001", "C", "Moved").await;
+
+    let mut mock_fetcher = MockProblemFetcher::new();
+    mock_fetcher
+        .expect_fetch_problems()
+        .with(mockall::predicate::eq("abc001"))
+        .times(1)
+        .returning(|_| {
+            Ok(vec![
+                fetched_problem("abc001_a", "A", "New Name"),
+                fetched_problem("abc001_c", "B", "Moved"),
+                fetched_problem("abc001_d", "C", "Late"),
+            ])
+        });
+    // Only the new problem needs its task page.
+    mock_fetcher
+        .expect_fetch_problem_point()
+        .withf(|_, problem_id| problem_id == "abc001_d")
+        .times(1)
+        .returning(|_, _| Ok(Some(300.0)));
+
+    let changes =
+        atcoder_problems_backend::crawler_utils::reconcile_problems(&mock_fetcher, &db, None)
+            .await
+            .unwrap();
+
+    let summary: Vec<(&str, ChangeKind)> = changes
+        .iter()
+        .map(|change| {
+            assert_eq!(change.entity, ChangeEntity::Problem);
+            assert_eq!(change.contest_id, "abc001");
+            (change.id.as_str(), change.kind.clone())
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "abc001_a",
+                ChangeKind::Renamed {
+                    old: "Old Name".to_string(),
+                    new: "New Name".to_string()
+                }
+            ),
+            (
+                "abc001_c",
+                ChangeKind::Reindexed {
+                    old: "C".to_string(),
+                    new: "B".to_string()
+                }
+            ),
+            ("abc001_d", ChangeKind::Added),
+            ("abc001_b", ChangeKind::Removed),
+            ("abc001_b", ChangeKind::Orphaned),
+        ]
+    );
+
+    let a = sql_entities::problems::Entity::find_by_id("abc001_a")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(a.title, "A. New Name");
+    assert_eq!(a.point, Some(100.0));
+    let b = sql_entities::problems::Entity::find_by_id("abc001_b")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(b.orphaned_epoch_second.is_some());
+    let d = sql_entities::problems::Entity::find_by_id("abc001_d")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(d.point, Some(300.0));
+
+    let mut entries: Vec<(String, String)> = sql_entities::contest_problem::Entity::find()
+        .all(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|cp| (cp.problem_id, cp.problem_index))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("abc001_a".to_string(), "A".to_string()),
+            ("abc001_c".to_string(), "B".to_string()),
+            ("abc001_d".to_string(), "C".to_string()),
+        ]
+    );
+
+    // A second run finds nothing new and does not report the orphan again.
+    let mut mock_fetcher = MockProblemFetcher::new();
+    mock_fetcher
+        .expect_fetch_problems()
+        .times(1)
+        .returning(|_| {
+            Ok(vec![
+                fetched_problem("abc001_a", "A", "New Name"),
+                fetched_problem("abc001_c", "B", "Moved"),
+                fetched_problem("abc001_d", "C", "Late"),
+            ])
+        });
+    let changes =
+        atcoder_problems_backend::crawler_utils::reconcile_problems(&mock_fetcher, &db, Some(1))
+            .await
+            .unwrap();
+    assert!(changes.is_empty());
+}
+
+#[tokio::test]
+async fn test_reconcile_problems_skips_contests_whose_tasks_page_is_gone() {
+    let db = setup_db().await.unwrap();
+    insert_contest(&db, "abc001").await;
+    insert_problem(&db, "abc001", "A", "Kept").await;
+
+    let mut mock_fetcher = MockProblemFetcher::new();
+    mock_fetcher
+        .expect_fetch_problems()
+        .times(1)
+        .returning(|_| Err(CrawlerError::NotFound));
+
+    let changes =
+        atcoder_problems_backend::crawler_utils::reconcile_problems(&mock_fetcher, &db, None)
+            .await
+            .unwrap();
+
+    assert!(changes.is_empty());
+    assert_eq!(
+        sql_entities::contest_problem::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+/// Returns a fetcher whose archive lists `ids`, renamed to "Renamed <id>".
+fn reconciled_archive_fetcher(ids: Vec<String>) -> MockContestFetcher {
+    let mut mock_fetcher = MockContestFetcher::new();
+    mock_fetcher
+        .expect_fetch_permanent_contests()
+        .times(1)
+        .returning(|| Ok(vec![]));
+    mock_fetcher
+        .expect_fetch_contests()
+        .with(mockall::predicate::eq(1))
+        .times(1)
+        .returning(move |_| {
+            Ok(ids
+                .iter()
+                .map(|id| Contest {
+                    id: id.to_string(),
+                    start_epoch_second: 0,
+                    duration_second: 6000,
+                    title: format!("Renamed {}", id),
+                    rate_change: "-".to_string(),
+                })
+                .collect())
+        });
+    mock_fetcher
+        .expect_fetch_contests()
+        .with(mockall::predicate::eq(2))
+        .times(1)
+        .returning(|_| Ok(vec![]));
+    mock_fetcher
+        .expect_fetch_contests_in_category()
+        .returning(|_, _| Ok(vec![]));
+    mock_fetcher
+}
+
+async fn stored_contest_ids(db: &DatabaseConnection) -> Vec<String> {
+    let mut ids: Vec<String> = sql_entities::contests::Entity::find()
+        .all(db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|contest| contest.id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn test_reconcile_contests_removes_contests_missing_from_the_archive() {
+    use atcoder_problems_backend::crawler_utils::{
+        Change, ChangeEntity, ChangeKind, reconcile_contests,
+    };
+
+    let db = setup_db().await.unwrap();
+    let ids: Vec<String> = (1..=10).map(|i| format!("abc{:03}", i)).collect();
+    for id in &ids {
+        insert_contest(&db, id).await;
+    }
+    insert_problem(&db, "abc002", "A", "Gone").await;
+    let archive: Vec<String> = ids
+        .iter()
+        .filter(|id| *id != "abc002")
+        .cloned()
+        .chain(["abc011".to_string()])
+        .collect();
+
+    // Without --prune the missing contest is only reported.
+    let fetcher = reconciled_archive_fetcher(archive.clone());
+    let changes = reconcile_contests(&fetcher, &db, false).await.unwrap();
+    assert!(changes.contains(&Change {
+        entity: ChangeEntity::Contest,
+        id: "abc002".to_string(),
+        contest_id: "abc002".to_string(),
+        kind: ChangeKind::Removed,
+    }));
+    assert!(
+        stored_contest_ids(&db)
+            .await
+            .contains(&"abc002".to_string())
+    );
+
+    let fetcher = reconciled_archive_fetcher(archive.clone());
+    let changes = reconcile_contests(&fetcher, &db, true).await.unwrap();
+
+    let summary: Vec<(ChangeEntity, &str, ChangeKind)> = changes
+        .iter()
+        .map(|change| (change.entity, change.id.as_str(), change.kind.clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (ChangeEntity::Contest, "abc002", ChangeKind::Removed),
+            (ChangeEntity::Problem, "abc002_a", ChangeKind::Orphaned),
+        ]
+    );
+    assert_eq!(stored_contest_ids(&db).await, archive);
+    assert!(
+        sql_entities::contest_problem::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_reconcile_contests_keeps_contests_when_too_many_are_missing() {
+    use atcoder_problems_backend::crawler_utils::reconcile_contests;
+
+    let db = setup_db().await.unwrap();
+    let ids: Vec<String> = (1..=10).map(|i| format!("abc{:03}", i)).collect();
+    for id in &ids {
+        insert_contest(&db, id).await;
+    }
+
+    // An archive cut short after its first few contests.
+    let fetcher = reconciled_archive_fetcher(ids[..5].to_vec());
+    let changes = reconcile_contests(&fetcher, &db, true).await.unwrap();
+
+    assert_eq!(changes.len(), 10);
+    assert_eq!(stored_contest_ids(&db).await, ids);
+}
+
+fn submission(id: i64) -> Submission {
+    Submission {
+        id,
+        epoch_second: 1_700_000_000 + id,
+        problem_id: "abc001_a".to_string(),
+        contest_id: "abc001".to_string(),
+        user: "user".to_string(),
+        language: "Rust".to_string(),
+        score: 100.0,
+        code_length: 100,
+        result: "AC".to_string(),
+        execution_time: Some(1),
+        memory: Some(1024),
+    }
+}
+
+/// Returns submission pages of two submissions each, as if the contest had
+/// `page_count` pages in total.
+fn paged_submissions(page: i32, page_count: i32) -> Vec<Submission> {
+    if page > page_count {
+        return vec![];
+    }
+    let base = i64::from(page) * 10;
+    vec![submission(base), submission(base + 1)]
+}
+
+/// Returns submission pages of two submissions each, newest first like
+/// AtCoder, as if the contest had `page_count` pages in total.
+fn newest_first_submissions(page: i32, page_count: i32) -> Vec<Submission> {
+    if page > page_count {
+        return vec![];
+    }
+    let base = i64::from(page_count - page + 1) * 10;
+    vec![submission(base + 1), submission(base)]
+}
+
+async fn crawl_state(
+    db: &DatabaseConnection,
+    contest_id: &str,
+) -> sql_entities::crawl_state::Model {
+    sql_entities::crawl_state::Entity::find_by_id(contest_id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_paginates_until_empty_page() {
+    let db = setup_db().await.unwrap();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .withf(|contest_id, _| contest_id == "abc001")
+        .times(4)
+        .returning(|_, page| Ok(paged_submissions(page, 3)));
+
+    let inserted = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::All,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(inserted, 6);
+    let submissions = sql_entities::submissions::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(submissions.len(), 6);
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_new_mode_stops_after_unchanged_pages() {
+    let db = setup_db().await.unwrap();
+    // Store every page up front so that no page changes anything.
+    for page in 1..=10 {
+        atcoder_problems_backend::crawler_utils::upsert_submissions(
+            &db,
+            paged_submissions(page, 10),
+        )
+        .await
+        .unwrap();
+    }
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(5)
+        .returning(|_, page| Ok(paged_submissions(page, 10)));
+
+    let inserted = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::New,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(inserted, 0);
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_all_mode_does_not_stop_on_unchanged_pages() {
+    let db = setup_db().await.unwrap();
+    for page in 1..=7 {
+        atcoder_problems_backend::crawler_utils::upsert_submissions(
+            &db,
+            paged_submissions(page, 7),
+        )
+        .await
+        .unwrap();
+    }
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(9)
+        .returning(|_, page| Ok(paged_submissions(page, 8)));
+
+    let inserted = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::All,
+    )
+    .await
+    .unwrap();
+
+    // Only the 8th page is new.
+    assert_eq!(inserted, 2);
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_fails_instead_of_stopping_on_error() {
+    let db = setup_db().await.unwrap();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .returning(|_, page| match page {
+            1 => Ok(paged_submissions(page, 3)),
+            _ => Err(CrawlerError::RetriesExhausted {
+                attempts: 10,
+                last: Box::new(CrawlerError::HttpError("503".to_string())),
+            }),
+        });
+
+    let result = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::All,
+    )
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_treats_missing_page_as_end() {
+    let db = setup_db().await.unwrap();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(1)
+        .returning(|_, _| Err(CrawlerError::NotFound));
+
+    let inserted = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::All,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(inserted, 0);
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_new_mode_stops_at_known_submissions() {
+    let db = setup_db().await.unwrap();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(4)
+        .returning(|_, page| Ok(newest_first_submissions(page, 3)));
+    atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::All,
+    )
+    .await
+    .unwrap();
+    let state = crawl_state(&db, "abc001").await;
+    assert_eq!(state.max_submission_id, Some(31));
+    assert_eq!(state.last_crawled_page, 0);
+    assert!(state.last_crawled_epoch_second.is_some());
+
+    // Two new pages were added in front of the three known ones.
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(3)
+        .returning(|_, page| Ok(newest_first_submissions(page, 5)));
+    let inserted = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::New,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(inserted, 4);
+    assert_eq!(crawl_state(&db, "abc001").await.max_submission_id, Some(51));
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_all_mode_resumes_interrupted_crawl() {
+    let db = setup_db().await.unwrap();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .returning(|_, page| match page {
+            1 | 2 => Ok(newest_first_submissions(page, 4)),
+            _ => Err(CrawlerError::HttpError("503".to_string())),
+        });
+    let result = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::All,
+    )
+    .await;
+    assert!(result.is_err());
+    let state = crawl_state(&db, "abc001").await;
+    assert_eq!(state.last_crawled_page, 2);
+    assert_eq!(state.max_submission_id, None);
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .withf(|_, page| *page >= 3)
+        .times(3)
+        .returning(|_, page| Ok(newest_first_submissions(page, 4)));
+    let inserted = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::All,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(inserted, 4);
+    let state = crawl_state(&db, "abc001").await;
+    assert_eq!(state.last_crawled_page, 0);
+    assert_eq!(state.max_submission_id, Some(21));
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_new_mode_keeps_interrupted_full_crawl() {
+    let db = setup_db().await.unwrap();
+    sql_entities::crawl_state::Entity::insert(sql_entities::crawl_state::ActiveModel {
+        contest_id: Set("abc001".to_string()),
+        max_submission_id: Set(Some(31)),
+        last_crawled_page: Set(2),
+        last_crawled_epoch_second: Set(None),
+    })
+    .exec(&db)
+    .await
+    .unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(1)
+        .returning(|_, page| Ok(newest_first_submissions(page, 3)));
+    atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::New,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(crawl_state(&db, "abc001").await.last_crawled_page, 2);
+}
+
+#[tokio::test]
+async fn test_contest_ids_for_full_crawl_puts_interrupted_and_stale_contests_first() {
+    let db = setup_db().await.unwrap();
+    for id in ["abc001", "abc002", "abc003", "abc004"] {
+        sql_entities::contests::Entity::insert(sql_entities::contests::ActiveModel {
+            id: Set(id.to_string()),
+            start_epoch_second: Set(0),
+            duration_second: Set(0),
+            title: Set(id.to_string()),
+            rate_change: Set("-".to_string()),
+            rated_lower: Set(None),
+            rated_upper: Set(None),
+            contest_type: Set("other".to_string()),
+        })
+        .exec(&db)
+        .await
+        .unwrap();
+    }
+    for (contest_id, last_crawled_page, last_crawled_epoch_second) in [
+        ("abc001", 0, Some(200)),
+        ("abc002", 3, Some(300)),
+        ("abc003", 0, Some(100)),
+    ] {
+        sql_entities::crawl_state::Entity::insert(sql_entities::crawl_state::ActiveModel {
+            contest_id: Set(contest_id.to_string()),
+            max_submission_id: Set(None),
+            last_crawled_page: Set(last_crawled_page),
+            last_crawled_epoch_second: Set(last_crawled_epoch_second),
+        })
+        .exec(&db)
+        .await
+        .unwrap();
+    }
+
+    let contest_ids = atcoder_problems_backend::crawler_utils::contest_ids_for_full_crawl(&db)
+        .await
+        .unwrap();
+
+    assert_eq!(contest_ids, ["abc002", "abc004", "abc003", "abc001"]);
+}
+
+fn with_result(id: i64, result: &str, score: f64) -> Submission {
+    Submission {
+        result: result.to_string(),
+        score,
+        ..submission(id)
+    }
+}
+
+#[tokio::test]
+async fn test_upsert_submissions_records_result_changes() {
+    let db = setup_db().await.unwrap();
+    let upsert =
+        |submissions| atcoder_problems_backend::crawler_utils::upsert_submissions(&db, submissions);
+
+    upsert(vec![
+        with_result(1, "WA", 0.0),
+        with_result(2, "3/20 WJ", 0.0),
+    ])
+    .await
+    .unwrap();
+    upsert(vec![
+        with_result(1, "AC", 100.0),
+        with_result(2, "5/20 WJ", 0.0),
+    ])
+    .await
+    .unwrap();
+
+    let history = sql_entities::submission_history::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].submission_id, 1);
+    assert_eq!(history[0].old_result, "WA");
+    assert_eq!(history[0].new_result, "AC");
+    assert_eq!(history[0].old_point, 0.0);
+    assert_eq!(history[0].new_point, 100.0);
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_pending_mode_refreshes_until_oldest_pending() {
+    let db = setup_db().await.unwrap();
+    // Page 2 of 4 holds submissions 30 and 31, where 30 was still judging.
+    let mut stored = (1..=4)
+        .flat_map(|page| newest_first_submissions(page, 4))
+        .collect::<Vec<_>>();
+    for submission in &mut stored {
+        if submission.id == 30 {
+            submission.result = "WJ".to_string();
+        }
+    }
+    atcoder_problems_backend::crawler_utils::upsert_submissions(&db, stored)
+        .await
+        .unwrap();
+    assert_eq!(
+        atcoder_problems_backend::crawler_utils::contest_ids_with_pending_submissions(&db)
+            .await
+            .unwrap(),
+        ["abc001"]
+    );
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(2)
+        .returning(|_, page| Ok(newest_first_submissions(page, 4)));
+    let updated = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::Pending,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(updated, 1);
+    assert!(
+        atcoder_problems_backend::crawler_utils::contest_ids_with_pending_submissions(&db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let history = sql_entities::submission_history::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].new_result, "AC");
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_pending_mode_skips_settled_contests() {
+    let db = setup_db().await.unwrap();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher.expect_fetch_submissions().times(0);
+
+    let updated = atcoder_problems_backend::crawler_utils::crawl_submissions(
+        &mock_fetcher,
+        &db,
+        "abc001",
+        Mode::Pending,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(updated, 0);
+}
+
+fn contest(
+    id: &str,
+    start_epoch_second: i64,
+    duration_second: i64,
+) -> sql_entities::contests::Model {
+    sql_entities::contests::Model {
+        id: id.to_string(),
+        start_epoch_second,
+        duration_second,
+        title: id.to_string(),
+        rate_change: "-".to_string(),
+        rated_lower: None,
+        rated_upper: None,
+        contest_type: "other".to_string(),
+    }
+}
+
+async fn insert_contest(db: &DatabaseConnection, id: &str) {
+    let model: sql_entities::contests::ActiveModel = contest(id, 0, 6000).into();
+    sql_entities::contests::Entity::insert(model)
+        .exec(db)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_crawl_priority_prefers_live_interrupted_and_stale_contests() {
+    use atcoder_problems_backend::crawler_utils::crawl_priority;
+    const DAY: i64 = 24 * 3600;
+    let now = 1_000 * DAY;
+    let state = |last_crawled_page, last_crawled_epoch_second| sql_entities::crawl_state::Model {
+        contest_id: String::new(),
+        max_submission_id: None,
+        last_crawled_page,
+        last_crawled_epoch_second: Some(last_crawled_epoch_second),
+    };
+
+    let live = crawl_priority(&contest("abc400", now - 600, 6000), None, now);
+    let interrupted = crawl_priority(&contest("abc001", 0, 6000), Some(&state(5, now)), now);
+    let just_ended = crawl_priority(
+        &contest("abc399", now - 2 * DAY, 6000),
+        Some(&state(0, now - DAY)),
+        now,
+    );
+    let stale = crawl_priority(
+        &contest("abc002", 0, 6000),
+        Some(&state(0, now - 10 * DAY)),
+        now,
+    );
+    let fresh = crawl_priority(&contest("abc003", 0, 6000), Some(&state(0, now - DAY)), now);
+    let practice = crawl_priority(&contest("practice", 0, 100 * 365 * DAY), None, now);
+
+    assert!(live > interrupted);
+    assert!(interrupted > just_ended);
+    assert!(just_ended > stale);
+    assert!(stale > fresh);
+    assert!(practice < interrupted);
+}
+
+#[tokio::test]
+async fn test_claim_contests_skips_claimed_entries_until_lease_expires() {
+    use atcoder_problems_backend::crawler_utils::{claim_contests, enqueue_contests};
+    let db = setup_db().await.unwrap();
+    let ids = ["abc001", "abc002", "abc003"].map(String::from);
+    for id in &ids {
+        insert_contest(&db, id).await;
+    }
+
+    assert_eq!(
+        enqueue_contests(&db, &ids, Mode::All).await.unwrap().queued,
+        3
+    );
+    let first = claim_contests(&db, "worker-1", 2).await.unwrap();
+    let second = claim_contests(&db, "worker-2", 2).await.unwrap();
+    let third = claim_contests(&db, "worker-3", 2).await.unwrap();
+
+    assert_eq!(first.len(), 2);
+    assert_eq!(second.len(), 1);
+    assert!(third.is_empty());
+    assert!(
+        first
+            .iter()
+            .all(|e| e.claimed_by.as_deref() == Some("worker-1"))
+    );
+    assert!(first.iter().all(|e| e.contest_id != second[0].contest_id));
+
+    // The lease of a worker that died runs out.
+    sql_entities::crawl_queue::Entity::update(sql_entities::crawl_queue::ActiveModel {
+        contest_id: Set(second[0].contest_id.clone()),
+        claimed_until: Set(Some(0)),
+        ..Default::default()
+    })
+    .exec(&db)
+    .await
+    .unwrap();
+    let reclaimed = claim_contests(&db, "worker-3", 2).await.unwrap();
+    assert_eq!(reclaimed.len(), 1);
+    assert_eq!(reclaimed[0].contest_id, second[0].contest_id);
+}
+
+#[tokio::test]
+async fn test_run_crawl_queue_drains_queue_and_gives_up_on_failing_contests() {
+    use atcoder_problems_backend::crawler_utils::{enqueue_contests, run_crawl_queue};
+    let db = setup_db().await.unwrap();
+    let ids = ["abc001", "abc002"].map(String::from);
+    for id in &ids {
+        insert_contest(&db, id).await;
+    }
+    enqueue_contests(&db, &ids, Mode::All).await.unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .withf(|contest_id, _| contest_id == "abc001")
+        .times(4)
+        .returning(|_, page| Ok(paged_submissions(page, 3)));
+    mock_fetcher
+        .expect_fetch_submissions()
+        .withf(|contest_id, _| contest_id == "abc002")
+        .times(3)
+        .returning(|_, _| Err(CrawlerError::HttpError("503".to_string())));
+
+    let summary = run_crawl_queue(&mock_fetcher, &db, "worker", 2)
+        .await
+        .unwrap();
+
+    assert_eq!(summary.inserted, 6);
+    assert_eq!(summary.failed, vec!["abc002".to_string()]);
+    let queue = sql_entities::crawl_queue::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].contest_id, "abc002");
+    assert_eq!(queue[0].attempts, 3);
+    assert_eq!(queue[0].claimed_by, None);
+}
+
+#[tokio::test]
+async fn test_run_crawl_queue_stops_when_the_session_expires() {
+    use atcoder_problems_backend::crawler_utils::{CrawlError, enqueue_contests, run_crawl_queue};
+    let db = setup_db().await.unwrap();
+    insert_contest(&db, "abc001").await;
+    enqueue_contests(&db, &["abc001".to_string()], Mode::All)
+        .await
+        .unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(1)
+        .returning(|_, _| {
+            Err(CrawlerError::SessionExpired {
+                url: "https://atcoder.jp/contests/abc001/submissions?page=1".to_string(),
+            })
+        });
+
+    let result = run_crawl_queue(&mock_fetcher, &db, "worker", 1).await;
+
+    assert!(matches!(
+        result,
+        Err(CrawlError::Crawler(CrawlerError::SessionExpired { .. }))
+    ));
+    let queue = sql_entities::crawl_queue::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].attempts, 0);
+    assert_eq!(queue[0].claimed_by, None);
+}
+
+#[tokio::test]
+async fn test_enqueue_contests_reports_unknown_contest_ids() {
+    use atcoder_problems_backend::crawler_utils::{Enqueued, enqueue_contests};
+    let db = setup_db().await.unwrap();
+    insert_contest(&db, "abc001").await;
+
+    let enqueued = enqueue_contests(&db, &["abc001", "abc999"].map(String::from), Mode::All)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        enqueued,
+        Enqueued {
+            queued: 1,
+            unknown: vec!["abc999".to_string()],
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_enqueueing_a_claimed_contest_requeues_it_when_the_crawl_is_done() {
+    use atcoder_problems_backend::crawler_utils::{
+        claim_contests, enqueue_contests, run_crawl_queue,
+    };
+    let db = setup_db().await.unwrap();
+    insert_contest(&db, "abc001").await;
+    let ids = ["abc001".to_string()];
+    enqueue_contests(&db, &ids, Mode::New).await.unwrap();
+
+    // Enqueued again with another mode while a worker crawls it.
+    let claimed = claim_contests(&db, "worker-1", 1).await.unwrap();
+    assert_eq!(claimed.len(), 1);
+    enqueue_contests(&db, &ids, Mode::All).await.unwrap();
+    let entry = sql_entities::crawl_queue::Entity::find_by_id("abc001")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.claimed_by.as_deref(), Some("worker-1"));
+    assert!(entry.requeue);
+    assert_eq!(entry.mode, "all");
+
+    // The crawl in hand puts the entry back instead of removing it, so it is
+    // crawled once more before the queue is drained.
+    sql_entities::crawl_queue::Entity::update(sql_entities::crawl_queue::ActiveModel {
+        contest_id: Set("abc001".to_string()),
+        claimed_until: Set(Some(0)),
+        ..Default::default()
+    })
+    .exec(&db)
+    .await
+    .unwrap();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(2)
+        .returning(|_, _| Ok(vec![]));
+    let summary = run_crawl_queue(&mock_fetcher, &db, "worker-2", 1)
+        .await
+        .unwrap();
+
+    assert!(summary.failed.is_empty());
+    assert!(
+        sql_entities::crawl_queue::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_live_contests_ignores_finished_future_and_permanent_contests() {
+    let db = setup_db().await.unwrap();
+    let now = 1_000_000;
+    for model in [
+        contest("abc001", now - 100_000, 6000),
+        contest("abc400", now - 600, 6000),
+        contest("abc401", now + 600, 6000),
+        contest("practice", 0, 100 * 365 * 24 * 3600),
+    ] {
+        let model: sql_entities::contests::ActiveModel = model.into();
+        sql_entities::contests::Entity::insert(model)
+            .exec(&db)
+            .await
+            .unwrap();
+    }
+
+    let live = atcoder_problems_backend::crawler_utils::live_contests(&db, now)
+        .await
+        .unwrap();
+
+    assert_eq!(live.len(), 1);
+    assert_eq!(live[0].id, "abc400");
+}
+
+#[tokio::test]
+async fn test_crawl_live_contest_polls_until_the_contest_ends() {
+    use atcoder_problems_backend::crawler_utils::{LivePolling, crawl_live_contest};
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::time::Duration;
+
+    let db = setup_db().await.unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let running = contest("abc001", now - 600, 602);
+
+    // Every poll finds one new submission on top of the previous ones.
+    let polls = std::sync::Arc::new(AtomicI64::new(0));
+    let counter = polls.clone();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .returning(move |_, page| {
+            if page > 1 {
+                return Ok(vec![]);
+            }
+            let newest = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((1..=newest).rev().map(submission).collect())
+        });
+    let polling = LivePolling {
+        min_interval: Duration::from_millis(50),
+        max_interval: Duration::from_millis(200),
+    };
+
+    let started = std::time::Instant::now();
+    let inserted = crawl_live_contest(&mock_fetcher, &db, &running, polling)
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    let polls = polls.load(Ordering::SeqCst);
+    assert!(polls > 2);
+    assert_eq!(inserted, usize::try_from(polls).unwrap());
+}
+
+#[tokio::test]
+async fn test_crawl_live_contest_polls_once_after_the_end() {
+    use atcoder_problems_backend::crawler_utils::{LivePolling, crawl_live_contest};
+
+    let db = setup_db().await.unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(1)
+        .returning(|_, _| Ok(vec![]));
+
+    let inserted = crawl_live_contest(
+        &mock_fetcher,
+        &db,
+        &contest("abc001", now - 7200, 6000),
+        LivePolling::default(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(inserted, 0);
+}
+
+async fn queue_user_crawl_job(db: &DatabaseConnection, user_id: &str) {
+    sql_entities::user_crawl_jobs::Entity::insert(sql_entities::user_crawl_jobs::ActiveModel {
+        atcoder_user_id: Set(user_id.to_string()),
+        requested_epoch_second: Set(0),
+        last_contest_id: Set(None),
+        attempts: Set(0),
+        claimed_by: Set(None),
+        claimed_until: Set(None),
+    })
+    .exec(db)
+    .await
+    .unwrap();
+}
+
+async fn insert_participation(db: &DatabaseConnection, user_id: &str, contest_id: &str) {
+    sql_entities::user_rating_history::Entity::insert(
+        sql_entities::user_rating_history::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            contest_id: Set(contest_id.to_string()),
+            is_rated: Set(true),
+            place: Set(1),
+            old_rating: Set(0),
+            new_rating: Set(0),
+            performance: Set(0),
+            inner_performance: Set(None),
+            end_epoch_second: Set(6000),
+        },
+    )
+    .exec(db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_run_user_crawl_jobs_crawls_the_contests_the_user_may_have_submitted_to() {
+    let db = setup_db().await.unwrap();
+    for id in ["abc001", "abc003", "abc004"] {
+        insert_contest(&db, id).await;
+    }
+    let now = chrono::Utc::now().timestamp();
+    let running: sql_entities::contests::ActiveModel = contest("abc002", now - 600, 6000).into();
+    sql_entities::contests::Entity::insert(running)
+        .exec(&db)
+        .await
+        .unwrap();
+    insert_participation(&db, "kenkoooo", "abc001").await;
+    insert_participation(&db, "someone", "abc003").await;
+    let mut submitted = submission(100);
+    submitted.contest_id = "abc004".to_string();
+    submitted.user = "kenkoooo".to_string();
+    atcoder_problems_backend::crawler_utils::upsert_submissions(&db, vec![submitted])
+        .await
+        .unwrap();
+    queue_user_crawl_job(&db, "kenkoooo").await;
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_user_submissions()
+        .withf(|contest_id, user_id, _| contest_id == "abc001" && user_id == "kenkoooo")
+        .times(3)
+        .returning(|_, _, page| Ok(paged_submissions(page, 2)));
+    mock_fetcher
+        .expect_fetch_user_submissions()
+        .withf(|contest_id, user_id, _| contest_id == "abc002" && user_id == "kenkoooo")
+        .times(1)
+        .returning(|_, _, _| Err(CrawlerError::NotFound));
+    mock_fetcher
+        .expect_fetch_user_submissions()
+        .withf(|contest_id, user_id, _| contest_id == "abc004" && user_id == "kenkoooo")
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+
+    let inserted =
+        atcoder_problems_backend::crawler_utils::run_user_crawl_jobs(&mock_fetcher, &db, "worker")
+            .await
+            .unwrap();
+
+    assert_eq!(inserted, 4);
+    assert!(
+        sql_entities::user_crawl_jobs::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_run_user_crawl_jobs_resumes_after_last_finished_contest() {
+    let db = setup_db().await.unwrap();
+    for id in ["abc001", "abc002", "abc003"] {
+        insert_contest(&db, id).await;
+        insert_participation(&db, "kenkoooo", id).await;
+    }
+    queue_user_crawl_job(&db, "kenkoooo").await;
+
+    // abc002 fails on the first attempt only.
+    let failures = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = failures.clone();
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_user_submissions()
+        .withf(|contest_id, _, _| contest_id == "abc001")
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+    mock_fetcher
+        .expect_fetch_user_submissions()
+        .withf(|contest_id, _, _| contest_id == "abc002")
+        .returning(move |_, _, _| {
+            if counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                Err(CrawlerError::HttpError("503".to_string()))
+            } else {
+                Ok(vec![])
+            }
+        });
+    mock_fetcher
+        .expect_fetch_user_submissions()
+        .withf(|contest_id, _, _| contest_id == "abc003")
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+
+    atcoder_problems_backend::crawler_utils::run_user_crawl_jobs(&mock_fetcher, &db, "worker")
+        .await
+        .unwrap();
+
+    assert_eq!(failures.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert!(
+        sql_entities::user_crawl_jobs::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_upsert_submissions_detects_memory_changes() {
+    let db = setup_db().await.unwrap();
+    let upsert =
+        |submissions| atcoder_problems_backend::crawler_utils::upsert_submissions(&db, submissions);
+
+    assert_eq!(upsert(vec![submission(1)]).await.unwrap(), 1);
+    assert_eq!(upsert(vec![submission(1)]).await.unwrap(), 0);
+
+    let rejudged = Submission {
+        memory: Some(2048),
+        ..submission(1)
+    };
+    assert_eq!(upsert(vec![rejudged]).await.unwrap(), 1);
+
+    let stored = sql_entities::submissions::Entity::find_by_id(1)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.memory, Some(2048));
+}
+
+#[tokio::test]
+async fn test_upsert_submissions_writes_pages_larger_than_one_statement() {
+    use atcoder_problems_backend::crawler_utils::upsert_submissions;
+    let db = setup_db().await.unwrap();
+    let mut page: Vec<Submission> = (1..=2500).map(submission).collect();
+    // A submission listed twice is stored as it was listed last.
+    page.push(with_result(1, "WA", 0.0));
+
+    let written = upsert_submissions(&db, page.clone()).await.unwrap();
+    assert_eq!(written, 2500);
+    let submissions = sql_entities::submissions::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(submissions.len(), 2500);
+    let first = sql_entities::submissions::Entity::find_by_id(1)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.result, "WA");
+
+    let written = upsert_submissions(&db, page).await.unwrap();
+    assert_eq!(written, 0);
+}
+
+fn archive_fetcher(contests: Vec<Contest>) -> MockContestFetcher {
+    let mut mock_fetcher = MockContestFetcher::new();
+    mock_fetcher
+        .expect_fetch_permanent_contests()
+        .returning(|| Ok(vec![]));
+    mock_fetcher
+        .expect_fetch_contests()
+        .returning(move |page| match page {
+            1 => Ok(contests.clone()),
+            _ => Ok(vec![]),
+        });
+    mock_fetcher
+        .expect_fetch_contests_in_category()
+        .returning(|_, _| Ok(vec![]));
+    mock_fetcher
+}
+
+#[tokio::test]
+async fn test_crawl_contests_only_updates_changed_contests() {
+    use atcoder_problems_backend::crawl_runs::track_run;
+    use atcoder_problems_backend::crawler_utils::crawl_contests;
+    let db = setup_db().await.unwrap();
+    let archived = |id: &str, title: &str| Contest {
+        id: id.to_string(),
+        start_epoch_second: 1_780_747_200,
+        duration_second: 6_000,
+        title: title.to_string(),
+        rate_change: "~ 1999".to_string(),
+    };
+
+    let fetcher = archive_fetcher(vec![
+        archived("abc460", "ABC 460"),
+        archived("abc461", "ABC 461"),
+    ]);
+    let (result, stats) = track_run(crawl_contests(&fetcher, &db)).await;
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(stats.rows_inserted(), 2);
+    assert_eq!(stats.rows_updated(), 0);
+
+    let fetcher = archive_fetcher(vec![
+        archived("abc460", "ABC 460"),
+        archived("abc461", "AtCoder Beginner Contest 461"),
+    ]);
+    let (result, stats) = track_run(crawl_contests(&fetcher, &db)).await;
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(stats.rows_inserted(), 0);
+    assert_eq!(stats.rows_updated(), 1);
+    let renamed = sql_entities::contests::Entity::find_by_id("abc461")
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(renamed.title, "AtCoder Beginner Contest 461");
+}
+
+#[tokio::test]
+async fn test_backfill_contest_types_fills_contests_stored_before_they_were_parsed() {
+    use atcoder_problems_backend::crawler_utils::backfill_contest_types;
+    let db = setup_db().await.unwrap();
+    let rated = sql_entities::contests::Model {
+        rate_change: " ~ 1999".to_string(),
+        ..contest("abc001", 0, 6000)
+    };
+    let sponsored = sql_entities::contests::Model {
+        rate_change: "All".to_string(),
+        ..contest("keyence2021", 0, 6000)
+    };
+    for model in [rated, sponsored, contest("practice", 0, 6000)] {
+        let model: sql_entities::contests::ActiveModel = model.into();
+        sql_entities::contests::Entity::insert(model)
+            .exec(&db)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(backfill_contest_types(&db).await.unwrap(), 2);
+
+    let mut contests = sql_entities::contests::Entity::find()
+        .all(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| (c.id, c.rated_lower, c.rated_upper, c.contest_type))
+        .collect::<Vec<_>>();
+    contests.sort();
+    assert_eq!(
+        contests,
+        vec![
+            ("abc001".to_string(), Some(0), Some(1999), "abc".to_string()),
+            (
+                "keyence2021".to_string(),
+                Some(0),
+                None,
+                "sponsored".to_string()
+            ),
+            ("practice".to_string(), None, None, "other".to_string()),
+        ]
+    );
+    assert_eq!(backfill_contest_types(&db).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_crawl_submission_details_stores_judge_cases_of_new_accepted_submissions() {
+    let db = setup_db().await.unwrap();
+    sql_entities::solver::Entity::insert(sql_entities::solver::ActiveModel {
+        problem_id: Set("abc001_a".to_string()),
+        user_count: Set(10),
+    })
+    .exec(&db)
+    .await
+    .unwrap();
+
+    let wrong_answer = Submission {
+        result: "WA".to_string(),
+        ..submission(3)
+    };
+    let unpopular = Submission {
+        problem_id: "abc001_b".to_string(),
+        ..submission(4)
+    };
+    atcoder_problems_backend::crawler_utils::upsert_submissions(
+        &db,
+        vec![submission(1), submission(2), wrong_answer, unpopular],
+    )
+    .await
+    .unwrap();
+
+    // Submission 2 already has its judge cases.
+    sql_entities::submission_judge_cases::Entity::insert(
+        sql_entities::submission_judge_cases::ActiveModel {
+            submission_id: Set(2),
+            case_name: Set("00_sample_00.txt".to_string()),
+            verdict: Set("AC".to_string()),
+            execution_time: Set(Some(1)),
+            memory: Set(Some(1024)),
+        },
+    )
+    .exec(&db)
+    .await
+    .unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submission_detail()
+        .withf(|contest_id, submission_id| contest_id == "abc001" && *submission_id == 1)
+        .times(1)
+        .returning(|_, submission_id| {
+            Ok(SubmissionDetail {
+                submission_id,
+                memory_kb: Some(2048),
+                compile_error: false,
+                judge_cases: vec![
+                    JudgeCase {
+                        case_name: "00_sample_00.txt".to_string(),
+                        verdict: "AC".to_string(),
+                        time_ms: Some(1),
+                        memory_kb: Some(1024),
+                    },
+                    JudgeCase {
+                        case_name: "01_random_00.txt".to_string(),
+                        verdict: "AC".to_string(),
+                        time_ms: Some(5),
+                        memory_kb: Some(2048),
+                    },
+                ],
+            })
+        });
+
+    let crawled =
+        atcoder_problems_backend::crawler_utils::crawl_submission_details(&mock_fetcher, &db, 10)
+            .await
+            .unwrap();
+
+    assert_eq!(crawled, 1);
+    let judge_cases = sql_entities::submission_judge_cases::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(judge_cases.len(), 3);
+    assert!(judge_cases.iter().any(|c| c.submission_id == 1
+        && c.case_name == "01_random_00.txt"
+        && c.execution_time == Some(5)
+        && c.memory == Some(2048)));
+    let detail = sql_entities::submission_details::Entity::find_by_id(1)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(detail.status, "crawled");
+    assert_eq!(detail.memory_kb, Some(2048));
+    assert!(!detail.compile_error);
+}
+
+#[tokio::test]
+async fn test_crawl_submission_details_does_not_pick_failed_or_empty_pages_again() {
+    let db = setup_db().await.unwrap();
+    sql_entities::solver::Entity::insert(sql_entities::solver::ActiveModel {
+        problem_id: Set("abc001_a".to_string()),
+        user_count: Set(10),
+    })
+    .exec(&db)
+    .await
+    .unwrap();
+    atcoder_problems_backend::crawler_utils::upsert_submissions(
+        &db,
+        vec![submission(1), submission(2)],
+    )
+    .await
+    .unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submission_detail()
+        .withf(|_, submission_id| *submission_id == 2)
+        .times(1)
+        .returning(|_, _| Err(CrawlerError::HttpError("503".to_string())));
+    mock_fetcher
+        .expect_fetch_submission_detail()
+        .withf(|_, submission_id| *submission_id == 1)
+        .times(1)
+        .returning(|_, submission_id| {
+            Ok(SubmissionDetail {
+                submission_id,
+                memory_kb: None,
+                compile_error: true,
+                judge_cases: vec![],
+            })
+        });
+
+    let crawled =
+        atcoder_problems_backend::crawler_utils::crawl_submission_details(&mock_fetcher, &db, 10)
+            .await
+            .unwrap();
+    assert_eq!(crawled, 1);
+
+    let mut details: Vec<(i64, String, bool)> = sql_entities::submission_details::Entity::find()
+        .all(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|d| (d.submission_id, d.status, d.compile_error))
+        .collect();
+    details.sort();
+    assert_eq!(
+        details,
+        vec![
+            (1, "crawled".to_string(), true),
+            (2, "failed".to_string(), false)
+        ]
+    );
+
+    // Neither is fetched again.
+    let mock_fetcher = MockSubmissionFetcher::new();
+    let crawled =
+        atcoder_problems_backend::crawler_utils::crawl_submission_details(&mock_fetcher, &db, 10)
+            .await
+            .unwrap();
+    assert_eq!(crawled, 0);
+}
+
+fn standings(fixed: bool) -> Standings {
+    serde_json::from_value(serde_json::json!({
+        "Fixed": fixed,
+        "TaskInfo": [
+            {"Assignment": "A", "TaskName": "Problem A", "TaskScreenName": "abc001_a"}
+        ],
+        "StandingsData": [
+            {
+                "Rank": 1,
+                "UserScreenName": "user",
+                "IsRated": true,
+                "OldRating": 0,
+                "Competitions": 1,
+                "TotalResult": {"Count": 1, "Accepted": 1, "Penalty": 0, "Score": 10000, "Elapsed": 1},
+                "TaskResults": {
+                    "abc001_a": {
+                        "Count": 1, "Failure": 0, "Penalty": 0, "Score": 10000,
+                        "Elapsed": 1, "Status": 1, "Pending": false, "Frozen": false
+                    }
+                }
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_fetch_fixed_standings_returns_only_fixed_standings() {
+    let mut mock_fetcher = MockStandingsFetcher::new();
+    mock_fetcher
+        .expect_fetch_standings()
+        .returning(|contest_id| match contest_id {
+            "fixed" => Ok(Some(standings(true))),
+            "provisional" => Ok(Some(standings(false))),
+            _ => Ok(None),
+        });
+
+    let fixed =
+        atcoder_problems_backend::crawler_utils::fetch_fixed_standings(&mock_fetcher, "fixed")
+            .await
+            .unwrap();
+    assert!(fixed.is_some());
+
+    let provisional = atcoder_problems_backend::crawler_utils::fetch_fixed_standings(
+        &mock_fetcher,
+        "provisional",
+    )
+    .await
+    .unwrap();
+    assert!(provisional.is_none());
+
+    let missing =
+        atcoder_problems_backend::crawler_utils::fetch_fixed_standings(&mock_fetcher, "missing")
+            .await
+            .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_fetch_fixed_standings_rejects_invalid_standings() {
+    let mut mock_fetcher = MockStandingsFetcher::new();
+    mock_fetcher.expect_fetch_standings().returning(|_| {
+        let mut standings = standings(true);
+        standings.task_info.clear();
+        Ok(Some(standings))
+    });
+
+    let result =
+        atcoder_problems_backend::crawler_utils::fetch_fixed_standings(&mock_fetcher, "abc001")
+            .await;
+
+    assert!(matches!(result, Err(CrawlerError::InvalidStandings(_))));
+}
+
+fn user_profile(user_id: &str, rating: Option<i32>) -> UserProfile {
+    UserProfile {
+        user_id: user_id.to_string(),
+        country: Some("JP".to_string()),
+        affiliation: None,
+        birth_year: None,
+        rating,
+        highest_rating: rating,
+    }
+}
+
+fn rating_history_entry(contest_id: &str, new_rating: i32) -> RatingHistoryEntry {
+    RatingHistoryEntry {
+        is_rated: true,
+        place: 100,
+        old_rating: 0,
+        new_rating,
+        performance: 1200,
+        inner_performance: Some(1200),
+        contest_screen_name: format!("{}.contest.atcoder.jp", contest_id),
+        end_time: "2025-03-29T22:40:00+09:00".to_string(),
+        extra: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_users_to_refresh_picks_active_and_linked_users() {
+    use atcoder_problems_backend::crawler_utils::users_to_refresh;
+    let db = setup_db().await.unwrap();
+    let now = 100 * 24 * 3600;
+
+    let mut recent = submission(1);
+    recent.user = "active".to_string();
+    recent.epoch_second = now - 3600;
+    let mut old = submission(2);
+    old.user = "inactive".to_string();
+    old.epoch_second = 0;
+    let mut crawled_today = submission(3);
+    crawled_today.user = "fresh".to_string();
+    crawled_today.epoch_second = now - 3600;
+    atcoder_problems_backend::crawler_utils::upsert_submissions(
+        &db,
+        vec![recent, old, crawled_today],
+    )
+    .await
+    .unwrap();
+    for (internal_user_id, atcoder_user_id) in [("1", Some("linked")), ("2", None), ("3", Some(""))]
+    {
+        sql_entities::internal_users::Entity::insert(sql_entities::internal_users::ActiveModel {
+            internal_user_id: Set(internal_user_id.to_string()),
+            atcoder_user_id: Set(atcoder_user_id.map(str::to_string)),
+        })
+        .exec(&db)
+        .await
+        .unwrap();
+    }
+    for (user_id, crawled_epoch_second) in [("linked", now - 7 * 24 * 3600), ("fresh", now - 60)] {
+        sql_entities::user_profiles::Entity::insert(sql_entities::user_profiles::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            country: Set(None),
+            affiliation: Set(None),
+            birth_year: Set(None),
+            rating: Set(None),
+            highest_rating: Set(None),
+            crawled_epoch_second: Set(crawled_epoch_second),
+        })
+        .exec(&db)
+        .await
+        .unwrap();
+    }
+
+    let user_ids = users_to_refresh(&db, now, 10).await.unwrap();
+    assert_eq!(user_ids, vec!["active", "linked"]);
+
+    let user_ids = users_to_refresh(&db, now, 1).await.unwrap();
+    assert_eq!(user_ids, vec!["active"]);
+}
+
+#[tokio::test]
+async fn test_refresh_user_profiles_stores_profiles_and_history() {
+    use atcoder_problems_backend::crawler_utils::refresh_user_profiles;
+    let db = setup_db().await.unwrap();
+
+    let mut mock_fetcher = MockUserFetcher::new();
+    mock_fetcher
+        .expect_fetch_user_profile()
+        .withf(|user_id| user_id == "kenkoooo")
+        .times(2)
+        .returning(|user_id| Ok(user_profile(user_id, Some(1803))));
+    mock_fetcher
+        .expect_fetch_rating_history()
+        .withf(|user_id| user_id == "kenkoooo")
+        .times(2)
+        .returning(|_| {
+            Ok(vec![
+                rating_history_entry("abc042", 408),
+                rating_history_entry("abc399", 1803),
+            ])
+        });
+    // A missing user and a broken page are skipped.
+    mock_fetcher
+        .expect_fetch_user_profile()
+        .withf(|user_id| user_id == "deleted")
+        .times(1)
+        .returning(|_| Err(CrawlerError::NotFound));
+    mock_fetcher
+        .expect_fetch_user_profile()
+        .withf(|user_id| user_id == "broken")
+        .times(1)
+        .returning(|_| Err(CrawlerError::ParseError("layout".to_string())));
+
+    let user_ids = ["kenkoooo", "deleted", "broken"].map(String::from);
+    let refreshed = refresh_user_profiles(&mock_fetcher, &db, &user_ids)
+        .await
+        .unwrap();
+    assert_eq!(refreshed, 1);
+    // Refreshing again updates the stored rows instead of failing on them.
+    refresh_user_profiles(&mock_fetcher, &db, &user_ids[..1])
+        .await
+        .unwrap();
+
+    let profiles = sql_entities::user_profiles::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0].user_id, "kenkoooo");
+    assert_eq!(profiles[0].rating, Some(1803));
+    assert_eq!(profiles[0].country.as_deref(), Some("JP"));
+
+    let mut history = sql_entities::user_rating_history::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    history.sort_by(|a, b| a.contest_id.cmp(&b.contest_id));
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].contest_id, "abc042");
+    assert_eq!(history[0].new_rating, 408);
+    assert_eq!(history[1].end_epoch_second, 1_743_255_600);
+}
+
+#[tokio::test]
+async fn test_refresh_user_profiles_stops_when_the_session_expires() {
+    use atcoder_problems_backend::crawler_utils::{CrawlError, refresh_user_profiles};
+    let db = setup_db().await.unwrap();
+
+    let mut mock_fetcher = MockUserFetcher::new();
+    mock_fetcher
+        .expect_fetch_user_profile()
+        .times(1)
+        .returning(|user_id| {
+            Err(CrawlerError::SessionExpired {
+                url: format!("https://atcoder.jp/users/{}", user_id),
+            })
+        });
+
+    let user_ids = ["first", "second"].map(String::from);
+    let result = refresh_user_profiles(&mock_fetcher, &db, &user_ids).await;
+
+    assert!(matches!(
+        result,
+        Err(CrawlError::Crawler(CrawlerError::SessionExpired { .. }))
+    ));
+}
+
+fn editorial(problem_id: &str, url: &str, is_official: bool) -> Editorial {
+    Editorial {
+        problem_id: problem_id.to_string(),
+        url: url.to_string(),
+        language: Some("ja".to_string()),
+        is_official,
+    }
+}
+
+#[tokio::test]
+async fn test_contest_ids_for_editorial_crawl_revisits_recent_contests() {
+    use atcoder_problems_backend::crawler_utils::contest_ids_for_editorial_crawl;
+    let db = setup_db().await.unwrap();
+    let day = 24 * 3600;
+    let now = 1000 * day;
+
+    for model in [
+        contest("never_crawled", 0, 6000),
+        contest("recent", now - 2 * day, 6000),
+        contest("closed", now - 100 * day, 6000),
+        contest("crawled_in_window", now - 100 * day, 6000),
+        contest("running", now - 60, 6000),
+    ] {
+        let model: sql_entities::contests::ActiveModel = model.into();
+        sql_entities::contests::Entity::insert(model)
+            .exec(&db)
+            .await
+            .unwrap();
+    }
+    for (contest_id, crawled_epoch_second) in [
+        ("recent", now - day),
+        ("closed", now - 50 * day),
+        ("crawled_in_window", now - 95 * day),
+    ] {
+        sql_entities::editorial_crawls::Entity::insert(
+            sql_entities::editorial_crawls::ActiveModel {
+                contest_id: Set(contest_id.to_string()),
+                crawled_epoch_second: Set(crawled_epoch_second),
+            },
+        )
+        .exec(&db)
+        .await
+        .unwrap();
+    }
+
+    let contest_ids = contest_ids_for_editorial_crawl(&db, now).await.unwrap();
+
+    assert_eq!(
+        contest_ids,
+        vec!["recent", "crawled_in_window", "never_crawled"]
+    );
+}
+
+#[tokio::test]
+async fn test_crawl_editorials_replaces_the_editorials_of_each_contest() {
+    use atcoder_problems_backend::crawler_utils::crawl_editorials;
+    let db = setup_db().await.unwrap();
+
+    let mut mock_fetcher = MockEditorialFetcher::new();
+    let mut sequence = mockall::Sequence::new();
+    mock_fetcher
+        .expect_fetch_editorials()
+        .with(mockall::predicate::eq("abc399"))
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(|_| {
+            Ok(vec![
+                editorial(
+                    "abc399_a",
+                    "https://atcoder.jp/contests/abc399/editorial/1",
+                    true,
+                ),
+                editorial("abc399_a", "https://example.com/old", false),
+            ])
+        });
+    mock_fetcher
+        .expect_fetch_editorials()
+        .with(mockall::predicate::eq("abc399"))
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(|_| {
+            Ok(vec![
+                editorial(
+                    "abc399_a",
+                    "https://atcoder.jp/contests/abc399/editorial/1",
+                    true,
+                ),
+                editorial(
+                    "abc399_a",
+                    "https://atcoder.jp/contests/abc399/editorial/1",
+                    true,
+                ),
+                editorial(
+                    "abc399_b",
+                    "https://atcoder.jp/contests/abc399/editorial/2",
+                    true,
+                ),
+            ])
+        });
+    // A page that lists nothing, e.g. after a layout change.
+    mock_fetcher
+        .expect_fetch_editorials()
+        .with(mockall::predicate::eq("abc399"))
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(|_| Ok(vec![]));
+    mock_fetcher
+        .expect_fetch_editorials()
+        .with(mockall::predicate::eq("practice"))
+        .returning(|_| Err(CrawlerError::NotFound));
+
+    let stored = crawl_editorials(&mock_fetcher, &db, &["abc399".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(stored, 2);
+    let stored = crawl_editorials(
+        &mock_fetcher,
+        &db,
+        &["abc399", "practice"].map(String::from),
+    )
+    .await
+    .unwrap();
+    assert_eq!(stored, 2);
+    let stored = crawl_editorials(&mock_fetcher, &db, &["abc399".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+
+    let mut urls: Vec<(String, String)> = sql_entities::problem_editorials::Entity::find()
+        .all(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (row.problem_id, row.editorial_url))
+        .collect();
+    urls.sort();
+    assert_eq!(
+        urls,
+        vec![
+            (
+                "abc399_a".to_string(),
+                "https://atcoder.jp/contests/abc399/editorial/1".to_string()
+            ),
+            (
+                "abc399_b".to_string(),
+                "https://atcoder.jp/contests/abc399/editorial/2".to_string()
+            ),
+        ]
+    );
+    let crawls = sql_entities::editorial_crawls::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(crawls.len(), 2);
+}
+
+#[tokio::test]
+async fn test_refresh_user_profiles_in_a_dry_run_stores_nothing() {
+    use atcoder_problems_backend::crawler_utils::{dry_run, refresh_user_profiles};
+    let db = setup_db().await.unwrap();
+
+    let mut mock_fetcher = MockUserFetcher::new();
+    mock_fetcher
+        .expect_fetch_user_profile()
+        .returning(|user_id| Ok(user_profile(user_id, Some(1803))));
+    mock_fetcher
+        .expect_fetch_rating_history()
+        .returning(|_| Ok(vec![rating_history_entry("abc399", 1803)]));
+
+    let refreshed = dry_run(refresh_user_profiles(
+        &mock_fetcher,
+        &db,
+        &["kenkoooo".to_string()],
+    ))
+    .await
+    .unwrap();
+
+    assert_eq!(refreshed, 1);
+    let profiles = sql_entities::user_profiles::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert!(profiles.is_empty());
+    let history = sql_entities::user_rating_history::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert!(history.is_empty());
+}
+
+#[tokio::test]
+async fn test_crawl_submissions_in_a_dry_run_counts_changes_without_writing_them() {
+    use atcoder_problems_backend::crawler_utils::{crawl_submissions, dry_run, upsert_submissions};
+    let db = setup_db().await.unwrap();
+    upsert_submissions(&db, vec![submission(10)]).await.unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .times(3)
+        .returning(|_, page| Ok(paged_submissions(page, 2)));
+
+    let inserted = dry_run(crawl_submissions(&mock_fetcher, &db, "abc001", Mode::All))
+        .await
+        .unwrap();
+
+    // Submission 10 is stored already, the other three would be inserted.
+    assert_eq!(inserted, 3);
+    let ids: Vec<i64> = sql_entities::submissions::Entity::find()
+        .all(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|submission| submission.id)
+        .collect();
+    assert_eq!(ids, vec![10]);
+    assert!(
+        sql_entities::crawl_state::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_tracked_crawl_is_recorded_with_its_metrics() {
+    use atcoder_problems_backend::crawl_runs::{finish_run, start_run, track_run};
+    use atcoder_problems_backend::crawler_utils::{crawl_submissions, upsert_submissions};
+    let db = setup_db().await.unwrap();
+    upsert_submissions(
+        &db,
+        vec![Submission {
+            result: "WJ".to_string(),
+            ..submission(1)
+        }],
+    )
+    .await
+    .unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .returning(|_, page| match page {
+            1 => Ok(vec![submission(1), submission(2)]),
+            _ => Ok(vec![]),
+        });
+
+    let run_id = start_run(&db, "submissions", Some("all"), 100)
+        .await
+        .unwrap();
+    let running = sql_entities::crawl_runs::Entity::find_by_id(run_id)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(running.status, "running");
+    assert_eq!(running.finished_epoch_second, None);
+
+    let (result, stats) =
+        track_run(crawl_submissions(&mock_fetcher, &db, "abc001", Mode::All)).await;
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(stats.rows_inserted(), 1);
+    assert_eq!(stats.rows_updated(), 1);
+
+    let fetch_stats = crawler::FetchStats::default();
+    fetch_stats.record(&Ok::<(), CrawlerError>(()));
+    fetch_stats.record(&Err::<(), _>(CrawlerError::NotFound));
+    finish_run(&db, run_id, &stats, &fetch_stats, None, 160)
+        .await
+        .unwrap();
+
+    let run = sql_entities::crawl_runs::Entity::find_by_id(run_id)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.command, "submissions");
+    assert_eq!(run.mode.as_deref(), Some("all"));
+    assert_eq!(run.status, "succeeded");
+    assert_eq!(run.started_epoch_second, 100);
+    assert_eq!(run.finished_epoch_second, Some(160));
+    assert_eq!(run.pages_fetched, 1);
+    assert_eq!(run.rows_inserted, 1);
+    assert_eq!(run.rows_updated, 1);
+    assert_eq!(run.errors, serde_json::json!({ "NotFound": 1 }));
+    assert_eq!(run.error_message, None);
+
+    let contests = sql_entities::crawl_run_contests::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(contests.len(), 1);
+    assert_eq!(contests[0].run_id, run_id);
+    assert_eq!(contests[0].contest_id, "abc001");
+}
+
+#[tokio::test]
+async fn test_failed_crawl_is_recorded_without_its_contest() {
+    use atcoder_problems_backend::crawl_runs::{finish_run, start_run, track_run};
+    use atcoder_problems_backend::crawler_utils::crawl_submissions;
+    let db = setup_db().await.unwrap();
+
+    let mut mock_fetcher = MockSubmissionFetcher::new();
+    mock_fetcher
+        .expect_fetch_submissions()
+        .returning(|_, page| match page {
+            1 => Ok(vec![submission(1), submission(2)]),
+            _ => Err(CrawlerError::HttpError("503".to_string())),
+        });
+
+    let run_id = start_run(&db, "submissions", Some("all"), 100)
+        .await
+        .unwrap();
+    let (result, stats) =
+        track_run(crawl_submissions(&mock_fetcher, &db, "abc001", Mode::All)).await;
+    let error = result.unwrap_err().to_string();
+    finish_run(
+        &db,
+        run_id,
+        &stats,
+        &crawler::FetchStats::default(),
+        Some(&error),
+        160,
+    )
+    .await
+    .unwrap();
+
+    let run = sql_entities::crawl_runs::Entity::find_by_id(run_id)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.status, "failed");
+    assert_eq!(run.rows_inserted, 2);
+    assert_eq!(run.error_message, Some(error));
+    let contests = sql_entities::crawl_run_contests::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert!(contests.is_empty());
+}
+
+#[tokio::test]
+async fn test_runs_left_running_for_too_long_are_abandoned() {
+    use atcoder_problems_backend::crawl_runs::{STALE_RUN_SECOND, abandon_stale_runs, start_run};
+    let db = setup_db().await.unwrap();
+    let now = 10 * STALE_RUN_SECOND;
+    let killed = start_run(&db, "submissions", Some("all"), now - STALE_RUN_SECOND - 1)
+        .await
+        .unwrap();
+    let running = start_run(&db, "submissions", Some("recent"), now - 60)
+        .await
+        .unwrap();
+
+    assert_eq!(abandon_stale_runs(&db, now).await.unwrap(), 1);
+    let status = |id| {
+        let db = &db;
+        async move {
+            sql_entities::crawl_runs::Entity::find_by_id(id)
+                .one(db)
+                .await
+                .unwrap()
+                .unwrap()
+                .status
+        }
+    };
+    assert_eq!(status(killed).await, "abandoned");
+    assert_eq!(status(running).await, "running");
+    assert_eq!(abandon_stale_runs(&db, now).await.unwrap(), 0);
+}
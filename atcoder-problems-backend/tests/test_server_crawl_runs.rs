@@ -0,0 +1,120 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use atcoder_problems_backend::server::GithubToken;
+use axum::{Router, http::StatusCode};
+use sea_orm::{DatabaseConnection, EntityTrait, Set};
+use serde_json::{Value, json};
+use sql_entities::{crawl_run_contests, crawl_runs};
+
+async fn seed_run(
+    db: &DatabaseConnection,
+    id: i64,
+    command: &str,
+    status: &str,
+    started_epoch_second: i64,
+    contest_ids: &[&str],
+) {
+    crawl_runs::Entity::insert(crawl_runs::ActiveModel {
+        id: Set(id),
+        command: Set(command.to_string()),
+        mode: Set(None),
+        status: Set(status.to_string()),
+        started_epoch_second: Set(started_epoch_second),
+        finished_epoch_second: Set(Some(started_epoch_second + 60)),
+        pages_fetched: Set(10),
+        rows_inserted: Set(5),
+        rows_updated: Set(1),
+        errors: Set(json!({ "HttpError": 2 })),
+        error_message: Set(
+            (status == "failed").then(|| "HttpError: <html>Service Unavailable</html>".to_string())
+        ),
+    })
+    .exec(db)
+    .await
+    .unwrap();
+    for contest_id in contest_ids {
+        crawl_run_contests::Entity::insert(crawl_run_contests::ActiveModel {
+            run_id: Set(id),
+            contest_id: Set(contest_id.to_string()),
+        })
+        .exec(db)
+        .await
+        .unwrap();
+    }
+}
+
+fn build_app_as_user(db: DatabaseConnection) -> Router {
+    let mut mock = common::MockGithubAuthenticator::new();
+    mock.expect_verify_user()
+        .returning(|_| Ok(GithubToken { id: 1 }));
+    common::build_app(db, mock)
+}
+
+async fn get_runs(app: &Router, uri: &str) -> Value {
+    let resp = common::get_with_cookie(app, uri, "token=t").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    common::read_json(resp).await
+}
+
+fn run_ids(body: &Value) -> Vec<i64> {
+    body.as_array()
+        .unwrap()
+        .iter()
+        .map(|run| run["id"].as_i64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_recent_runs_are_listed_newest_first() {
+    let db = common::setup_db().await;
+    seed_run(&db, 1, "contests", "succeeded", 100, &[]).await;
+    seed_run(&db, 2, "submissions", "failed", 300, &[]).await;
+    seed_run(&db, 3, "submissions", "succeeded", 200, &["abc380"]).await;
+    let app = build_app_as_user(db);
+
+    let body = get_runs(&app, "/internal-api/crawl_runs/recent").await;
+    assert_eq!(run_ids(&body), vec![2, 3, 1]);
+    assert_eq!(body[1]["command"], "submissions");
+    assert_eq!(body[1]["status"], "succeeded");
+    assert_eq!(body[1]["finished_epoch_second"], 260);
+    assert_eq!(body[1]["pages_fetched"], 10);
+    assert_eq!(body[1]["rows_inserted"], 5);
+    assert_eq!(body[1]["rows_updated"], 1);
+    assert_eq!(body[1]["errors"], json!({ "HttpError": 2 }));
+    // The message of a failed run may quote the response of AtCoder.
+    assert_eq!(body[0]["status"], "failed");
+    assert!(body[0].get("error_message").is_none());
+
+    let body = get_runs(&app, "/internal-api/crawl_runs/recent?limit=1").await;
+    assert_eq!(run_ids(&body), vec![2]);
+}
+
+#[tokio::test]
+async fn test_recent_runs_are_filtered() {
+    let db = common::setup_db().await;
+    seed_run(
+        &db,
+        1,
+        "submissions",
+        "succeeded",
+        100,
+        &["abc380", "abc381"],
+    )
+    .await;
+    seed_run(&db, 2, "submissions", "failed", 200, &[]).await;
+    seed_run(&db, 3, "submissions", "succeeded", 300, &["abc381"]).await;
+    seed_run(&db, 4, "contests", "succeeded", 400, &[]).await;
+    let app = build_app_as_user(db);
+
+    let body = get_runs(&app, "/internal-api/crawl_runs/recent?command=submissions").await;
+    assert_eq!(run_ids(&body), vec![3, 2, 1]);
+
+    let body = get_runs(&app, "/internal-api/crawl_runs/recent?status=failed").await;
+    assert_eq!(run_ids(&body), vec![2]);
+
+    // When did submissions last crawl successfully for abc380?
+    let body = get_runs(&app, "/internal-api/crawl_runs/recent?command=submissions&status=succeeded&contest_id=abc380&limit=1").await;
+    assert_eq!(run_ids(&body), vec![1]);
+}
@@ -41,6 +41,7 @@ async fn test_all_authed_endpoints_require_cookie() {
         "/internal-api/contest/my",
         "/internal-api/contest/joined",
         "/internal-api/progress_reset/list",
+        "/internal-api/crawl_runs/recent",
     ];
     for ep in get_endpoints {
         let resp = common::get(&app, ep).await;
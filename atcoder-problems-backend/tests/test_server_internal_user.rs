@@ -6,7 +6,7 @@ use atcoder_problems_backend::server::GithubToken;
 use axum::http::StatusCode;
 use sea_orm::{ActiveValue, EntityTrait};
 use serde_json::{Value, json};
-use sql_entities::internal_users;
+use sql_entities::{internal_users, user_crawl_jobs};
 
 fn with_verify(mock: &mut common::MockGithubAuthenticator, id: i64) {
     mock.expect_verify_user()
@@ -88,3 +88,38 @@ async fn test_update_user_writes_db() {
         .unwrap();
     assert_eq!(row.atcoder_user_id.as_deref(), Some("newname"));
 }
+
+#[tokio::test]
+async fn test_update_user_queues_crawl_of_new_atcoder_id() {
+    let db = common::setup_db().await;
+    common::seed_user(&db, "99", Some("oldname")).await;
+
+    let mut mock = common::MockGithubAuthenticator::new();
+    with_verify(&mut mock, 99);
+    let app = common::build_app(db.clone(), mock);
+    let update = |atcoder_user_id: &'static str| {
+        common::post_json_with_cookie(
+            &app,
+            "/internal-api/user/update",
+            json!({ "atcoder_user_id": atcoder_user_id }),
+            "token=tok",
+        )
+    };
+
+    // Keeping the linked id queues nothing.
+    assert_eq!(update("oldname").await.status(), StatusCode::OK);
+    assert!(
+        user_crawl_jobs::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    assert_eq!(update("newname").await.status(), StatusCode::OK);
+    let jobs = user_crawl_jobs::Entity::find().all(&db).await.unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].atcoder_user_id, "newname");
+    assert_eq!(jobs[0].last_contest_id, None);
+    assert_eq!(jobs[0].claimed_by, None);
+}
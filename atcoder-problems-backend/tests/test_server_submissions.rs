@@ -28,6 +28,7 @@ async fn seed(db: &sea_orm::DatabaseConnection) {
             length: ActiveValue::Set(0),
             result: ActiveValue::Set((*r).into()),
             execution_time: ActiveValue::Set(None),
+            memory: ActiveValue::Set(Some(1024)),
         })
         .collect();
     submissions::Entity::insert_many(ams)
@@ -79,6 +80,7 @@ async fn test_from_time() {
     let arr = body.as_array().unwrap();
     assert_eq!(arr.len(), 1);
     assert_eq!(arr[0]["id"], 5);
+    assert_eq!(arr[0]["memory"], 1024);
 }
 
 #[tokio::test]
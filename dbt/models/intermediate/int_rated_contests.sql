@@ -3,7 +3,7 @@
 --   - is_rated = true
 --   - Started after first AGC (2016/7/16, epoch 1468670400)
 --   - Has at least 2 problems
---   - Excludes heuristic contests (ahc*)
+--   - Excludes heuristic contests (contest_type = 'ahc')
 
 select c.contest_id, c.start_epoch_second
 from {{ ref('stg_contests') }} c
@@ -15,4 +15,4 @@ inner join (
 where c.start_epoch_second >= 1468670400
   and c.is_rated
   and cp.problem_count >= 2
-  and c.contest_id not like 'ahc%'
+  and c.contest_type != 'ahc'
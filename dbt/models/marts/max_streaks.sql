@@ -12,10 +12,12 @@ with first_ac_per_problem as (
 ),
 
 daily_solves as (
-    -- Convert to JST date (epoch + 9 hours, then truncate to day)
+    -- Convert to the JST date: to_timestamp returns a timestamptz, and
+    -- at time zone 'Asia/Tokyo' turns it into the local time in Tokyo, whose
+    -- date is the JST day whatever the session time zone is
     select distinct
         user_id,
-        (to_timestamp(first_ac_epoch) at time zone 'UTC' at time zone 'Asia/Tokyo')::date as solve_date
+        (to_timestamp(first_ac_epoch) at time zone 'Asia/Tokyo')::date as solve_date
     from first_ac_per_problem
 ),
 
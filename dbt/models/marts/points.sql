@@ -1,10 +1,12 @@
--- Max points for each problem from rated contest submissions
+-- Points for each problem from rated contests.
+-- The point crawled from the task page wins; the max submission point is the fallback.
 
 select
     cp.problem_id,
-    max(s.point) as point,
+    coalesce(max(p.point), max(s.point)) as point,
     null::double precision as predict  -- Placeholder for difficulty prediction
 from {{ ref('int_rated_contests') }} rc
 inner join {{ ref('stg_contest_problem') }} cp on rc.contest_id = cp.contest_id
 inner join {{ ref('stg_submissions') }} s on cp.problem_id = s.problem_id
+left join {{ ref('stg_problems') }} p on cp.problem_id = p.problem_id
 group by cp.problem_id
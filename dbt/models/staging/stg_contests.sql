@@ -1,8 +1,14 @@
+-- The rated range and contest type come from the crawler; see the contests
+-- table in postgres/schema.sql.
+
 select
     id as contest_id,
     start_epoch_second,
     duration_second,
     title,
     rate_change,
-    rate_change != '-' as is_rated
+    rated_lower,
+    rated_upper,
+    contest_type,
+    rated_lower is not null as is_rated
 from {{ source('atcoder', 'contests') }}
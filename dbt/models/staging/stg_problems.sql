@@ -3,5 +3,8 @@ select
     contest_id,
     problem_index,
     name,
-    title
+    title,
+    time_limit_ms,
+    memory_limit_kb,
+    point
 from {{ source('atcoder', 'problems') }}
@@ -9,7 +9,12 @@ select
     s.length,
     s.result,
     s.execution_time,
+    s.memory,
     s.result = 'AC' as is_accepted
 from {{ source('atcoder', 'submissions') }} s
 left join {{ ref('excluded_users') }} e on s.user_id = e.user_id
 where e.user_id is null
+  -- Pending results (WJ, WR, "3/20 WJ") are refreshed by the crawler and must not be counted yet
+  and s.result not in ('WJ', 'WR')
+  and s.result not like '%/%'
+  and s.result not like '% WJ'
@@ -434,8 +434,22 @@ def _parse_rated_range(rate_change: str) -> tuple[int, int | None] | None:
     return (lower, upper)
 
 
+def _rated_range(contest: Contest) -> tuple[int, int | None] | None:
+    """Return the rated range parsed by the backend crawler (see the contests
+    table in postgres/schema.sql).
+
+    Falls back to parsing ``rate_change`` for dumps made before the crawler
+    parsed it.
+    """
+    if contest.contest_type is None:
+        return _parse_rated_range(contest.rate_change)
+    if contest.rated_lower is None:
+        return None
+    return (contest.rated_lower, contest.rated_upper)
+
+
 def infer_contest_type(contest: Contest) -> ContestType:
-    rated_range = _parse_rated_range(contest.rate_change)
+    rated_range = _rated_range(contest)
     if rated_range is not None:
         _lower, upper = rated_range
         if upper is None:
@@ -17,6 +17,10 @@ class Contest(BaseModel):
     duration_second: int
     title: str
     rate_change: str
+    # Parsed by the backend crawler; absent in older contests.json dumps
+    rated_lower: int | None = None
+    rated_upper: int | None = None
+    contest_type: str | None = None
 
 
 class ProblemModel(BaseModel):
@@ -14,12 +14,151 @@ CREATE TABLE submissions (
   length        INT NOT NULL,
   result        VARCHAR(255) NOT NULL,
   execution_time  INT,
+  memory          INT,
   PRIMARY KEY (id)
 );
 CREATE INDEX ON submissions (epoch_second);
 CREATE INDEX ON submissions (user_id, epoch_second ASC);
 CREATE INDEX ON submissions (LOWER(user_id), epoch_second ASC);
 
+-- Result changes of stored submissions, e.g. after a rejudge.
+DROP TABLE IF EXISTS submission_history;
+CREATE TABLE submission_history (
+  submission_id         BIGINT NOT NULL,
+  changed_epoch_second  BIGINT NOT NULL,
+  old_result            VARCHAR(255) NOT NULL,
+  new_result            VARCHAR(255) NOT NULL,
+  old_point             DOUBLE PRECISION NOT NULL,
+  new_point             DOUBLE PRECISION NOT NULL,
+  PRIMARY KEY (submission_id, changed_epoch_second)
+);
+
+DROP TABLE IF EXISTS submission_judge_cases;
+CREATE TABLE submission_judge_cases (
+  submission_id   BIGINT NOT NULL,
+  case_name       VARCHAR(255) NOT NULL,
+  verdict         VARCHAR(255) NOT NULL,
+  execution_time  INT,
+  memory          INT,
+  PRIMARY KEY (submission_id, case_name)
+);
+
+-- One row per submission whose detail page was crawled for its judge cases.
+-- status is crawled or failed; both are left out of later detail crawls.
+DROP TABLE IF EXISTS submission_details;
+CREATE TABLE submission_details (
+  submission_id         BIGINT NOT NULL,
+  status                VARCHAR(255) NOT NULL,
+  memory_kb             INT,
+  compile_error         BOOLEAN NOT NULL DEFAULT FALSE,
+  crawled_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (submission_id)
+);
+
+-- Progress of the submission crawler for each contest.
+-- max_submission_id is the newest submission stored by the last completed crawl,
+-- and last_crawled_page is the last page stored by an unfinished `all` crawl
+-- (0 when none is in progress).
+DROP TABLE IF EXISTS crawl_state;
+CREATE TABLE crawl_state (
+  contest_id                 VARCHAR(255) NOT NULL,
+  max_submission_id          BIGINT,
+  last_crawled_page          INT NOT NULL DEFAULT 0,
+  last_crawled_epoch_second  BIGINT,
+  PRIMARY KEY (contest_id)
+);
+
+-- Contests waiting to be crawled by `crawl submissions` workers.
+-- Workers claim the entries with the highest priority using FOR UPDATE SKIP LOCKED
+-- and hold them until claimed_until, so that a worker that died releases its entries.
+-- requeue marks a claimed entry enqueued again, which is crawled once more when done.
+DROP TABLE IF EXISTS crawl_queue;
+CREATE TABLE crawl_queue (
+  contest_id             VARCHAR(255) NOT NULL,
+  mode                   VARCHAR(255) NOT NULL,
+  priority               BIGINT NOT NULL,
+  attempts               INT NOT NULL DEFAULT 0,
+  claimed_by             VARCHAR(255),
+  claimed_until          BIGINT,
+  requeue                BOOLEAN NOT NULL DEFAULT FALSE,
+  enqueued_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (contest_id)
+);
+CREATE INDEX ON crawl_queue (priority DESC);
+
+-- Users whose submissions in their contests are to be crawled, queued when an
+-- AtCoder id is linked. last_contest_id is the last contest finished so far.
+DROP TABLE IF EXISTS user_crawl_jobs;
+CREATE TABLE user_crawl_jobs (
+  atcoder_user_id         VARCHAR(255) NOT NULL,
+  requested_epoch_second  BIGINT NOT NULL,
+  last_contest_id         VARCHAR(255),
+  attempts                INT NOT NULL DEFAULT 0,
+  claimed_by              VARCHAR(255),
+  claimed_until           BIGINT,
+  PRIMARY KEY (atcoder_user_id)
+);
+
+-- One row per run of `atcoder-problems crawl <target>`, written when the run
+-- starts and completed when it ends. errors counts the failed requests by
+-- CrawlerError variant, and status is running, succeeded or failed. Each crawl
+-- marks the runs left running for over a day, killed before they could finish,
+-- as abandoned.
+DROP TABLE IF EXISTS crawl_runs;
+CREATE TABLE crawl_runs (
+  id                     BIGSERIAL NOT NULL,
+  command                VARCHAR(255) NOT NULL,
+  mode                   VARCHAR(255),
+  status                 VARCHAR(255) NOT NULL,
+  started_epoch_second   BIGINT NOT NULL,
+  finished_epoch_second  BIGINT,
+  pages_fetched          BIGINT NOT NULL DEFAULT 0,
+  rows_inserted          BIGINT NOT NULL DEFAULT 0,
+  rows_updated           BIGINT NOT NULL DEFAULT 0,
+  errors                 JSONB NOT NULL DEFAULT '{}',
+  error_message          TEXT,
+  PRIMARY KEY (id)
+);
+CREATE INDEX ON crawl_runs (command, started_epoch_second DESC);
+
+-- Contests whose submissions were crawled to the end by a crawl run.
+DROP TABLE IF EXISTS crawl_run_contests;
+CREATE TABLE crawl_run_contests (
+  run_id      BIGINT NOT NULL,
+  contest_id  VARCHAR(255) NOT NULL,
+  PRIMARY KEY (run_id, contest_id)
+);
+CREATE INDEX ON crawl_run_contests (contest_id);
+
+-- AtCoder profiles of active and linked users, refreshed by `crawl users`.
+DROP TABLE IF EXISTS user_profiles;
+CREATE TABLE user_profiles (
+  user_id               VARCHAR(255) NOT NULL,
+  country               VARCHAR(255),
+  affiliation           VARCHAR(255),
+  birth_year            INT,
+  rating                INT,
+  highest_rating        INT,
+  crawled_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (user_id)
+);
+CREATE INDEX ON user_profiles (crawled_epoch_second);
+
+-- Algorithm contest results from /users/{id}/history/json.
+DROP TABLE IF EXISTS user_rating_history;
+CREATE TABLE user_rating_history (
+  user_id           VARCHAR(255) NOT NULL,
+  contest_id        VARCHAR(255) NOT NULL,
+  is_rated          BOOLEAN NOT NULL,
+  place             INT NOT NULL,
+  old_rating        INT NOT NULL,
+  new_rating        INT NOT NULL,
+  performance       INT NOT NULL,
+  inner_performance INT,
+  end_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (user_id, contest_id)
+);
+
 DROP TABLE IF EXISTS problems;
 CREATE TABLE problems (
   id            VARCHAR(255) NOT NULL,
@@ -27,9 +166,38 @@ CREATE TABLE problems (
   problem_index VARCHAR(255) NOT NULL,
   name          VARCHAR(255) NOT NULL,
   title         VARCHAR(255) NOT NULL,
+  time_limit_ms   INT,
+  memory_limit_kb INT,
+  point           DOUBLE PRECISION,
+  orphaned_epoch_second BIGINT,
   PRIMARY KEY (id)
 );
 
+-- Editorials listed on /contests/{id}/editorial, replaced per contest on each crawl.
+DROP TABLE IF EXISTS problem_editorials;
+CREATE TABLE problem_editorials (
+  problem_id    VARCHAR(255) NOT NULL,
+  editorial_url VARCHAR(1024) NOT NULL,
+  contest_id    VARCHAR(255) NOT NULL,
+  language      VARCHAR(255),
+  is_official   BOOLEAN NOT NULL,
+  PRIMARY KEY (problem_id, editorial_url)
+);
+CREATE INDEX ON problem_editorials (contest_id);
+
+-- When the editorial list of each contest was last crawled.
+DROP TABLE IF EXISTS editorial_crawls;
+CREATE TABLE editorial_crawls (
+  contest_id            VARCHAR(255) NOT NULL,
+  crawled_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (contest_id)
+);
+
+-- rated_lower, rated_upper and contest_type are parsed from rate_change by the
+-- crawler, so that the backend, dbt, and the estimator agree on which contests
+-- are rated. Rows stored before these columns existed are filled in at the
+-- start of `crawl contests`, `aggregate` and `dump-json`; run one of them before
+-- dbt, which would otherwise count these contests as unrated.
 DROP TABLE IF EXISTS contests;
 CREATE TABLE contests (
   id                    VARCHAR(255) NOT NULL,
@@ -37,6 +205,9 @@ CREATE TABLE contests (
   duration_second       BIGINT       NOT NULL,
   title                 VARCHAR(255) NOT NULL,
   rate_change           VARCHAR(255) NOT NULL,
+  rated_lower           INT,
+  rated_upper           INT,
+  contest_type          VARCHAR(255) NOT NULL DEFAULT 'other',
   PRIMARY KEY (id)
 );
 